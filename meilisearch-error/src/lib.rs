@@ -78,6 +78,16 @@ pub enum Code {
 
     DumpAlreadyInProgress,
     DumpProcessFailed,
+
+    UpdateWaitTimeout,
+
+    InvalidRule,
+    RuleNotFound,
+
+    InvalidVector,
+
+    InvalidDocumentSchema,
+    DocumentSchemaViolation,
 }
 
 impl Code {
@@ -142,6 +152,26 @@ impl Code {
             DumpProcessFailed => {
                 ErrCode::internal("dump_process_failed", StatusCode::INTERNAL_SERVER_ERROR)
             }
+
+            // thrown when an update is not processed in time for a read waiting for it
+            UpdateWaitTimeout => {
+                ErrCode::invalid("update_wait_timeout", StatusCode::REQUEST_TIMEOUT)
+            }
+
+            // error related to merchandising rules
+            InvalidRule => ErrCode::invalid("invalid_rule", StatusCode::BAD_REQUEST),
+            RuleNotFound => ErrCode::invalid("rule_not_found", StatusCode::NOT_FOUND),
+
+            // error related to the vectors of the documents and of the search
+            InvalidVector => ErrCode::invalid("invalid_vector", StatusCode::BAD_REQUEST),
+
+            // error related to the validation of the documents against the document schema
+            InvalidDocumentSchema => {
+                ErrCode::invalid("invalid_document_schema", StatusCode::BAD_REQUEST)
+            }
+            DocumentSchemaViolation => {
+                ErrCode::invalid("document_schema_violation", StatusCode::BAD_REQUEST)
+            }
         }
     }
 
@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use sha2::Digest;
 
-use crate::index::{Checked, Settings};
+use crate::index::{Checked, Rule, Rules, Settings};
 use crate::index_controller::{
     error::Result, DumpInfo, IndexController, IndexMetadata, IndexSettings, IndexStats, Stats,
 };
@@ -83,6 +83,14 @@ impl Data {
         self.index_controller.settings(uid).await
     }
 
+    pub async fn rules(&self, uid: String) -> Result<Rules> {
+        self.index_controller.rules(uid).await
+    }
+
+    pub async fn rule(&self, uid: String, id: String) -> Result<Rule> {
+        self.index_controller.rule(uid, id).await
+    }
+
     pub async fn list_indexes(&self) -> Result<Vec<IndexMetadata>> {
         self.index_controller.list_indexes().await
     }
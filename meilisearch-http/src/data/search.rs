@@ -1,23 +1,70 @@
+use std::time::Duration;
+
 use serde_json::{Map, Value};
 
 use super::Data;
-use crate::index::{SearchQuery, SearchResult};
+use crate::index::{
+    BrowseDocumentsQuery, BrowseDocumentsResult, ExportFormat, FetchDocumentsQuery,
+    FetchDocumentsResult, SearchExplanation, SearchQuery, SearchResult,
+};
 use crate::index_controller::error::Result;
+use crate::index_controller::DocumentsExport;
 
 impl Data {
-    pub async fn search(&self, index: String, search_query: SearchQuery) -> Result<SearchResult> {
+    pub async fn search(
+        &self,
+        index: String,
+        mut search_query: SearchQuery,
+    ) -> Result<SearchResult> {
+        if search_query.timeout_ms.is_none() {
+            search_query.timeout_ms = self.options.search_timeout_ms;
+        }
         self.index_controller.search(index, search_query).await
     }
 
+    /// Waits for the update `update_id` of the index to be processed, so that the reads that
+    /// follow see its changes.
+    pub async fn wait_for_update(&self, index: String, update_id: u64) -> Result<()> {
+        let timeout = Duration::from_millis(self.options.wait_for_update_timeout_ms);
+        self.index_controller
+            .wait_update(index, update_id, timeout)
+            .await
+    }
+
+    pub async fn explain_search(
+        &self,
+        index: String,
+        search_query: SearchQuery,
+    ) -> Result<SearchExplanation> {
+        self.index_controller
+            .explain_search(index, search_query)
+            .await
+    }
+
     pub async fn retrieve_documents(
         &self,
         index: String,
-        offset: usize,
-        limit: usize,
-        attributes_to_retrieve: Option<Vec<String>>,
-    ) -> Result<Vec<Map<String, Value>>> {
+        query: BrowseDocumentsQuery,
+    ) -> Result<BrowseDocumentsResult> {
+        self.index_controller.documents(index, query).await
+    }
+
+    pub async fn fetch_documents(
+        &self,
+        index: String,
+        query: FetchDocumentsQuery,
+    ) -> Result<FetchDocumentsResult> {
+        self.index_controller.fetch_documents(index, query).await
+    }
+
+    pub async fn export_documents(
+        &self,
+        index: String,
+        filter: Option<Value>,
+        format: ExportFormat,
+    ) -> Result<DocumentsExport> {
         self.index_controller
-            .documents(index, offset, limit, attributes_to_retrieve)
+            .export_documents(index, filter, format)
             .await
     }
 
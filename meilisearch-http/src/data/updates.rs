@@ -1,7 +1,8 @@
-use milli::update::{IndexDocumentsMethod, UpdateFormat};
+use milli::update::IndexDocumentsMethod;
 
+use crate::extractors::documents_format::DocumentsFormat;
 use crate::extractors::payload::Payload;
-use crate::index::{Checked, Settings};
+use crate::index::{Checked, Rule, Settings};
 use crate::index_controller::{error::Result, IndexMetadata, IndexSettings, UpdateStatus};
 use crate::Data;
 
@@ -10,7 +11,7 @@ impl Data {
         &self,
         index: String,
         method: IndexDocumentsMethod,
-        format: UpdateFormat,
+        format: DocumentsFormat,
         stream: Payload,
         primary_key: Option<String>,
     ) -> Result<UpdateStatus> {
@@ -51,6 +52,28 @@ impl Data {
         Ok(update)
     }
 
+    pub async fn delete_documents_by_filter(
+        &self,
+        index: String,
+        filter: serde_json::Value,
+    ) -> Result<UpdateStatus> {
+        let update = self
+            .index_controller
+            .delete_documents_by_filter(index, filter)
+            .await?;
+        Ok(update)
+    }
+
+    pub async fn put_rule(&self, index: String, id: String, rule: Rule) -> Result<UpdateStatus> {
+        let update = self.index_controller.put_rule(index, id, rule).await?;
+        Ok(update)
+    }
+
+    pub async fn delete_rule(&self, index: String, id: String) -> Result<UpdateStatus> {
+        let update = self.index_controller.delete_rule(index, id).await?;
+        Ok(update)
+    }
+
     pub async fn delete_index(&self, index: String) -> Result<()> {
         self.index_controller.delete_index(index).await?;
         Ok(())
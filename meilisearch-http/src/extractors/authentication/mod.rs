@@ -130,6 +130,20 @@ pub enum AuthConfig {
     Auth(Policies),
 }
 
+impl AuthConfig {
+    /// Returns whether `token` is accepted by the policy `P`, for the requests that can't be
+    /// authenticated by their headers.
+    pub fn authenticate<P: Policy + 'static>(&self, token: Option<&[u8]>) -> bool {
+        match self {
+            AuthConfig::NoAuth => true,
+            AuthConfig::Auth(policies) => match (policies.get::<P>(), token) {
+                (Some(policy), Some(token)) => policy.authenticate(token),
+                _ => false,
+            },
+        }
+    }
+}
+
 impl Default for AuthConfig {
     fn default() -> Self {
         Self::NoAuth
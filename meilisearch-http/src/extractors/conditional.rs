@@ -0,0 +1,140 @@
+use std::fmt::Debug;
+use std::hash::Hasher;
+
+use actix_web::http::header::{HeaderValue, ETAG, IF_NONE_MATCH, VARY};
+use actix_web::{dev, FromRequest, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use futures::future::{ok, Ready};
+use siphasher::sip::SipHasher;
+
+use crate::error::ResponseError;
+use crate::extractors::response_format::ResponseFormat;
+
+/// The conditional part of a read request: its `If-None-Match` header, and what identifies the
+/// representation it asks for besides its parameters, the path and the negotiated format.
+#[derive(Debug)]
+pub struct Conditional {
+    path: String,
+    format: ResponseFormat,
+    if_none_match: Option<String>,
+}
+
+/// A strong entity tag, derived from the version of the index and the normalised request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ETag(String);
+
+impl Conditional {
+    /// Returns the entity tag of the response to this request, on the version of the index last
+    /// updated at `updated_at`.
+    ///
+    /// `request` is the parsed form of the parameters of the request, its debug representation
+    /// doesn't depend on the order of the parameters, their encoding or the defaults that were
+    /// left out, so equivalent requests get the same tag.
+    pub fn etag(&self, updated_at: &DateTime<Utc>, request: &impl Debug) -> ETag {
+        let mut hasher = SipHasher::new();
+        hasher.write(self.path.as_bytes());
+        hasher.write(format!("{:?}", self.format).as_bytes());
+        hasher.write_i64(updated_at.timestamp_nanos());
+        hasher.write(format!("{:?}", request).as_bytes());
+        ETag(format!("\"{:016x}\"", hasher.finish()))
+    }
+
+    /// Whether the client already has the representation tagged `etag`. The tags are compared
+    /// with the weak comparison, as mandated for `If-None-Match`.
+    pub fn is_fresh(&self, etag: &ETag) -> bool {
+        self.if_none_match.as_deref().map_or(false, |tags| {
+            tags.split(',')
+                .map(str::trim)
+                .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag.0)
+        })
+    }
+}
+
+impl ETag {
+    /// The tag depends on the negotiated format, so both the tagged responses and the
+    /// `304 Not Modified` ones vary with the `Accept` header.
+    pub fn not_modified(&self) -> HttpResponse {
+        HttpResponse::NotModified()
+            .insert_header((ETAG, self.0.as_str()))
+            .insert_header((VARY, "Accept"))
+            .finish()
+    }
+
+    pub fn tag(&self, mut response: HttpResponse) -> HttpResponse {
+        if let Ok(value) = HeaderValue::from_str(&self.0) {
+            response.headers_mut().insert(ETAG, value);
+        }
+        response
+            .headers_mut()
+            .insert(VARY, HeaderValue::from_static("Accept"));
+        response
+    }
+}
+
+impl FromRequest for Conditional {
+    type Config = ();
+
+    type Error = ResponseError;
+
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut dev::Payload) -> Self::Future {
+        let if_none_match = req
+            .headers()
+            .get(IF_NONE_MATCH)
+            .and_then(|tags| tags.to_str().ok())
+            .map(String::from);
+        ok(Self {
+            path: req.path().to_string(),
+            format: ResponseFormat::negotiate(req),
+            if_none_match,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn conditional(if_none_match: Option<&str>) -> Conditional {
+        Conditional {
+            path: "/indexes/movies/search".to_string(),
+            format: ResponseFormat::Json,
+            if_none_match: if_none_match.map(String::from),
+        }
+    }
+
+    #[test]
+    fn etag_depends_on_version_and_request() {
+        let conditional = conditional(None);
+        let version = Utc.timestamp(1609459200, 0);
+        let etag = conditional.etag(&version, &("shirt", 20));
+
+        assert_eq!(etag, conditional.etag(&version, &("shirt", 20)));
+        assert_ne!(etag, conditional.etag(&version, &("shirt", 10)));
+        assert_ne!(
+            etag,
+            conditional.etag(&Utc.timestamp(1609459201, 0), &("shirt", 20))
+        );
+
+        let csv = Conditional {
+            format: ResponseFormat::Csv,
+            ..conditional
+        };
+        assert_ne!(etag, csv.etag(&version, &("shirt", 20)));
+    }
+
+    #[test]
+    fn match_if_none_match() {
+        let version = Utc.timestamp(1609459200, 0);
+        let etag = conditional(None).etag(&version, &());
+
+        assert!(!conditional(None).is_fresh(&etag));
+        assert!(conditional(Some("*")).is_fresh(&etag));
+        assert!(conditional(Some(&etag.0)).is_fresh(&etag));
+        assert!(conditional(Some(&format!("\"other\", W/{}", etag.0))).is_fresh(&etag));
+        assert!(!conditional(Some("\"other\"")).is_fresh(&etag));
+    }
+}
@@ -0,0 +1,143 @@
+use std::borrow::Cow;
+
+use actix_web::http::header::{HeaderName, CONTENT_ENCODING, CONTENT_TYPE};
+use actix_web::{dev, FromRequest, HttpRequest};
+use futures::future::{ready, Ready};
+use meilisearch_error::{Code, ErrorCode};
+use milli::update::UpdateFormat;
+
+use crate::error::ResponseError;
+use crate::index::ContentEncoding;
+
+/// The media types of the documents sent to the documents routes.
+const ACCEPTED_MEDIA_TYPES: &[&str] = &["application/json", "application/x-ndjson", "text/csv"];
+/// The compressions of the documents sent to the documents routes.
+const ACCEPTED_ENCODINGS: &[&str] = &["gzip", "br", "zstd", "deflate"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum DocumentsFormatError {
+    #[error(
+        "The Content-Type `{0}` is not supported, the accepted ones are: {}.",
+        ACCEPTED_MEDIA_TYPES.join(", ")
+    )]
+    UnsupportedMediaType(String),
+    #[error(
+        "The Content-Encoding `{0}` is not supported, the accepted ones are: {}.",
+        ACCEPTED_ENCODINGS.join(", ")
+    )]
+    UnsupportedEncoding(String),
+    #[error("The CSV delimiter must be a single ASCII character, found: `{0}`.")]
+    InvalidCsvDelimiter(String),
+}
+
+impl ErrorCode for DocumentsFormatError {
+    fn error_code(&self) -> Code {
+        match self {
+            DocumentsFormatError::UnsupportedMediaType(_) => Code::UnsupportedMediaType,
+            DocumentsFormatError::UnsupportedEncoding(_) => Code::UnsupportedMediaType,
+            DocumentsFormatError::InvalidCsvDelimiter(_) => Code::BadParameter,
+        }
+    }
+}
+
+/// The format of the documents sent to the documents routes, read from the `Content-Type` and
+/// `Content-Encoding` headers of the request. The documents are uncompressed JSON when the
+/// headers are missing. The CSV delimiter is a parameter of the routes, see
+/// [`parse_csv_delimiter`].
+#[derive(Debug, Clone, Copy)]
+pub struct DocumentsFormat {
+    pub format: UpdateFormat,
+    pub encoding: ContentEncoding,
+    pub csv_delimiter: Option<u8>,
+}
+
+fn format_from_content_type(content_type: &str) -> Result<UpdateFormat, DocumentsFormatError> {
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+    match media_type.to_lowercase().as_str() {
+        "application/json" => Ok(UpdateFormat::Json),
+        "application/x-ndjson" => Ok(UpdateFormat::JsonStream),
+        "text/csv" => Ok(UpdateFormat::Csv),
+        _ => Err(DocumentsFormatError::UnsupportedMediaType(
+            content_type.to_string(),
+        )),
+    }
+}
+
+fn header<'a>(req: &'a HttpRequest, name: HeaderName) -> Option<Cow<'a, str>> {
+    req.headers()
+        .get(name)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()))
+}
+
+/// Parses the `csvDelimiter` parameter of the documents routes.
+pub fn parse_csv_delimiter(delimiter: Option<&str>) -> Result<Option<u8>, DocumentsFormatError> {
+    match delimiter {
+        None => Ok(None),
+        Some(s) => match s.as_bytes() {
+            [byte] if byte.is_ascii() => Ok(Some(*byte)),
+            _ => Err(DocumentsFormatError::InvalidCsvDelimiter(s.to_string())),
+        },
+    }
+}
+
+impl FromRequest for DocumentsFormat {
+    type Config = ();
+
+    type Error = ResponseError;
+
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut dev::Payload) -> Self::Future {
+        let format = || -> Result<Self, DocumentsFormatError> {
+            let format = match header(req, CONTENT_TYPE) {
+                Some(content_type) => format_from_content_type(&content_type)?,
+                None => UpdateFormat::Json,
+            };
+            let encoding = match header(req, CONTENT_ENCODING) {
+                Some(encoding) => ContentEncoding::from_header(&encoding).ok_or_else(|| {
+                    DocumentsFormatError::UnsupportedEncoding(encoding.into_owned())
+                })?,
+                None => ContentEncoding::Identity,
+            };
+            Ok(Self {
+                format,
+                encoding,
+                csv_delimiter: None,
+            })
+        };
+        ready(format().map_err(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn documents_format() {
+        assert!(matches!(
+            format_from_content_type("application/json; charset=utf-8"),
+            Ok(UpdateFormat::Json)
+        ));
+        assert!(matches!(
+            format_from_content_type("application/x-ndjson"),
+            Ok(UpdateFormat::JsonStream)
+        ));
+        assert!(matches!(
+            format_from_content_type("Text/CSV"),
+            Ok(UpdateFormat::Csv)
+        ));
+        assert!(matches!(
+            format_from_content_type("text/plain"),
+            Err(DocumentsFormatError::UnsupportedMediaType(_))
+        ));
+    }
+
+    #[test]
+    fn csv_delimiter() {
+        assert_eq!(parse_csv_delimiter(None).unwrap(), None);
+        assert_eq!(parse_csv_delimiter(Some(";")).unwrap(), Some(b';'));
+        assert!(parse_csv_delimiter(Some(";;")).is_err());
+        assert!(parse_csv_delimiter(Some("é")).is_err());
+    }
+}
@@ -1,3 +1,6 @@
+pub mod conditional;
+pub mod documents_format;
 pub mod payload;
+pub mod response_format;
 #[macro_use]
 pub mod authentication;
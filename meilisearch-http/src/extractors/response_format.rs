@@ -0,0 +1,254 @@
+use std::error::Error;
+
+use actix_web::http::header::{ACCEPT, VARY};
+use actix_web::{dev, FromRequest, HttpRequest, HttpResponse};
+use futures::future::{ok, Ready};
+use indexmap::IndexSet;
+use meilisearch_error::{Code, ErrorCode};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::error::ResponseError;
+use crate::index::{BrowseDocumentsResult, Document, SearchHit, SearchResult};
+
+#[derive(Debug, thiserror::Error)]
+pub enum EncodingError {
+    #[error("Internal error: {0}")]
+    Internal(Box<dyn Error + Send + Sync + 'static>),
+}
+
+internal_error!(
+    EncodingError: csv::Error,
+    rmp_serde::encode::Error,
+    serde_json::Error,
+    std::io::Error
+);
+
+impl ErrorCode for EncodingError {
+    fn error_code(&self) -> Code {
+        match self {
+            EncodingError::Internal(_) => Code::Internal,
+        }
+    }
+}
+
+/// A response made of a list of rows, the documents, that can be encoded line by line in the
+/// NDJSON and CSV formats.
+pub trait Rows: Serialize {
+    type Row: Serialize;
+
+    fn rows(&self) -> &[Self::Row];
+}
+
+impl Rows for SearchResult {
+    type Row = SearchHit;
+
+    fn rows(&self) -> &[SearchHit] {
+        &self.hits
+    }
+}
+
+impl Rows for BrowseDocumentsResult {
+    type Row = Document;
+
+    fn rows(&self) -> &[Document] {
+        &self.results
+    }
+}
+
+impl<T: Serialize> Rows for Vec<T> {
+    type Row = T;
+
+    fn rows(&self) -> &[T] {
+        self
+    }
+}
+
+impl Rows for Map<String, Value> {
+    type Row = Self;
+
+    fn rows(&self) -> &[Self] {
+        std::slice::from_ref(self)
+    }
+}
+
+/// The encoding of the response of a read route, negotiated from the `Accept` header of the
+/// request. JSON is used when the header is missing or doesn't accept any other format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResponseFormat {
+    Json,
+    Ndjson,
+    Csv,
+    MessagePack,
+}
+
+impl ResponseFormat {
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type {
+            "application/json" | "application/*" | "*/*" => Some(Self::Json),
+            "application/x-ndjson" => Some(Self::Ndjson),
+            "text/csv" => Some(Self::Csv),
+            "application/msgpack" | "application/x-msgpack" => Some(Self::MessagePack),
+            _ => None,
+        }
+    }
+
+    /// Returns the supported format with the highest quality in the `Accept` header.
+    fn from_accept(accept: &str) -> Self {
+        let mut ranges: Vec<_> = accept
+            .split(',')
+            .filter_map(|range| {
+                let mut params = range.split(';').map(str::trim);
+                let format = Self::from_media_type(&params.next()?.to_lowercase())?;
+                let quality = params
+                    .find_map(|param| param.strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.parse::<f32>().ok())?;
+                Some((format, quality))
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect();
+
+        // The sort is stable, the first of the formats with the same quality is preferred.
+        ranges.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+        ranges.first().map_or(Self::Json, |(format, _)| *format)
+    }
+
+    pub fn negotiate(req: &HttpRequest) -> Self {
+        req.headers()
+            .get(ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .map_or(Self::Json, Self::from_accept)
+    }
+
+    /// Encodes the `payload` in the negotiated format. The CSV columns are the given `columns`,
+    /// or all the fields of the rows, in order of appearance.
+    ///
+    /// The response varies with the `Accept` header, which caches are told about.
+    pub fn respond<T: Rows>(
+        self,
+        payload: &T,
+        columns: Option<&[String]>,
+    ) -> Result<HttpResponse, ResponseError> {
+        let mut response = HttpResponse::Ok();
+        response.insert_header((VARY, "Accept"));
+        match self {
+            Self::Json => Ok(response.json(payload)),
+            Self::Ndjson => {
+                let mut body = Vec::new();
+                for row in payload.rows() {
+                    serde_json::to_writer(&mut body, row).map_err(EncodingError::from)?;
+                    body.push(b'\n');
+                }
+                Ok(response.content_type("application/x-ndjson").body(body))
+            }
+            Self::Csv => {
+                let body = encode_csv(payload.rows(), columns)?;
+                Ok(response.content_type("text/csv").body(body))
+            }
+            Self::MessagePack => {
+                let body = rmp_serde::to_vec_named(payload).map_err(EncodingError::from)?;
+                Ok(response.content_type("application/msgpack").body(body))
+            }
+        }
+    }
+}
+
+fn encode_csv<R: Serialize>(
+    rows: &[R],
+    columns: Option<&[String]>,
+) -> Result<Vec<u8>, EncodingError> {
+    let rows = rows
+        .iter()
+        .map(|row| match serde_json::to_value(row)? {
+            Value::Object(object) => Ok(object),
+            _ => Ok(Map::new()),
+        })
+        .collect::<Result<Vec<_>, EncodingError>>()?;
+
+    let columns: IndexSet<&str> = match columns {
+        Some(columns) if !columns.iter().any(|c| c == "*") => {
+            columns.iter().map(String::as_str).collect()
+        }
+        _ => rows
+            .iter()
+            .flat_map(|row| row.keys().map(String::as_str))
+            .collect(),
+    };
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&columns)?;
+    for row in &rows {
+        let record = columns.iter().map(|column| match row.get(*column) {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+        });
+        writer.write_record(record)?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| EncodingError::Internal(e.to_string().into()))
+}
+
+impl FromRequest for ResponseFormat {
+    type Config = ();
+
+    type Error = ResponseError;
+
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut dev::Payload) -> Self::Future {
+        ok(Self::negotiate(req))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn negotiate_format() {
+        assert_eq!(ResponseFormat::from_accept("*/*"), ResponseFormat::Json);
+        assert_eq!(
+            ResponseFormat::from_accept("text/html"),
+            ResponseFormat::Json
+        );
+        assert_eq!(ResponseFormat::from_accept("text/csv"), ResponseFormat::Csv);
+        assert_eq!(
+            ResponseFormat::from_accept("application/json;q=0.5, application/x-ndjson"),
+            ResponseFormat::Ndjson
+        );
+        assert_eq!(
+            ResponseFormat::from_accept("application/msgpack;q=0, text/csv;q=0.2"),
+            ResponseFormat::Csv
+        );
+        assert_eq!(
+            ResponseFormat::from_accept("application/x-msgpack, text/csv"),
+            ResponseFormat::MessagePack
+        );
+    }
+
+    #[test]
+    fn csv_columns() {
+        let rows = vec![
+            json!({ "id": 1, "title": "shirt, red", "tags": ["a", "b"] }),
+            json!({ "id": 2, "color": null, "price": 12.5 }),
+        ];
+
+        let csv = encode_csv(&rows, None).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "id,title,tags,color,price\n1,\"shirt, red\",\"[\"\"a\"\",\"\"b\"\"]\",,\n2,,,,12.5\n"
+        );
+
+        let columns = vec!["title".to_string(), "id".to_string()];
+        let csv = encode_csv(&rows, Some(&columns)).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "title,id\n\"shirt, red\",1\n,2\n"
+        );
+    }
+}
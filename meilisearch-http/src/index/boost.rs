@@ -0,0 +1,174 @@
+use std::ops::Range;
+
+use heed::RoTxn;
+use milli::{Criterion, DocumentId};
+use roaring::RoaringBitmap;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::error::{IndexError, Result};
+use super::search::parse_filter;
+use super::Index;
+
+/// The number of top ranked documents the boosts can reorder. Only the ranking buckets that end
+/// within this window are reordered, whatever the page, which keeps the pagination consistent.
+pub const BOOST_WINDOW_SIZE: usize = 1000;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Boost {
+    pub filter: Value,
+    pub weight: f64,
+}
+
+/// The documents matched by each boost filter, along with the boost weight.
+#[derive(Debug, Default)]
+pub struct Boosts(Vec<(RoaringBitmap, f64)>);
+
+impl Boosts {
+    /// The boost of a document is the product of the weights of the filters it matches.
+    fn score(&self, id: DocumentId) -> f64 {
+        self.0
+            .iter()
+            .filter(|(docids, _)| docids.contains(id))
+            .map(|(_, weight)| weight)
+            .product()
+    }
+
+    /// Sorts the documents of each ranking bucket by decreasing boost, the boost being a last
+    /// ranking rule. The sort is stable, so the documents with the same boost keep their ranking
+    /// order.
+    pub fn apply(&self, ranked: &mut [DocumentId], buckets: &[Range<usize>]) {
+        for bucket in buckets {
+            ranked[bucket.clone()]
+                .sort_by(|a, b| self.score(*b).partial_cmp(&self.score(*a)).unwrap());
+        }
+    }
+}
+
+impl Index {
+    pub fn compile_boosts(&self, txn: &RoTxn, boosts: &[Boost]) -> Result<Boosts> {
+        let mut compiled = Vec::new();
+        for Boost { filter, weight } in boosts {
+            if !weight.is_finite() || *weight <= 0.0 {
+                return Err(IndexError::InvalidBoostWeight(*weight));
+            }
+
+            if let Some(condition) = parse_filter(filter, self, txn)? {
+                let docids = condition.evaluate(txn, self)?;
+                compiled.push((docids, *weight));
+            }
+        }
+
+        Ok(Boosts(compiled))
+    }
+
+    /// Whether the ranking buckets of a search for `query` can be told apart, so that it can be
+    /// boosted.
+    ///
+    /// milli doesn't expose its buckets. Without a query, or when the ranking rules are only
+    /// `asc` and `desc` rules, the buckets are made of the documents having the same sorted
+    /// values, which can be read. The buckets of the textual rules depend on where the words of
+    /// the query are found in the documents, they can't be known.
+    pub fn boostable(&self, txn: &RoTxn, query: Option<&str>) -> Result<bool> {
+        if query.map_or(true, |query| query.trim().is_empty()) {
+            return Ok(true);
+        }
+
+        let textual = self
+            .criteria(txn)?
+            .iter()
+            .any(|criterion| !matches!(criterion, Criterion::Asc(_) | Criterion::Desc(_)));
+        Ok(!textual)
+    }
+
+    /// Splits the `ranked` documents of a [boostable](Index::boostable) search in the ranking
+    /// buckets milli put them in: a change of the values sorted by the `asc` and `desc` rules
+    /// starts a new bucket, as does a decreasing id, milli ranking the documents of a bucket by
+    /// increasing id. The last bucket may continue past the ranked documents.
+    pub fn ranking_buckets(&self, txn: &RoTxn, ranked: &[DocumentId]) -> Result<Vec<Range<usize>>> {
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let sorted_ids: Vec<_> = self
+            .criteria(txn)?
+            .into_iter()
+            .filter_map(|criterion| match criterion {
+                Criterion::Asc(field) | Criterion::Desc(field) => fields_ids_map.id(&field),
+                _ => None,
+            })
+            .collect();
+
+        let mut sorted_values = Vec::with_capacity(ranked.len());
+        if !sorted_ids.is_empty() {
+            for (_, obkv) in self.documents(txn, ranked.iter().copied())? {
+                let mut values = Vec::with_capacity(sorted_ids.len());
+                for id in &sorted_ids {
+                    let value: Option<Value> = match obkv.get(*id) {
+                        Some(bytes) => Some(serde_json::from_slice(bytes)?),
+                        None => None,
+                    };
+                    values.push(value);
+                }
+                sorted_values.push(values);
+            }
+        }
+
+        let same_bucket = |i: usize| {
+            ranked[i - 1] < ranked[i]
+                && sorted_values.get(i - 1).map_or(true, |previous| {
+                    previous
+                        .iter()
+                        .zip(&sorted_values[i])
+                        .all(|(a, b)| same_sorted_value(a, b))
+                })
+        };
+
+        let mut buckets = Vec::new();
+        let mut start = 0;
+        for i in 1..ranked.len() {
+            if !same_bucket(i) {
+                buckets.push(start..i);
+                start = i;
+            }
+        }
+        if start < ranked.len() {
+            buckets.push(start..ranked.len());
+        }
+
+        Ok(buckets)
+    }
+}
+
+/// The `asc` and `desc` rules compare the numbers by value, `1` and `1.0` are the same.
+fn same_sorted_value(a: &Option<Value>, b: &Option<Value>) -> bool {
+    match (a, b) {
+        (Some(Value::Number(a)), Some(Value::Number(b))) => a.as_f64() == b.as_f64(),
+        (a, b) => a == b,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn boost_within_bucket() {
+        let in_stock: RoaringBitmap = vec![3, 4].into_iter().collect();
+        let sponsored: RoaringBitmap = vec![4, 5].into_iter().collect();
+        let discontinued: RoaringBitmap = vec![1].into_iter().collect();
+        let boosts = Boosts(vec![(in_stock, 2.0), (sponsored, 1.5), (discontinued, 0.5)]);
+
+        let mut ranked = vec![1, 2, 3, 4, 5, 6];
+        boosts.apply(&mut ranked, &[0..6]);
+        assert_eq!(ranked, vec![4, 3, 5, 2, 6, 1]);
+    }
+
+    #[test]
+    fn boost_never_crosses_buckets() {
+        let in_stock: RoaringBitmap = vec![2, 6].into_iter().collect();
+        let boosts = Boosts(vec![(in_stock, 2.0)]);
+
+        let mut ranked = vec![1, 2, 3, 4, 5, 6];
+        boosts.apply(&mut ranked, &[0..1, 1..4, 4..6]);
+        assert_eq!(ranked, vec![1, 2, 3, 4, 6, 5]);
+    }
+}
@@ -0,0 +1,211 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use flate2::read::{MultiGzDecoder, ZlibDecoder};
+use serde::{Deserialize, Serialize};
+
+/// The compression of the documents of an addition, from the `Content-Encoding` header of the
+/// request. The documents are stored compressed in the update files and decompressed while they
+/// are indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentEncoding {
+    Identity,
+    Gzip,
+    Brotli,
+    Zstd,
+    Deflate,
+}
+
+impl ContentEncoding {
+    /// Parses a `Content-Encoding` header, returns `None` if the encoding is not supported.
+    /// The content can only be compressed once.
+    pub fn from_header(header: &str) -> Option<Self> {
+        match header.trim().to_lowercase().as_str() {
+            "" | "identity" => Some(Self::Identity),
+            "gzip" | "x-gzip" => Some(Self::Gzip),
+            "br" => Some(Self::Brotli),
+            "zstd" => Some(Self::Zstd),
+            "deflate" => Some(Self::Deflate),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ContentEncoding {
+    fn default() -> Self {
+        Self::Identity
+    }
+}
+
+/// Returns a reader decompressing `content`. The `deflate` encoding is the zlib format.
+pub fn decode<'a>(
+    content: impl Read + 'a,
+    encoding: ContentEncoding,
+) -> io::Result<Box<dyn Read + 'a>> {
+    let reader: Box<dyn Read + 'a> = match encoding {
+        ContentEncoding::Identity => Box::new(content),
+        ContentEncoding::Gzip => Box::new(MultiGzDecoder::new(content)),
+        ContentEncoding::Brotli => Box::new(brotli2::read::BrotliDecoder::new(content)),
+        ContentEncoding::Zstd => Box::new(zstd::stream::read::Decoder::new(content)?),
+        ContentEncoding::Deflate => Box::new(ZlibDecoder::new(content)),
+    };
+    Ok(reader)
+}
+
+/// Returns a reader decompressing the `content` of an update file, as it is read.
+pub fn decode_file(content: File, encoding: ContentEncoding) -> io::Result<DecodedFile> {
+    let reader = decode(content.try_clone()?, encoding)?;
+    Ok(DecodedFile {
+        file: content,
+        encoding,
+        reader,
+    })
+}
+
+/// The decompressed content of an update file. The documents of an addition may be read twice,
+/// so the reader can be rewound: the file is then decompressed again from its start, rather than
+/// being decompressed once to a temporary file.
+pub struct DecodedFile {
+    file: File,
+    encoding: ContentEncoding,
+    reader: Box<dyn Read>,
+}
+
+impl Read for DecodedFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl Seek for DecodedFile {
+    /// Only rewinding is supported, the decompressed content can't be sought in.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        if pos != SeekFrom::Start(0) {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "a decompressed update file can only be rewound",
+            ));
+        }
+
+        // The clones of the file share its offset.
+        self.file.seek(SeekFrom::Start(0))?;
+        self.reader = decode(self.file.try_clone()?, self.encoding)?;
+        Ok(0)
+    }
+}
+
+/// Returns the decompressed size of `content`, or `None` if it is larger than `limit`.
+pub fn decoded_size(
+    content: impl Read,
+    encoding: ContentEncoding,
+    limit: usize,
+) -> io::Result<Option<u64>> {
+    let limit = limit as u64;
+    let size = io::copy(
+        &mut decode(content, encoding)?.take(limit + 1),
+        &mut io::sink(),
+    )?;
+    Ok(if size > limit { None } else { Some(size) })
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use flate2::write::{GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+
+    use super::*;
+
+    const CONTENT: &[u8] = br#"[{ "id": 1, "title": "red shirt" }]"#;
+
+    fn decoded(content: &[u8], encoding: ContentEncoding) -> Vec<u8> {
+        let mut decoded = Vec::new();
+        decode(content, encoding)
+            .unwrap()
+            .read_to_end(&mut decoded)
+            .unwrap();
+        decoded
+    }
+
+    #[test]
+    fn decode_encodings() {
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(CONTENT).unwrap();
+        assert_eq!(
+            decoded(&gzip.finish().unwrap(), ContentEncoding::Gzip),
+            CONTENT
+        );
+
+        let mut deflate = ZlibEncoder::new(Vec::new(), Compression::default());
+        deflate.write_all(CONTENT).unwrap();
+        assert_eq!(
+            decoded(&deflate.finish().unwrap(), ContentEncoding::Deflate),
+            CONTENT
+        );
+
+        let mut brotli = brotli2::write::BrotliEncoder::new(Vec::new(), 6);
+        brotli.write_all(CONTENT).unwrap();
+        assert_eq!(
+            decoded(&brotli.finish().unwrap(), ContentEncoding::Brotli),
+            CONTENT
+        );
+
+        let zstd = zstd::stream::encode_all(CONTENT, 0).unwrap();
+        assert_eq!(decoded(&zstd, ContentEncoding::Zstd), CONTENT);
+    }
+
+    #[test]
+    fn rewind_decoded_file() {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&zstd::stream::encode_all(CONTENT, 0).unwrap())
+            .unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        let mut decoded = decode_file(file, ContentEncoding::Zstd).unwrap();
+        for _ in 0..2 {
+            let mut content = Vec::new();
+            decoded.read_to_end(&mut content).unwrap();
+            assert_eq!(content, CONTENT);
+            decoded.seek(SeekFrom::Start(0)).unwrap();
+        }
+        assert!(decoded.seek(SeekFrom::Current(1)).is_err());
+    }
+
+    #[test]
+    fn limit_decoded_size() {
+        let zeros = vec![0; 10_000];
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::best());
+        gzip.write_all(&zeros).unwrap();
+        let gzip = gzip.finish().unwrap();
+
+        assert!(gzip.len() < 1000);
+        assert_eq!(
+            decoded_size(&gzip[..], ContentEncoding::Gzip, 10_000).unwrap(),
+            Some(10_000)
+        );
+        assert_eq!(
+            decoded_size(&gzip[..], ContentEncoding::Gzip, 9_999).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_content_encoding() {
+        assert_eq!(
+            ContentEncoding::from_header("identity"),
+            Some(ContentEncoding::Identity)
+        );
+        assert_eq!(
+            ContentEncoding::from_header("GZIP"),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(
+            ContentEncoding::from_header("br"),
+            Some(ContentEncoding::Brotli)
+        );
+        assert_eq!(ContentEncoding::from_header("gzip, br"), None);
+        assert_eq!(ContentEncoding::from_header("compress"), None);
+    }
+}
@@ -0,0 +1,168 @@
+use std::io;
+
+use serde_json::{Map, Number, Value};
+
+use super::error::{DocumentFormatError, Result};
+
+pub const DEFAULT_CSV_DELIMITER: u8 = b',';
+
+/// The type of the values of a CSV column, declared in its header as `name:type`. The values of
+/// the columns without a known type are strings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnType {
+    String,
+    Number,
+    Boolean,
+}
+
+impl ColumnType {
+    fn name(self) -> &'static str {
+        match self {
+            ColumnType::String => "string",
+            ColumnType::Number => "number",
+            ColumnType::Boolean => "boolean",
+        }
+    }
+}
+
+fn parse_header(header: &str) -> (String, ColumnType) {
+    if let Some((name, kind)) = header.rsplit_once(':') {
+        let kind = match kind.trim() {
+            "string" => Some(ColumnType::String),
+            "number" => Some(ColumnType::Number),
+            "boolean" => Some(ColumnType::Boolean),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            return (name.trim().to_string(), kind);
+        }
+    }
+    (header.trim().to_string(), ColumnType::String)
+}
+
+/// Parses a CSV value, empty numbers and booleans are null.
+fn parse_value(value: &str, kind: ColumnType) -> Option<Value> {
+    match kind {
+        ColumnType::String => Some(Value::String(value.to_string())),
+        _ if value.trim().is_empty() => Some(Value::Null),
+        ColumnType::Number => {
+            let value = value.trim();
+            match value.parse::<i64>() {
+                Ok(n) => Some(Value::from(n)),
+                Err(_) => value
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(Number::from_f64)
+                    .map(Value::Number),
+            }
+        }
+        ColumnType::Boolean => value.trim().parse::<bool>().ok().map(Value::Bool),
+    }
+}
+
+/// The documents of a CSV payload, one per record, typed from the headers of its columns.
+pub struct CsvDocuments<R> {
+    reader: csv::Reader<R>,
+    columns: Vec<(String, ColumnType)>,
+    record: csv::StringRecord,
+}
+
+impl<R: io::Read> CsvDocuments<R> {
+    pub fn new(content: R, delimiter: u8) -> Result<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .from_reader(content);
+        let columns = reader
+            .headers()
+            .map_err(DocumentFormatError::from)?
+            .iter()
+            .map(parse_header)
+            .collect();
+
+        Ok(Self {
+            reader,
+            columns,
+            record: csv::StringRecord::new(),
+        })
+    }
+
+    fn next_document(&mut self) -> Result<Option<Map<String, Value>>> {
+        if !self
+            .reader
+            .read_record(&mut self.record)
+            .map_err(DocumentFormatError::from)?
+        {
+            return Ok(None);
+        }
+
+        let line = self.record.position().map_or(0, |p| p.line());
+        let mut document = Map::new();
+        for ((name, kind), value) in self.columns.iter().zip(&self.record) {
+            let value =
+                parse_value(value, *kind).ok_or_else(|| DocumentFormatError::InvalidCsvValue {
+                    line,
+                    column: name.clone(),
+                    expected: kind.name(),
+                    value: value.to_string(),
+                })?;
+            document.insert(name.clone(), value);
+        }
+
+        Ok(Some(document))
+    }
+}
+
+impl<R: io::Read> Iterator for CsvDocuments<R> {
+    type Item = Result<Map<String, Value>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_document().transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn parse(csv: &str, delimiter: u8) -> Result<Vec<Value>> {
+        CsvDocuments::new(csv.as_bytes(), delimiter)?
+            .map(|document| document.map(Value::Object))
+            .collect()
+    }
+
+    #[test]
+    fn typed_headers() {
+        let csv = "id:number,title,price:number,available:boolean,tag:unknown\n\
+                   1,red shirt,12.5,true,a\n\
+                   2,\"blue, shirt\",,false,b\n";
+
+        assert_eq!(
+            parse(csv, b',').unwrap(),
+            vec![
+                json!({ "id": 1, "title": "red shirt", "price": 12.5, "available": true, "tag:unknown": "a" }),
+                json!({ "id": 2, "title": "blue, shirt", "price": null, "available": false, "tag:unknown": "b" }),
+            ]
+        );
+    }
+
+    #[test]
+    fn delimiter() {
+        let csv = "id:number;title\n1;red, shirt\n";
+        assert_eq!(
+            parse(csv, b';').unwrap(),
+            vec![json!({ "id": 1, "title": "red, shirt" })]
+        );
+    }
+
+    #[test]
+    fn invalid_value() {
+        let csv = "id:number,title\n1,shirt\ntwo,hat\n";
+        let error = parse(csv, b',').unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid value `two` at line 3 in column `id`, expected a number."
+        );
+    }
+}
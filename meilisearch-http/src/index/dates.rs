@@ -0,0 +1,561 @@
+use std::collections::BTreeSet;
+use std::io::{Seek, SeekFrom, Write};
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc};
+use heed::types::{SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use milli::update::{IndexDocumentsMethod, UpdateBuilder, UpdateFormat};
+use milli::FieldsIdsMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use super::error::{FacetError, Result};
+use super::Index;
+
+/// The attributes holding dates. Their values are parsed at indexing time and stored as
+/// timestamps, in seconds, so they can be filtered and sorted as numbers.
+pub type DateAttributes = BTreeSet<String>;
+
+/// The field keeping the values of the date attributes of a document as they were sent, while
+/// the attributes hold their timestamps. It is never displayed nor searched, and the documents
+/// sent can't hold it.
+pub const ORIGINAL_DATES_FIELD: &str = "_dates";
+
+const DATE_ATTRIBUTES_KEY: &str = "date-attributes";
+/// Set when all the attributes are searchable but the original dates, milli is then given the
+/// list of the other fields.
+const SEARCHABLE_WILDCARD_KEY: &str = "searchable-wildcard";
+
+/// The greatest offset of a relative date, in seconds, that a `Duration` can hold.
+const MAX_RELATIVE_OFFSET: i64 = i64::MAX / 1000;
+
+static RELATIVE_DATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^now(?:([+-])(\d+)([smhdw]))?$").unwrap());
+
+impl Index {
+    pub fn date_attributes(&self, txn: &RoTxn) -> Result<DateAttributes> {
+        let attributes = self
+            .main
+            .get::<_, Str, SerdeJson<DateAttributes>>(txn, DATE_ATTRIBUTES_KEY)?
+            .unwrap_or_default();
+        Ok(attributes)
+    }
+
+    pub fn put_date_attributes(&self, txn: &mut RwTxn, attributes: &DateAttributes) -> Result<()> {
+        self.main
+            .put::<_, Str, SerdeJson<DateAttributes>>(txn, DATE_ATTRIBUTES_KEY, attributes)?;
+        Ok(())
+    }
+
+    /// Returns the searchable attributes of the index, `None` when they all are. This shadows
+    /// milli's, which lists the fields when the original dates are kept out of the search.
+    pub fn searchable_fields<'t>(&self, txn: &'t RoTxn) -> Result<Option<Vec<&'t str>>> {
+        let wildcard = self
+            .main
+            .get::<_, Str, SerdeJson<bool>>(txn, SEARCHABLE_WILDCARD_KEY)?
+            .unwrap_or(false);
+        if wildcard {
+            return Ok(None);
+        }
+        Ok(self.0.searchable_fields(txn)?)
+    }
+
+    /// Records whether all the attributes are searchable while milli is given a list of fields,
+    /// see [`Index::hide_original_dates`].
+    pub fn put_searchable_wildcard(&self, txn: &mut RwTxn, wildcard: bool) -> Result<()> {
+        if wildcard {
+            self.main
+                .put::<_, Str, SerdeJson<bool>>(txn, SEARCHABLE_WILDCARD_KEY, &true)?;
+        } else {
+            self.main.delete::<_, Str>(txn, SEARCHABLE_WILDCARD_KEY)?;
+        }
+        Ok(())
+    }
+
+    /// Keeps the original dates out of the search when all the attributes are searchable, by
+    /// giving milli all the other fields. The documents are reindexed when these fields change,
+    /// that is when a document brings a new field.
+    pub fn hide_original_dates(&self, txn: &mut RwTxn) -> Result<()> {
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        if self.searchable_fields(txn)?.is_some()
+            || fields_ids_map.id(ORIGINAL_DATES_FIELD).is_none()
+        {
+            return Ok(());
+        }
+
+        let searchable: Vec<_> = fields_ids_map
+            .iter()
+            .map(|(_, name)| name)
+            .filter(|name| *name != ORIGINAL_DATES_FIELD)
+            .map(String::from)
+            .collect();
+        let unchanged = self.0.searchable_fields(txn)?.map_or(false, |fields| {
+            fields.len() == searchable.len()
+                && searchable.iter().all(|f| fields.contains(&f.as_str()))
+        });
+        if unchanged {
+            return Ok(());
+        }
+
+        self.put_searchable_wildcard(txn, true)?;
+        let mut builder = UpdateBuilder::new(0).settings(txn, self);
+        builder.set_searchable_fields(searchable);
+        builder.execute(|_, _| ())?;
+        Ok(())
+    }
+
+    /// Converts the dates of the indexed documents after the date attributes changed from
+    /// `previous` to `attributes`. The documents are reindexed with their new values.
+    pub fn reindex_dates(
+        &self,
+        txn: &mut RwTxn,
+        previous: &DateAttributes,
+        attributes: &DateAttributes,
+    ) -> Result<()> {
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let mut file = tempfile::tempfile()?;
+        let mut is_empty = true;
+
+        for entry in self.all_documents(txn)? {
+            let (_, obkv) = entry?;
+            let mut document = Map::new();
+            for (fid, bytes) in obkv.iter() {
+                match fields_ids_map.name(fid) {
+                    Some(ORIGINAL_DATES_FIELD) | None => (),
+                    Some(name) => {
+                        document.insert(name.to_string(), serde_json::from_slice(bytes)?);
+                    }
+                }
+            }
+
+            let originals = original_dates(&fields_ids_map, obkv)?;
+            restore_dates(document.iter_mut(), previous, &originals);
+            convert_dates(&mut document, attributes);
+            serde_json::to_writer(&mut file, &document)?;
+            file.write_all(b"\n")?;
+            is_empty = false;
+        }
+
+        if is_empty {
+            return Ok(());
+        }
+
+        file.seek(SeekFrom::Start(0))?;
+        let mut builder = UpdateBuilder::new(0).index_documents(txn, self);
+        builder.update_format(UpdateFormat::JsonStream);
+        builder.index_documents_method(IndexDocumentsMethod::ReplaceDocuments);
+        builder.execute(file, |_, _| ())?;
+
+        Ok(())
+    }
+}
+
+/// Parses an absolute date, either as RFC 3339, as a date and time without timezone or as a
+/// simple date, the last two being considered UTC. Returns the timestamp of the date.
+pub fn parse_date(s: &str) -> Option<i64> {
+    let s = s.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(s) {
+        return Some(date.timestamp());
+    }
+    if let Ok(date) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S") {
+        return Some(date.timestamp());
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Some(date.and_hms(0, 0, 0).timestamp());
+    }
+    None
+}
+
+/// Parses a date literal of a filter, which is either an absolute date or a date relative to
+/// `now`, like `now-7d`. Returns the timestamp of the date, or `None` when the literal is not a
+/// date. A relative date too far from `now` to be represented is an error.
+pub fn parse_date_literal(s: &str, now: DateTime<Utc>) -> Result<Option<i64>> {
+    let captures = match RELATIVE_DATE.captures(s.trim()) {
+        Some(captures) => captures,
+        None => return Ok(parse_date(s)),
+    };
+
+    let out_of_range = || FacetError::DateOutOfRange(s.trim().to_string());
+    let date = match (captures.get(1), captures.get(2), captures.get(3)) {
+        (Some(sign), Some(amount), Some(unit)) => {
+            let unit_seconds = match unit.as_str() {
+                "s" => 1,
+                "m" => 60,
+                "h" => 60 * 60,
+                "d" => 24 * 60 * 60,
+                _ => 7 * 24 * 60 * 60,
+            };
+            let offset = amount
+                .as_str()
+                .parse::<i64>()
+                .ok()
+                .and_then(|amount| amount.checked_mul(unit_seconds))
+                .filter(|seconds| *seconds <= MAX_RELATIVE_OFFSET)
+                .map(Duration::seconds)
+                .ok_or_else(out_of_range)?;
+            if sign.as_str() == "-" {
+                now.checked_sub_signed(offset)
+            } else {
+                now.checked_add_signed(offset)
+            }
+        }
+        _ => Some(now),
+    };
+
+    Ok(Some(date.ok_or_else(out_of_range)?.timestamp()))
+}
+
+/// Replaces the dates of the date attributes of a document by their timestamps. The values that
+/// are not dates are kept as is. The values as they were sent are kept in the
+/// `ORIGINAL_DATES_FIELD` of the document, to be restored when it is returned.
+pub fn convert_dates(document: &mut Map<String, Value>, attributes: &DateAttributes) {
+    let mut originals = Map::new();
+    for attribute in attributes {
+        if let Some(value) = document.get_mut(attribute) {
+            originals.insert(attribute.clone(), value.clone());
+            convert_date_value(value);
+        }
+    }
+
+    if !originals.is_empty() {
+        document.insert(ORIGINAL_DATES_FIELD.to_string(), Value::Object(originals));
+    }
+}
+
+fn convert_date_value(value: &mut Value) {
+    match value {
+        Value::String(s) => {
+            if let Some(timestamp) = parse_date(s) {
+                *value = Value::from(timestamp);
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(convert_date_value),
+        _ => (),
+    }
+}
+
+/// Returns the values of the date attributes of a document as they were sent, kept in its
+/// `ORIGINAL_DATES_FIELD`.
+pub fn original_dates(
+    fields_ids_map: &FieldsIdsMap,
+    obkv: obkv::KvReader,
+) -> Result<Map<String, Value>> {
+    let bytes = match fields_ids_map
+        .id(ORIGINAL_DATES_FIELD)
+        .and_then(|id| obkv.get(id))
+    {
+        Some(bytes) => bytes,
+        None => return Ok(Map::new()),
+    };
+
+    match serde_json::from_slice(bytes)? {
+        Value::Object(originals) => Ok(originals),
+        _ => Ok(Map::new()),
+    }
+}
+
+/// Restores the values of the date attributes of a document as they were sent, from their
+/// `originals`. The documents indexed before the original values were kept have their
+/// timestamps formatted back as RFC 3339 dates.
+pub fn restore_dates<'a, K: AsRef<str>>(
+    document: impl IntoIterator<Item = (K, &'a mut Value)>,
+    attributes: &DateAttributes,
+    originals: &Map<String, Value>,
+) {
+    if attributes.is_empty() {
+        return;
+    }
+
+    for (name, value) in document {
+        if attributes.contains(name.as_ref()) {
+            match originals.get(name.as_ref()) {
+                Some(original) => *value = original.clone(),
+                None => restore_date_value(value),
+            }
+        }
+    }
+}
+
+/// The timestamps out of the range of dates are kept as is.
+fn restore_date_value(value: &mut Value) {
+    match value {
+        Value::Number(n) => {
+            let date = n
+                .as_f64()
+                .and_then(|timestamp| Utc.timestamp_opt(timestamp.floor() as i64, 0).single());
+            if let Some(date) = date {
+                *value = Value::String(date.to_rfc3339_opts(SecondsFormat::Secs, true));
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(restore_date_value),
+        _ => (),
+    }
+}
+
+/// Returns whether a filter, in any of its forms, holds a date relative to `now`. The documents
+/// matching such a filter change over time.
+pub fn has_relative_dates(filter: &Value) -> bool {
+    match filter {
+        Value::String(expr) => tokenize_filter(expr)
+            .iter()
+            .any(|token| RELATIVE_DATE.is_match(unquote(token))),
+        Value::Array(values) => values.iter().any(has_relative_dates),
+        Value::Object(object) => object.values().any(has_relative_dates),
+        _ => false,
+    }
+}
+
+/// Replaces the date literals compared to date attributes in a filter expression by their
+/// timestamps, e.g. `releaseDate > 2021-01-01` becomes `releaseDate > 1609459200`. A filter
+/// without date literals is returned as it was written.
+pub fn convert_filter_dates(
+    expr: &str,
+    attributes: &DateAttributes,
+    now: DateTime<Utc>,
+) -> Result<String> {
+    if attributes.is_empty() {
+        return Ok(expr.to_string());
+    }
+
+    let mut tokens = tokenize_filter(expr);
+    let mut converted = false;
+
+    let is_date_attribute = |token: &str| attributes.contains(unquote(token));
+    let is_operator = |token: &str| matches!(token, "=" | "!=" | ">" | ">=" | "<" | "<=");
+    let mut convert = |token: &mut String| -> Result<()> {
+        if let Some(timestamp) = parse_date_literal(unquote(token), now)? {
+            *token = timestamp.to_string();
+            converted = true;
+        }
+        Ok(())
+    };
+
+    let mut i = 0;
+    while i < tokens.len() {
+        if is_date_attribute(&tokens[i]) {
+            // `attribute <op> date`
+            if i + 2 < tokens.len() && is_operator(&tokens[i + 1]) {
+                convert(&mut tokens[i + 2])?;
+                i += 3;
+                continue;
+            }
+            // `attribute date TO date`
+            if i + 3 < tokens.len() && tokens[i + 2] == "TO" {
+                convert(&mut tokens[i + 1])?;
+                convert(&mut tokens[i + 3])?;
+                i += 4;
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    if converted {
+        Ok(tokens.join(" "))
+    } else {
+        Ok(expr.to_string())
+    }
+}
+
+fn unquote(token: &str) -> &str {
+    let quoted = token.len() >= 2
+        && ((token.starts_with('"') && token.ends_with('"'))
+            || (token.starts_with('\'') && token.ends_with('\'')));
+    if quoted {
+        &token[1..token.len() - 1]
+    } else {
+        token
+    }
+}
+
+/// Splits a filter expression in quoted strings, parentheses, operators and words.
+fn tokenize_filter(expr: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '"' | '\'' => {
+                let mut token = String::new();
+                token.push(c);
+                chars.next();
+                for next in &mut chars {
+                    token.push(next);
+                    if next == c {
+                        break;
+                    }
+                }
+                tokens.push(token);
+            }
+            '(' | ')' => {
+                tokens.push(c.to_string());
+                chars.next();
+            }
+            '=' | '!' | '>' | '<' => {
+                let mut token = c.to_string();
+                chars.next();
+                if chars.peek() == Some(&'=') {
+                    token.push('=');
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+            _ => {
+                let mut token = String::new();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || "\"'()=!<>".contains(next) {
+                        break;
+                    }
+                    token.push(next);
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+        }
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn attributes() -> DateAttributes {
+        vec![String::from("releaseDate")].into_iter().collect()
+    }
+
+    #[test]
+    fn parse_dates() {
+        assert_eq!(parse_date("2021-01-01"), Some(1609459200));
+        assert_eq!(parse_date("2021-01-01T01:00:00"), Some(1609462800));
+        assert_eq!(parse_date("2021-01-01T02:00:00+01:00"), Some(1609462800));
+        assert_eq!(parse_date("yesterday"), None);
+        assert_eq!(parse_date("12"), None);
+    }
+
+    #[test]
+    fn parse_relative_dates() {
+        let now = Utc.timestamp(1609459200, 0);
+        assert_eq!(parse_date_literal("now", now).unwrap(), Some(1609459200));
+        assert_eq!(
+            parse_date_literal("now-7d", now).unwrap(),
+            Some(1609459200 - 7 * 86400)
+        );
+        assert_eq!(
+            parse_date_literal("now+2h", now).unwrap(),
+            Some(1609459200 + 7200)
+        );
+        assert_eq!(parse_date_literal("now-7y", now).unwrap(), None);
+    }
+
+    #[test]
+    fn find_relative_dates() {
+        assert!(has_relative_dates(&json!("releaseDate > now-7d")));
+        assert!(has_relative_dates(&json!([[
+            "title = shirt",
+            "releaseDate < 'now'"
+        ]])));
+        assert!(has_relative_dates(&json!({
+            "and": [{ "field": "releaseDate", "op": ">", "value": "now-1h" }]
+        })));
+        assert!(!has_relative_dates(&json!(
+            "releaseDate > 2021-01-01 AND title = nowhere"
+        )));
+        assert!(!has_relative_dates(&json!(["price < 20"])));
+    }
+
+    #[test]
+    fn relative_dates_out_of_range() {
+        let now = Utc.timestamp(1609459200, 0);
+        assert!(parse_date_literal("now-99999999999999w", now).is_err());
+        assert!(parse_date_literal("now+9223372036854775807s", now).is_err());
+        assert!(parse_date_literal("now+99999999999999999999s", now).is_err());
+        assert!(parse_date_literal("now+100000000000w", now).is_err());
+    }
+
+    #[test]
+    fn convert_and_restore_documents() {
+        let mut document = json!({
+            "id": 1,
+            "releaseDate": "2021-01-01T00:00:00Z",
+            "title": "2021-01-01",
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+
+        convert_dates(&mut document, &attributes());
+        assert_eq!(document["releaseDate"], json!(1609459200));
+        assert_eq!(document["title"], json!("2021-01-01"));
+
+        restore_dates(document.iter_mut(), &attributes(), &Map::new());
+        assert_eq!(document["releaseDate"], json!("2021-01-01T00:00:00Z"));
+    }
+
+    #[test]
+    fn restore_original_dates() {
+        for original in &[json!("2021-01-01T02:00:00.5+01:00"), json!(1609459200)] {
+            let mut document = json!({ "id": 1, "releaseDate": original })
+                .as_object()
+                .unwrap()
+                .clone();
+
+            convert_dates(&mut document, &attributes());
+            let originals = match document.remove(ORIGINAL_DATES_FIELD) {
+                Some(Value::Object(originals)) => originals,
+                other => panic!("unexpected original dates: {:?}", other),
+            };
+            assert_eq!(document["releaseDate"], json!(1609459200));
+
+            restore_dates(document.iter_mut(), &attributes(), &originals);
+            assert_eq!(document["releaseDate"], *original);
+        }
+    }
+
+    #[test]
+    fn keep_timestamps_out_of_range() {
+        let mut document = json!({ "releaseDate": 1e300 }).as_object().unwrap().clone();
+        restore_dates(document.iter_mut(), &attributes(), &Map::new());
+        assert_eq!(document["releaseDate"], json!(1e300));
+    }
+
+    #[test]
+    fn convert_filter_expressions() {
+        let now = Utc.timestamp(1609459200, 0);
+        assert_eq!(
+            convert_filter_dates(
+                "releaseDate>2021-01-01 AND (title = 2021-01-01 OR releaseDate <= now-1d)",
+                &attributes(),
+                now
+            )
+            .unwrap(),
+            "releaseDate > 1609459200 AND ( title = 2021-01-01 OR releaseDate <= 1609372800 )"
+        );
+        assert_eq!(
+            convert_filter_dates(
+                r#""releaseDate" 2021-01-01 TO "2021-01-02""#,
+                &attributes(),
+                now
+            )
+            .unwrap(),
+            r#""releaseDate" 1609459200 TO 1609545600"#
+        );
+
+        let expr = r#"title = "red  shirt"   AND releaseDate > 1609459200"#;
+        assert_eq!(
+            convert_filter_dates(expr, &attributes(), now).unwrap(),
+            expr
+        );
+        assert_eq!(
+            convert_filter_dates(expr, &DateAttributes::new(), now).unwrap(),
+            expr
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::fs::{create_dir_all, File};
 use std::io::{BufRead, BufReader, Write};
+use std::iter::once;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -7,17 +8,25 @@ use anyhow::{bail, Context};
 use heed::RoTxn;
 use indexmap::IndexMap;
 use milli::update::{IndexDocumentsMethod, UpdateFormat::JsonStream};
+use milli::DocumentId;
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::option::IndexerOpts;
 
+use super::dates::{original_dates, restore_dates, ORIGINAL_DATES_FIELD};
 use super::error::Result;
-use super::{update_handler::UpdateHandler, Index, Settings, Unchecked};
+use super::nested::unflatten;
+use super::vectors::VECTORS_FIELD;
+use super::{update_handler::UpdateHandler, Index, Rules, Settings, Unchecked};
 
 #[derive(Serialize, Deserialize)]
 struct DumpMeta {
     settings: Settings<Unchecked>,
     primary_key: Option<String>,
+    #[serde(default)]
+    rules: Rules,
 }
 
 const META_FILE_NAME: &str = "meta.json";
@@ -38,22 +47,66 @@ impl Index {
         let document_file_path = path.as_ref().join(DATA_FILE_NAME);
         let mut document_file = File::create(&document_file_path)?;
 
-        let documents = self.all_documents(txn)?;
+        // The vectors are added back to the documents, they are extracted again on load, as
+        // the nested objects are flattened again.
+        let nested_fields = self.nested_fields(txn)?;
+        self.for_each_document(txn, None, true, |document| {
+            let document = unflatten(document.drain(..), &nested_fields);
+            serde_json::to_writer(&mut document_file, &document)?;
+            document_file.write_all(b"\n")?;
+            Ok(())
+        })
+    }
+
+    /// Calls `f` with every document of the index in the order of their internal ids, or only
+    /// with the documents in `candidates` when given. The dates of the documents are restored,
+    /// and their vector is added under the `_vectors` field when `with_vectors` is set. The
+    /// documents are flattened, `f` can take their fields.
+    pub(super) fn for_each_document<F>(
+        &self,
+        txn: &RoTxn,
+        candidates: Option<&RoaringBitmap>,
+        with_vectors: bool,
+        mut f: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&mut IndexMap<&str, Value>) -> Result<()>,
+    {
+        // Only the candidates are read when given, instead of all the documents of the index.
+        // They are read one at a time, so that a single document is held at once.
+        let documents: Box<dyn Iterator<Item = Result<(DocumentId, obkv::KvReader)>> + '_> =
+            match candidates {
+                Some(candidates) => Box::new(candidates.iter().flat_map(move |id| {
+                    match self.documents(txn, once(id)) {
+                        Ok(documents) => documents.into_iter().map(Ok).collect::<Vec<_>>(),
+                        Err(e) => vec![Err(e.into())],
+                    }
+                })),
+                None => Box::new(self.all_documents(txn)?.map(|document| Ok(document?))),
+            };
         let fields_ids_map = self.fields_ids_map(txn)?;
+        let date_attributes = self.date_attributes(txn)?;
 
-        // dump documents
         let mut json_map = IndexMap::new();
         for document in documents {
-            let (_, reader) = document?;
-
+            let (id, reader) = document?;
             for (fid, bytes) in reader.iter() {
-                if let Some(name) = fields_ids_map.name(fid) {
-                    json_map.insert(name, serde_json::from_slice::<serde_json::Value>(bytes)?);
+                match fields_ids_map.name(fid) {
+                    Some(ORIGINAL_DATES_FIELD) | None => (),
+                    Some(name) => {
+                        json_map.insert(name, serde_json::from_slice::<Value>(bytes)?);
+                    }
                 }
             }
 
-            serde_json::to_writer(&mut document_file, &json_map)?;
-            document_file.write_all(b"\n")?;
+            let original_dates = original_dates(&fields_ids_map, reader)?;
+            restore_dates(json_map.iter_mut(), &date_attributes, &original_dates);
+            if with_vectors {
+                if let Some(vector) = self.vector(txn, id)? {
+                    json_map.insert(VECTORS_FIELD, serde_json::json!(vector));
+                }
+            }
+            f(&mut json_map)?;
 
             json_map.clear();
         }
@@ -67,9 +120,11 @@ impl Index {
 
         let settings = self.settings_txn(txn)?.into_unchecked();
         let primary_key = self.primary_key(txn)?.map(String::from);
+        let rules = self.rules(txn)?;
         let meta = DumpMeta {
             settings,
             primary_key,
+            rules,
         };
 
         serde_json::to_writer(&mut meta_file, &meta)?;
@@ -96,14 +151,19 @@ impl Index {
         let DumpMeta {
             settings,
             primary_key,
+            rules,
         } = serde_json::from_reader(&mut meta_file)?;
-        let settings = settings.check();
+        let mut settings = settings.check();
+        // The documents of the dump may predate the document schema, it is only set once they
+        // are loaded.
+        let document_schema = settings.document_schema.take();
         let index = Self::open(&dst_dir_path, size)?;
         let mut txn = index.write_txn()?;
 
         let handler = UpdateHandler::new(&indexing_options)?;
 
         index.update_settings_txn(&mut txn, &settings, handler.update_builder(0))?;
+        index.put_rules(&mut txn, &rules)?;
 
         let document_file_path = src.as_ref().join(DATA_FILE_NAME);
         let reader = File::open(&document_file_path)?;
@@ -115,6 +175,7 @@ impl Index {
             index.update_documents_txn(
                 &mut txn,
                 JsonStream,
+                None,
                 IndexDocumentsMethod::UpdateDocuments,
                 Some(reader),
                 handler.update_builder(0),
@@ -122,6 +183,10 @@ impl Index {
             )?;
         }
 
+        if let Some(ref document_schema) = document_schema {
+            index.put_document_schema(&mut txn, document_schema.as_ref())?;
+        }
+
         txn.commit()?;
 
         match Arc::try_unwrap(index.0) {
@@ -1,8 +1,10 @@
 use std::error::Error;
 
+use chrono::{DateTime, Utc};
 use meilisearch_error::{Code, ErrorCode};
 use serde_json::Value;
 
+use super::schema::InvalidDocuments;
 use crate::error::MilliError;
 
 pub type Result<T> = std::result::Result<T, IndexError>;
@@ -13,14 +15,37 @@ pub enum IndexError {
     Internal(Box<dyn Error + Send + Sync + 'static>),
     #[error("Document with id {0} not found.")]
     DocumentNotFound(String),
+    #[error("Document ids must be strings or integers, found: {0}")]
+    InvalidDocumentId(Value),
     #[error("{0}")]
     Facet(#[from] FacetError),
     #[error("{0}")]
+    Rule(#[from] RuleError),
+    #[error("{0}")]
+    Vector(#[from] VectorError),
+    #[error("{0}")]
+    DocumentFormat(#[from] DocumentFormatError),
+    #[error("{0}")]
+    Schema(#[from] SchemaError),
+    #[error("{0}")]
     Milli(#[from] milli::Error),
+    #[error("Boost weight must be a strictly positive number, found: {0}")]
+    InvalidBoostWeight(f64),
+    #[error("`boost` can't be used together with `groupBy`.")]
+    BoostWithGroupBy,
+    #[error("`boost` can only be used without a query, or when the ranking rules are only `asc` and `desc` rules, and without `vector`.")]
+    UnboostableSearch,
+    #[error("`page` and `hitsPerPage` can't be used together with `offset` and `limit`.")]
+    MixedPagination,
+    #[error("Invalid cursor `{0}`, it must be the `next` cursor of a previous page.")]
+    InvalidCursor(String),
+    #[error("`cursor` can't be used together with `offset`.")]
+    CursorWithOffset,
 }
 
 internal_error!(
-    IndexError: std::io::Error,
+    IndexError: csv::Error,
+    std::io::Error,
     heed::Error,
     fst::Error,
     serde_json::Error
@@ -31,8 +56,19 @@ impl ErrorCode for IndexError {
         match self {
             IndexError::Internal(_) => Code::Internal,
             IndexError::DocumentNotFound(_) => Code::DocumentNotFound,
+            IndexError::InvalidDocumentId(_) => Code::BadParameter,
             IndexError::Facet(e) => e.error_code(),
+            IndexError::Rule(e) => e.error_code(),
+            IndexError::Vector(e) => e.error_code(),
+            IndexError::DocumentFormat(e) => e.error_code(),
+            IndexError::Schema(e) => e.error_code(),
             IndexError::Milli(e) => MilliError(e).error_code(),
+            IndexError::InvalidBoostWeight(_) => Code::BadParameter,
+            IndexError::BoostWithGroupBy => Code::BadParameter,
+            IndexError::UnboostableSearch => Code::BadParameter,
+            IndexError::MixedPagination => Code::BadParameter,
+            IndexError::InvalidCursor(_) => Code::BadParameter,
+            IndexError::CursorWithOffset => Code::BadParameter,
         }
     }
 }
@@ -41,12 +77,124 @@ impl ErrorCode for IndexError {
 pub enum FacetError {
     #[error("Invalid facet expression, expected {}, found: {1}", .0.join(", "))]
     InvalidExpression(&'static [&'static str], Value),
+    #[error("Hierarchical facet `{0}` is not declared in the index settings.")]
+    UnknownHierarchicalFacet(String),
+    #[error("Facet value `{0}` can't contain both simple and double quotes.")]
+    UnquotableValue(String),
+    #[error("Attribute `{0}` is not filterable.")]
+    NotFilterable(String),
+    #[error("Histogram interval must be a strictly positive number, found: {0}")]
+    InvalidInterval(f64),
+    #[error("Histogram interval {interval} is too small for the value {value}.")]
+    IntervalOverflow { interval: f64, value: f64 },
+    #[error("Invalid filter at `{pointer}`: {message}.")]
+    InvalidFilter { pointer: String, message: String },
+    #[error("Date `{0}` is out of range.")]
+    DateOutOfRange(String),
 }
 
 impl ErrorCode for FacetError {
     fn error_code(&self) -> Code {
         match self {
             FacetError::InvalidExpression(_, _) => Code::Facet,
+            FacetError::UnknownHierarchicalFacet(_) => Code::Facet,
+            FacetError::UnquotableValue(_) => Code::Facet,
+            FacetError::NotFilterable(_) => Code::Facet,
+            FacetError::InvalidInterval(_) => Code::Facet,
+            FacetError::IntervalOverflow { .. } => Code::Facet,
+            FacetError::InvalidFilter { .. } => Code::Filter,
+            FacetError::DateOutOfRange(_) => Code::Filter,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RuleError {
+    #[error("Rule `{0}` not found.")]
+    NotFound(String),
+    #[error("Invalid query pattern: {0}")]
+    InvalidRegex(String),
+    #[error("Invalid date window, the rule would be valid from {0} until {1}.")]
+    InvalidDateWindow(DateTime<Utc>, DateTime<Utc>),
+}
+
+impl ErrorCode for RuleError {
+    fn error_code(&self) -> Code {
+        match self {
+            RuleError::NotFound(_) => Code::RuleNotFound,
+            RuleError::InvalidRegex(_) => Code::InvalidRule,
+            RuleError::InvalidDateWindow(_, _) => Code::InvalidRule,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum VectorError {
+    #[error("The `_vectors` field of document `{0}` must be an array of numbers or null.")]
+    InvalidDocumentVector(String),
+    #[error("Expected a vector of {expected} dimensions, found {found} dimensions.")]
+    DimensionMismatch { expected: usize, found: usize },
+    #[error("A hybrid search requires a `vector`, the embeddings are not computed by the engine.")]
+    MissingVector,
+    #[error("The semantic ratio must be between 0 and 1, found: {0}")]
+    InvalidSemanticRatio(f32),
+    #[error("A vector search can't group its results.")]
+    GroupBy,
+}
+
+impl ErrorCode for VectorError {
+    fn error_code(&self) -> Code {
+        match self {
+            VectorError::InvalidDocumentVector(_) => Code::InvalidVector,
+            VectorError::DimensionMismatch { .. } => Code::InvalidVector,
+            VectorError::MissingVector => Code::InvalidVector,
+            VectorError::InvalidSemanticRatio(_) => Code::BadParameter,
+            VectorError::GroupBy => Code::BadRequest,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DocumentFormatError {
+    #[error("Malformed CSV payload: {0}")]
+    MalformedCsv(#[from] csv::Error),
+    #[error("Invalid value `{value}` at line {line} in column `{column}`, expected a {expected}.")]
+    InvalidCsvValue {
+        line: u64,
+        column: String,
+        expected: &'static str,
+        value: String,
+    },
+    #[error("Field `{0}` is present both as a dotted key and as a nested object.")]
+    ConflictingField(String),
+    #[error("Field `{0}` is reserved and can't be sent in a document.")]
+    ReservedField(String),
+}
+
+impl ErrorCode for DocumentFormatError {
+    fn error_code(&self) -> Code {
+        match self {
+            DocumentFormatError::MalformedCsv(_) => Code::BadRequest,
+            DocumentFormatError::InvalidCsvValue { .. } => Code::BadRequest,
+            DocumentFormatError::ConflictingField(_) => Code::BadRequest,
+            DocumentFormatError::ReservedField(_) => Code::BadRequest,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaError {
+    #[error("Invalid document schema at `{path}`: {message}.")]
+    InvalidSchema { path: String, message: String },
+    #[error("{0}")]
+    InvalidDocuments(InvalidDocuments),
+}
+
+impl ErrorCode for SchemaError {
+    fn error_code(&self) -> Code {
+        match self {
+            SchemaError::InvalidSchema { .. } => Code::InvalidDocumentSchema,
+            SchemaError::InvalidDocuments(_) => Code::DocumentSchemaViolation,
         }
     }
 }
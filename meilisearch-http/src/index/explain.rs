@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use heed::RoTxn;
+use meilisearch_tokenizer::{Analyzer, AnalyzerConfig};
+use milli::{Criterion, FilterCondition, Operator};
+use roaring::RoaringBitmap;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::error::Result;
+use super::search::{SearchQuery, SearchTrace};
+use super::Index;
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchExplanation {
+    pub query: String,
+    pub query_words: Vec<QueryWord>,
+    /// The words of the query ignored because they are stop words.
+    pub stop_words: Vec<String>,
+    pub filter: Option<Value>,
+    pub nb_hits: u64,
+    pub ranking_rules: Vec<RankingRuleExplanation>,
+    pub timings: SearchTimings,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryWord {
+    pub word: String,
+    /// Whether the word also matches the words it is a prefix of, only the last word of the
+    /// query can be a prefix.
+    pub prefix: bool,
+    pub synonyms: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RankingRuleExplanation {
+    pub rule: String,
+    /// The number of candidates sorted by an `asc` or `desc` rule, the candidates having a
+    /// numeric value for its attribute. The others are left at the end. milli doesn't expose
+    /// how the other rules split the candidates in buckets.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidates: Option<u64>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchTimings {
+    pub filtering_ms: f64,
+    pub ranking_ms: f64,
+    pub fetching_ms: f64,
+    pub formatting_ms: f64,
+    pub total_ms: f64,
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl Index {
+    /// Performs the search and explains how its query and filter were understood, how many
+    /// candidates the `asc` and `desc` ranking rules sorted and where the time was spent.
+    pub fn explain_search(&self, query: SearchQuery) -> Result<SearchExplanation> {
+        let mut trace = SearchTrace::default();
+        let result = self.perform_search_traced(query, Some(&mut trace))?;
+
+        let rtxn = self.read_txn()?;
+        let q = result.rewritten_query.as_ref().unwrap_or(&result.query);
+        let (query_words, stop_words) = self.analyze_query(&rtxn, q)?;
+        let ranking_rules = self.explain_ranking_rules(&rtxn, &trace.candidates)?;
+
+        Ok(SearchExplanation {
+            query: result.query,
+            query_words,
+            stop_words,
+            filter: trace.filter,
+            nb_hits: result.nb_hits,
+            ranking_rules,
+            timings: SearchTimings {
+                filtering_ms: millis(trace.filtering),
+                ranking_ms: millis(trace.ranking),
+                fetching_ms: millis(trace.fetching),
+                formatting_ms: millis(trace.formatting),
+                total_ms: result.processing_time_ms as f64,
+            },
+        })
+    }
+
+    /// Tokenizes the query the way milli does, and returns its words along with their synonyms,
+    /// and the stop words that were ignored.
+    fn analyze_query(&self, txn: &RoTxn, query: &str) -> Result<(Vec<QueryWord>, Vec<String>)> {
+        let stop_words = self.stop_words(txn)?;
+        let synonyms = self.synonyms(txn)?;
+
+        let mut config = AnalyzerConfig::default();
+        if let Some(ref stop_words) = stop_words {
+            config.stop_words(stop_words);
+        }
+        let analyzer = Analyzer::new(config);
+        let analyzed = analyzer.analyze(query);
+
+        let tokens: Vec<_> = analyzed.tokens().collect();
+        let mut words = Vec::new();
+        let mut ignored = Vec::new();
+
+        for (i, token) in tokens.iter().enumerate() {
+            let word = token.text().to_string();
+            if token.is_stopword() {
+                ignored.push(word);
+            } else if token.is_word() {
+                let synonyms = synonyms
+                    .get(&vec![word.clone()])
+                    .map(|synonyms| synonyms.iter().map(|words| words.join(" ")).collect())
+                    .unwrap_or_default();
+                words.push(QueryWord {
+                    word,
+                    prefix: i + 1 == tokens.len(),
+                    synonyms,
+                });
+            }
+        }
+
+        Ok((words, ignored))
+    }
+
+    fn explain_ranking_rules(
+        &self,
+        txn: &RoTxn,
+        candidates: &RoaringBitmap,
+    ) -> Result<Vec<RankingRuleExplanation>> {
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let mut explanations = Vec::new();
+
+        for criterion in self.criteria(txn)? {
+            let candidates = match criterion {
+                Criterion::Asc(ref field) | Criterion::Desc(ref field) => {
+                    match fields_ids_map.id(field) {
+                        Some(field_id) => {
+                            let numbers = Operator::Between(f64::MIN, f64::MAX);
+                            let condition = FilterCondition::Operator(field_id, numbers);
+                            Some((condition.evaluate(txn, self)? & candidates).len())
+                        }
+                        None => Some(0),
+                    }
+                }
+                _ => None,
+            };
+
+            explanations.push(RankingRuleExplanation {
+                rule: criterion.to_string(),
+                candidates,
+            });
+        }
+
+        Ok(explanations)
+    }
+}
@@ -0,0 +1,66 @@
+use std::io::Write;
+
+use serde_json::Value;
+
+use super::error::Result;
+use super::nested::unflatten;
+use super::Index;
+
+/// The formats the documents can be exported in. They are encoded one document at a time, so
+/// that an export never holds more than one document in memory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Ndjson,
+    Csv,
+}
+
+impl Index {
+    /// Writes the documents matching the `filter`, or all the documents of the index, to
+    /// `writer` from a single read transaction. NDJSON exports keep the vectors and the nested
+    /// objects of the documents, so that they can be added back as is. The CSV columns are all
+    /// the fields of the index, in the order they were first seen in, with the fields of the
+    /// nested objects under their dot path.
+    pub fn export_documents(
+        &self,
+        filter: Option<&Value>,
+        format: ExportFormat,
+        writer: impl Write,
+    ) -> Result<()> {
+        let txn = self.read_txn()?;
+
+        let candidates = self.filtered_candidates(&txn, filter)?;
+
+        match format {
+            ExportFormat::Ndjson => {
+                let nested_fields = self.nested_fields(&txn)?;
+                let mut writer = writer;
+                self.for_each_document(&txn, candidates.as_ref(), true, |document| {
+                    let document = unflatten(document.drain(..), &nested_fields);
+                    serde_json::to_writer(&mut writer, &document)?;
+                    writer.write_all(b"\n")?;
+                    Ok(())
+                })?;
+                writer.flush()?;
+            }
+            ExportFormat::Csv => {
+                let fields_ids_map = self.fields_ids_map(&txn)?;
+                let columns: Vec<_> = fields_ids_map.iter().map(|(_, name)| name).collect();
+
+                let mut writer = csv::Writer::from_writer(writer);
+                writer.write_record(&columns)?;
+                self.for_each_document(&txn, candidates.as_ref(), false, |document| {
+                    let record = columns.iter().map(|column| match document.get(column) {
+                        None | Some(Value::Null) => String::new(),
+                        Some(Value::String(s)) => s.clone(),
+                        Some(value) => value.to_string(),
+                    });
+                    writer.write_record(record)?;
+                    Ok(())
+                })?;
+                writer.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+}
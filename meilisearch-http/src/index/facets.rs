@@ -0,0 +1,380 @@
+use std::collections::BTreeMap;
+
+use heed::types::{SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use milli::{FieldId, FilterCondition};
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+
+use super::error::{FacetError, Result};
+use super::Index;
+
+/// Maps the name of a hierarchical facet to the attributes holding each of its levels, from the
+/// root level to the deepest one.
+pub type HierarchicalFacets = BTreeMap<String, Vec<String>>;
+
+pub type FacetsDistribution = BTreeMap<String, BTreeMap<String, u64>>;
+
+const HIERARCHICAL_FACETS_KEY: &str = "hierarchical-facets";
+
+/// The buckets requested for a numeric facet, either as explicit ranges or as an evenly spaced
+/// histogram.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum FacetRanges {
+    Ranges(Vec<FacetRange>),
+    Histogram(Histogram),
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct Histogram {
+    pub interval: f64,
+}
+
+/// A range of numeric values, `from` is inclusive and `to` is exclusive. A missing bound means
+/// that the range is unbounded on this side.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FacetRange {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FacetRangeCount {
+    #[serde(flatten)]
+    pub range: FacetRange,
+    pub count: u64,
+}
+
+impl FacetRange {
+    fn contains(&self, value: f64) -> bool {
+        self.from.map_or(true, |from| value >= from) && self.to.map_or(true, |to| value < to)
+    }
+}
+
+impl FacetRanges {
+    fn validate(&self) -> Result<()> {
+        match self {
+            FacetRanges::Histogram(Histogram { interval })
+                if !interval.is_finite() || *interval <= 0.0 =>
+            {
+                Err(FacetError::InvalidInterval(*interval).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts the documents that fall in each bucket. `values` yields each distinct value of the
+    /// facet along with the documents holding it. A document holding several values of a bucket
+    /// is only counted once in this bucket. A histogram fails when its interval is too small for
+    /// the values, as their buckets couldn't be told apart.
+    fn count(
+        &self,
+        values: impl IntoIterator<Item = (f64, RoaringBitmap)>,
+    ) -> Result<Vec<FacetRangeCount>> {
+        match self {
+            FacetRanges::Ranges(ranges) => {
+                let mut docids = vec![RoaringBitmap::new(); ranges.len()];
+                for (value, value_docids) in values {
+                    for (range, docids) in ranges.iter().zip(docids.iter_mut()) {
+                        if range.contains(value) {
+                            *docids |= &value_docids;
+                        }
+                    }
+                }
+
+                Ok(ranges
+                    .iter()
+                    .zip(docids)
+                    .map(|(range, docids)| FacetRangeCount {
+                        range: *range,
+                        count: docids.len(),
+                    })
+                    .collect())
+            }
+            FacetRanges::Histogram(Histogram { interval }) => {
+                let mut buckets = BTreeMap::new();
+                for (value, docids) in values {
+                    let bucket = (value / interval).floor();
+                    if !(i64::MIN as f64..i64::MAX as f64).contains(&bucket) {
+                        return Err(FacetError::IntervalOverflow {
+                            interval: *interval,
+                            value,
+                        }
+                        .into());
+                    }
+                    let bucket = bucket as i64;
+                    *buckets.entry(bucket).or_insert_with(RoaringBitmap::new) |= docids;
+                }
+
+                Ok(buckets
+                    .into_iter()
+                    .map(|(bucket, docids)| {
+                        let from = bucket as f64 * interval;
+                        FacetRangeCount {
+                            range: FacetRange {
+                                from: Some(from),
+                                to: Some(from + interval),
+                            },
+                            count: docids.len(),
+                        }
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+impl Index {
+    pub fn hierarchical_facets(&self, txn: &RoTxn) -> Result<HierarchicalFacets> {
+        let facets = self
+            .main
+            .get::<_, Str, SerdeJson<HierarchicalFacets>>(txn, HIERARCHICAL_FACETS_KEY)?
+            .unwrap_or_default();
+        Ok(facets)
+    }
+
+    pub fn put_hierarchical_facets(
+        &self,
+        txn: &mut RwTxn,
+        facets: &HierarchicalFacets,
+    ) -> Result<()> {
+        self.main.put::<_, Str, SerdeJson<HierarchicalFacets>>(
+            txn,
+            HIERARCHICAL_FACETS_KEY,
+            facets,
+        )?;
+        Ok(())
+    }
+
+    /// Computes the distribution of the levels of the requested hierarchical facets.
+    ///
+    /// `selected` maps each requested hierarchical facet to the path of values selected by the
+    /// user. The root level is always returned, and each following level is only returned for
+    /// the documents that match the value selected in its parent level.
+    pub fn hierarchical_facets_distribution(
+        &self,
+        txn: &RoTxn,
+        selected: &BTreeMap<String, Vec<String>>,
+        candidates: &RoaringBitmap,
+    ) -> Result<FacetsDistribution> {
+        let hierarchical_facets = self.hierarchical_facets(txn)?;
+        let mut distribution = BTreeMap::new();
+
+        for (name, path) in selected {
+            let levels = hierarchical_facets
+                .get(name)
+                .ok_or_else(|| FacetError::UnknownHierarchicalFacet(name.clone()))?;
+
+            let mut candidates = candidates.clone();
+            for (depth, level) in levels.iter().enumerate() {
+                let mut facets_distribution = self.facets_distribution(txn);
+                facets_distribution
+                    .facets(std::iter::once(level))
+                    .candidates(candidates.clone());
+                distribution.extend(facets_distribution.execute()?);
+
+                // The children of a level are only computed under its selected value.
+                match path.get(depth) {
+                    Some(value) => {
+                        let expr = format!(
+                            "{} = {}",
+                            quote_facet_value(level)?,
+                            quote_facet_value(value)?
+                        );
+                        let condition = FilterCondition::from_str(txn, self, &expr)?;
+                        candidates &= condition.evaluate(txn, self)?;
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        Ok(distribution)
+    }
+
+    /// Counts the candidates falling in each of the requested buckets of numeric facets.
+    pub fn facet_ranges(
+        &self,
+        txn: &RoTxn,
+        query: &BTreeMap<String, FacetRanges>,
+        candidates: &RoaringBitmap,
+    ) -> Result<BTreeMap<String, Vec<FacetRangeCount>>> {
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let filterable_fields = self.faceted_fields(txn)?;
+        let mut facet_ranges = BTreeMap::new();
+
+        for (name, ranges) in query {
+            ranges.validate()?;
+
+            if !filterable_fields.contains(name) {
+                return Err(FacetError::NotFilterable(name.clone()).into());
+            }
+
+            let values = match fields_ids_map.id(name) {
+                Some(field_id) => self.facet_number_values(txn, field_id, candidates)?,
+                None => Vec::new(),
+            };
+
+            facet_ranges.insert(name.clone(), ranges.count(values)?);
+        }
+
+        Ok(facet_ranges)
+    }
+
+    /// Returns the distinct values of a numeric facet along with the candidates holding them, by
+    /// reading the level 0 of milli's numeric facet levels.
+    fn facet_number_values(
+        &self,
+        txn: &RoTxn,
+        field_id: FieldId,
+        candidates: &RoaringBitmap,
+    ) -> Result<Vec<(f64, RoaringBitmap)>> {
+        let level_zero = (field_id, 0, f64::MIN, f64::MIN)..=(field_id, 0, f64::MAX, f64::MAX);
+        let mut values = Vec::new();
+
+        for entry in self.facet_id_f64_docids.range(txn, &level_zero)? {
+            let ((_, _, value, _), mut docids) = entry?;
+            docids &= candidates;
+            if !docids.is_empty() {
+                values.push((value, docids));
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+/// Quotes a facet name or value so it can safely be inserted in a filter expression.
+pub fn quote_facet_value(value: &str) -> Result<String> {
+    if !value.contains('"') {
+        Ok(format!("\"{}\"", value))
+    } else if !value.contains('\'') {
+        Ok(format!("'{}'", value))
+    } else {
+        Err(FacetError::UnquotableValue(value.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn quote_values() {
+        assert_eq!(quote_facet_value("Books").unwrap(), r#""Books""#);
+        assert_eq!(quote_facet_value(r#"12" vinyl"#).unwrap(), r#"'12" vinyl'"#);
+        assert_eq!(
+            quote_facet_value("rock'n'roll").unwrap(),
+            r#""rock'n'roll""#
+        );
+        assert!(quote_facet_value(r#"rock'n'roll 12""#).is_err());
+    }
+
+    fn docids(ids: &[u32]) -> RoaringBitmap {
+        ids.iter().copied().collect()
+    }
+
+    #[test]
+    fn count_explicit_ranges() {
+        let ranges: FacetRanges =
+            serde_json::from_str(r#"[{"to": 10}, {"from": 10, "to": 50}, {"from": 50}]"#).unwrap();
+        let values = vec![
+            (2.0, docids(&[0])),
+            (9.99, docids(&[1, 2, 3])),
+            (10.0, docids(&[4, 5])),
+            (49.5, docids(&[6])),
+            (50.0, docids(&[7, 8, 9, 10])),
+            (1200.0, docids(&[11])),
+        ];
+
+        let counts = ranges.count(values).unwrap();
+        let counts: Vec<_> = counts.iter().map(|c| c.count).collect();
+        assert_eq!(counts, vec![4, 3, 5]);
+    }
+
+    #[test]
+    fn count_documents_with_several_values_once() {
+        let ranges: FacetRanges = serde_json::from_str(r#"[{"to": 10}, {"from": 10}]"#).unwrap();
+        let values = vec![
+            (2.0, docids(&[0, 1])),
+            (5.0, docids(&[0])),
+            (20.0, docids(&[0])),
+        ];
+
+        let counts = ranges.count(values.clone()).unwrap();
+        let counts: Vec<_> = counts.iter().map(|c| c.count).collect();
+        assert_eq!(counts, vec![2, 1]);
+
+        let histogram: FacetRanges = serde_json::from_str(r#"{"interval": 10}"#).unwrap();
+        let counts = histogram.count(values).unwrap();
+        let counts: Vec<_> = counts.iter().map(|c| c.count).collect();
+        assert_eq!(counts, vec![2, 1]);
+    }
+
+    #[test]
+    fn count_histogram() {
+        let ranges: FacetRanges = serde_json::from_str(r#"{"interval": 10}"#).unwrap();
+        let values = vec![
+            (-1.0, docids(&[0])),
+            (2.0, docids(&[1])),
+            (9.0, docids(&[2, 3, 4])),
+            (10.0, docids(&[5, 6])),
+            (35.0, docids(&[7])),
+        ];
+
+        let counts = ranges.count(values).unwrap();
+        assert_eq!(
+            counts,
+            vec![
+                FacetRangeCount {
+                    range: FacetRange {
+                        from: Some(-10.0),
+                        to: Some(0.0)
+                    },
+                    count: 1
+                },
+                FacetRangeCount {
+                    range: FacetRange {
+                        from: Some(0.0),
+                        to: Some(10.0)
+                    },
+                    count: 4
+                },
+                FacetRangeCount {
+                    range: FacetRange {
+                        from: Some(10.0),
+                        to: Some(20.0)
+                    },
+                    count: 2
+                },
+                FacetRangeCount {
+                    range: FacetRange {
+                        from: Some(30.0),
+                        to: Some(40.0)
+                    },
+                    count: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn histogram_interval_overflow() {
+        let ranges: FacetRanges = serde_json::from_str(r#"{"interval": 1e-300}"#).unwrap();
+        assert!(ranges.count(vec![(1.0, docids(&[0]))]).is_err());
+
+        let ranges: FacetRanges = serde_json::from_str(r#"{"interval": 1}"#).unwrap();
+        assert!(ranges.count(vec![(-1e300, docids(&[0]))]).is_err());
+    }
+
+    #[test]
+    fn invalid_histogram_interval() {
+        let ranges: FacetRanges = serde_json::from_str(r#"{"interval": 0}"#).unwrap();
+        assert!(ranges.validate().is_err());
+    }
+}
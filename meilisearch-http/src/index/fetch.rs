@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+
+use heed::RoTxn;
+use milli::obkv_to_json;
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::dates::{original_dates, restore_dates};
+use super::error::{IndexError, Result};
+use super::nested::unflatten;
+use super::search::parse_filter;
+use super::{Document, Index};
+
+pub const DEFAULT_DOCUMENTS_OFFSET: usize = 0;
+pub const DEFAULT_DOCUMENTS_LIMIT: usize = 20;
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FetchDocumentsQuery {
+    /// The external ids of the documents to fetch, as strings or integers.
+    pub ids: Option<Vec<Value>>,
+    pub filter: Option<Value>,
+    pub attributes_to_retrieve: Option<Vec<String>>,
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchDocumentsResult {
+    pub results: Vec<Document>,
+    pub offset: usize,
+    pub limit: usize,
+    pub total: u64,
+    /// The requested ids that match no document of the index.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct BrowseDocumentsQuery {
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+    pub attributes_to_retrieve: Option<Vec<String>>,
+    pub filter: Option<Value>,
+    /// The `next` cursor of the previous page, the page starts right after it.
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowseDocumentsResult {
+    pub results: Vec<Document>,
+    pub offset: usize,
+    pub limit: usize,
+    pub total: u64,
+    /// The cursor of the next page, missing on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+impl Index {
+    /// Returns the documents matching both the given ids and filter, read from a single
+    /// transaction. When ids are given the documents are returned in the requested order,
+    /// otherwise in the order of their internal ids.
+    pub fn fetch_documents(&self, query: FetchDocumentsQuery) -> Result<FetchDocumentsResult> {
+        let txn = self.read_txn()?;
+
+        let mut missing = Vec::new();
+        let ordered_ids = match query.ids {
+            Some(ids) => {
+                let external_ids = self.external_documents_ids(&txn)?;
+                let mut seen = HashSet::new();
+                let mut ordered_ids = Vec::with_capacity(ids.len());
+                for id in ids {
+                    let id = external_id(id)?;
+                    match external_ids.get(id.as_bytes()) {
+                        Some(internal_id) => {
+                            if seen.insert(internal_id) {
+                                ordered_ids.push(internal_id);
+                            }
+                        }
+                        None => missing.push(id),
+                    }
+                }
+                Some(ordered_ids)
+            }
+            None => None,
+        };
+
+        let filtered = self.filtered_candidates(&txn, query.filter.as_ref())?;
+        let candidates: Vec<u32> = match (ordered_ids, filtered) {
+            (Some(ids), Some(filtered)) => ids
+                .into_iter()
+                .filter(|id| filtered.contains(*id))
+                .collect(),
+            (Some(ids), None) => ids,
+            (None, Some(filtered)) => filtered.into_iter().collect(),
+            (None, None) => self.documents_ids(&txn)?.into_iter().collect(),
+        };
+
+        let offset = query.offset.unwrap_or(DEFAULT_DOCUMENTS_OFFSET);
+        let limit = query.limit.unwrap_or(DEFAULT_DOCUMENTS_LIMIT);
+        let page = candidates.iter().skip(offset).take(limit).copied();
+        let results = self.documents_json(&txn, page, &query.attributes_to_retrieve)?;
+
+        Ok(FetchDocumentsResult {
+            results,
+            offset,
+            limit,
+            total: candidates.len() as u64,
+            missing,
+        })
+    }
+
+    /// Returns a page of the documents of the index, or of those matching the filter, in the
+    /// order of their internal ids. A page starts either at an `offset` or right after the
+    /// document of a `cursor`, walking the index with the cursors costs the same for every page.
+    pub fn browse_documents(&self, query: BrowseDocumentsQuery) -> Result<BrowseDocumentsResult> {
+        if query.cursor.is_some() && query.offset.is_some() {
+            return Err(IndexError::CursorWithOffset);
+        }
+
+        let txn = self.read_txn()?;
+
+        let mut candidates = match self.filtered_candidates(&txn, query.filter.as_ref())? {
+            Some(filtered) => filtered,
+            None => self.documents_ids(&txn)?,
+        };
+        let total = candidates.len();
+
+        if let Some(ref cursor) = query.cursor {
+            let last = decode_cursor(cursor)?;
+            candidates.remove_range(0..u64::from(last) + 1);
+        }
+
+        let offset = query.offset.unwrap_or(DEFAULT_DOCUMENTS_OFFSET);
+        let limit = query.limit.unwrap_or(DEFAULT_DOCUMENTS_LIMIT);
+
+        let mut ids = candidates.iter().skip(offset);
+        let page: Vec<_> = ids.by_ref().take(limit).collect();
+        let next = match (page.last(), ids.next()) {
+            (Some(last), Some(_)) => Some(encode_cursor(*last)),
+            _ => None,
+        };
+        let results = self.documents_json(&txn, page, &query.attributes_to_retrieve)?;
+
+        Ok(BrowseDocumentsResult {
+            results,
+            offset,
+            limit,
+            total,
+            next,
+        })
+    }
+
+    /// Returns the ids of the documents matching the `filter`, or `None` when there is no filter
+    /// to apply.
+    pub(super) fn filtered_candidates(
+        &self,
+        txn: &RoTxn,
+        filter: Option<&Value>,
+    ) -> Result<Option<RoaringBitmap>> {
+        match filter {
+            Some(filter) => match parse_filter(filter, self, txn)? {
+                Some(condition) => Ok(Some(condition.evaluate(txn, self)?)),
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the documents of the given ids, in the same order, with their displayed and
+    /// retrieved attributes only.
+    fn documents_json<S: AsRef<str>>(
+        &self,
+        txn: &RoTxn,
+        ids: impl IntoIterator<Item = u32>,
+        attributes_to_retrieve: &Option<Vec<S>>,
+    ) -> Result<Vec<Document>> {
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let fields_to_display =
+            self.fields_to_display(txn, attributes_to_retrieve, &fields_ids_map)?;
+        let date_attributes = self.date_attributes(txn)?;
+        let nested_fields = self.nested_fields(txn)?;
+
+        let mut documents = Vec::new();
+        for (_id, obkv) in self.documents(txn, ids)? {
+            let original_dates = original_dates(&fields_ids_map, obkv)?;
+            let mut object = obkv_to_json(&fields_to_display, &fields_ids_map, obkv)?;
+            restore_dates(object.iter_mut(), &date_attributes, &original_dates);
+            documents.push(unflatten(object, &nested_fields));
+        }
+
+        Ok(documents)
+    }
+}
+
+fn external_id(id: Value) -> Result<String> {
+    match id {
+        Value::String(id) => Ok(id),
+        Value::Number(id) if id.is_u64() || id.is_i64() => Ok(id.to_string()),
+        other => Err(IndexError::InvalidDocumentId(other)),
+    }
+}
+
+/// The cursors are the internal id of the last document of a page. They are scrambled a bit so
+/// that they are not mistaken for a document id.
+const CURSOR_MASK: u32 = 0x5bd1_e995;
+
+fn encode_cursor(last: u32) -> String {
+    format!("{:08x}", last ^ CURSOR_MASK)
+}
+
+fn decode_cursor(cursor: &str) -> Result<u32> {
+    let invalid = || IndexError::InvalidCursor(cursor.to_string());
+    if cursor.len() != 8 || !cursor.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let last = u32::from_str_radix(cursor, 16).map_err(|_| invalid())?;
+    Ok(last ^ CURSOR_MASK)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trip() {
+        for id in [0, 1, 42, u32::MAX].iter() {
+            assert_eq!(decode_cursor(&encode_cursor(*id)).unwrap(), *id);
+        }
+    }
+
+    #[test]
+    fn invalid_cursor() {
+        assert!(decode_cursor("").is_err());
+        assert!(decode_cursor("12").is_err());
+        assert!(decode_cursor("zzzzzzzz").is_err());
+        assert!(decode_cursor("+1234567").is_err());
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use heed::RoTxn;
+use milli::{FieldsIdsMap, FilterCondition, Operator};
+use serde_json::{json, Map, Value};
+
+use super::dates::{parse_date_literal, DateAttributes};
+use super::error::{FacetError, IndexError, Result};
+use super::Index;
+
+const OPERATORS: &[&str] = &["=", "!=", ">", ">=", "<", "<=", "between"];
+
+/// Compiles a structured filter into a `FilterCondition`.
+///
+/// A node of the filter is either a combination of nodes, `{"and": [...]}` or `{"or": [...]}`,
+/// or a condition on a filterable attribute, `{"field": "price", "op": "<", "value": 20}`. The
+/// errors report the JSON pointer of the invalid node.
+pub fn compile_filter(index: &Index, txn: &RoTxn, filter: &Value) -> Result<FilterCondition> {
+    let compiler = FilterCompiler {
+        filterable_fields: index.faceted_fields(txn)?,
+        fields_ids_map: index.fields_ids_map(txn)?,
+        date_attributes: index.date_attributes(txn)?,
+        now: Utc::now(),
+    };
+    compiler.compile(filter, "")
+}
+
+struct FilterCompiler {
+    filterable_fields: HashSet<String>,
+    fields_ids_map: FieldsIdsMap,
+    date_attributes: DateAttributes,
+    now: DateTime<Utc>,
+}
+
+fn invalid(pointer: &str, message: impl Into<String>) -> FacetError {
+    FacetError::InvalidFilter {
+        pointer: if pointer.is_empty() { "/" } else { pointer }.to_string(),
+        message: message.into(),
+    }
+}
+
+/// Returns the pointer to the `token` child of the node at `pointer`, with the token escaped as
+/// RFC 6901 requires.
+fn child_pointer(pointer: &str, token: impl ToString) -> String {
+    let token = token.to_string().replace('~', "~0").replace('/', "~1");
+    format!("{}/{}", pointer, token)
+}
+
+impl FilterCompiler {
+    fn compile(&self, node: &Value, pointer: &str) -> Result<FilterCondition> {
+        let object = match node {
+            Value::Object(object) => object,
+            _ => return Err(invalid(pointer, "expected an object").into()),
+        };
+
+        let combinators: [(&str, Combinator); 2] =
+            [("and", FilterCondition::And), ("or", FilterCondition::Or)];
+        for (key, combine) in combinators.iter() {
+            if let Some(children) = object.get(*key) {
+                if object.len() > 1 {
+                    let message = format!("`{}` can't be used along with other keys", key);
+                    return Err(invalid(pointer, message).into());
+                }
+                let pointer = child_pointer(pointer, key);
+                return self.compile_children(children, &pointer, *combine);
+            }
+        }
+
+        if object.contains_key("field") {
+            return self.compile_condition(object, pointer);
+        }
+
+        let message = "expected `and`, `or`, or a condition with `field`, `op` and `value`";
+        Err(invalid(pointer, message).into())
+    }
+
+    fn compile_children(
+        &self,
+        children: &Value,
+        pointer: &str,
+        combine: Combinator,
+    ) -> Result<FilterCondition> {
+        let children = match children {
+            Value::Array(children) if !children.is_empty() => children,
+            _ => return Err(invalid(pointer, "expected a non-empty array").into()),
+        };
+
+        let mut condition: Option<FilterCondition> = None;
+        for (i, child) in children.iter().enumerate() {
+            let child = self.compile(child, &child_pointer(pointer, i))?;
+            condition = Some(match condition {
+                Some(lhs) => combine(Box::new(lhs), Box::new(child)),
+                None => child,
+            });
+        }
+
+        // The array is not empty, so there is at least one condition.
+        Ok(condition.unwrap())
+    }
+
+    fn compile_condition(
+        &self,
+        object: &Map<String, Value>,
+        pointer: &str,
+    ) -> Result<FilterCondition> {
+        if let Some(key) = object
+            .keys()
+            .find(|k| !matches!(k.as_str(), "field" | "op" | "value"))
+        {
+            let message = "unknown key, expected `field`, `op` or `value`";
+            return Err(invalid(&child_pointer(pointer, key), message).into());
+        }
+
+        let field_pointer = child_pointer(pointer, "field");
+        let field = match object.get("field") {
+            Some(Value::String(field)) => field,
+            _ => return Err(invalid(&field_pointer, "expected a string").into()),
+        };
+        let field_id = match self.fields_ids_map.id(field) {
+            Some(id) if self.filterable_fields.contains(field) => id,
+            _ => {
+                let message = format!("attribute `{}` is not filterable", field);
+                return Err(invalid(&field_pointer, message).into());
+            }
+        };
+
+        let op_pointer = child_pointer(pointer, "op");
+        let op = match object.get("op") {
+            Some(Value::String(op)) if OPERATORS.contains(&op.as_str()) => op.as_str(),
+            _ => {
+                let message = format!("expected one of `{}`", OPERATORS.join("`, `"));
+                return Err(invalid(&op_pointer, message).into());
+            }
+        };
+
+        let value_pointer = child_pointer(pointer, "value");
+        let mut value = object
+            .get("value")
+            .cloned()
+            .ok_or_else(|| invalid(&value_pointer, "missing value"))?;
+
+        // The dates are compared as timestamps.
+        if self.date_attributes.contains(field) {
+            self.convert_date_value(&mut value)?;
+        }
+        let value = &value;
+
+        let operator = match op {
+            "=" => {
+                let (number, string) = equality_value(value, &value_pointer)?;
+                Operator::Equal(number, string)
+            }
+            "!=" => {
+                let (number, string) = equality_value(value, &value_pointer)?;
+                Operator::NotEqual(number, string)
+            }
+            ">" => Operator::GreaterThan(number_value(value, &value_pointer)?),
+            ">=" => Operator::GreaterThanOrEqual(number_value(value, &value_pointer)?),
+            "<" => Operator::LowerThan(number_value(value, &value_pointer)?),
+            "<=" => Operator::LowerThanOrEqual(number_value(value, &value_pointer)?),
+            _ => match value {
+                Value::Array(bounds) if bounds.len() == 2 => {
+                    let from = number_value(&bounds[0], &child_pointer(&value_pointer, 0))?;
+                    let to = number_value(&bounds[1], &child_pointer(&value_pointer, 1))?;
+                    Operator::Between(from, to)
+                }
+                _ => return Err(invalid(&value_pointer, "expected an array of two numbers").into()),
+            },
+        };
+
+        Ok(FilterCondition::Operator(field_id, operator))
+    }
+}
+
+impl FilterCompiler {
+    fn convert_date_value(&self, value: &mut Value) -> Result<()> {
+        match value {
+            Value::String(s) => {
+                if let Some(timestamp) = parse_date_literal(s, self.now)? {
+                    *value = Value::from(timestamp);
+                }
+            }
+            Value::Array(values) => {
+                for value in values {
+                    self.convert_date_value(value)?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// Renders a `FilterCondition` in the structured filter format, nested combinations of the
+/// same kind are flattened.
+pub fn filter_to_json(condition: &FilterCondition, fields_ids_map: &FieldsIdsMap) -> Value {
+    fn flatten<'a>(
+        condition: &'a FilterCondition,
+        and: bool,
+        children: &mut Vec<&'a FilterCondition>,
+    ) {
+        match condition {
+            FilterCondition::And(lhs, rhs) if and => {
+                flatten(lhs, and, children);
+                flatten(rhs, and, children);
+            }
+            FilterCondition::Or(lhs, rhs) if !and => {
+                flatten(lhs, and, children);
+                flatten(rhs, and, children);
+            }
+            condition => children.push(condition),
+        }
+    }
+
+    let (field_id, operator) = match condition {
+        FilterCondition::Operator(field_id, operator) => (field_id, operator),
+        FilterCondition::And(..) | FilterCondition::Or(..) => {
+            let and = matches!(condition, FilterCondition::And(..));
+            let mut children = Vec::new();
+            flatten(condition, and, &mut children);
+            let children: Vec<_> = children
+                .into_iter()
+                .map(|child| filter_to_json(child, fields_ids_map))
+                .collect();
+            let key = if and { "and" } else { "or" };
+            return json!({ key: children });
+        }
+    };
+
+    let equality = |number: &Option<f64>, string: &str| match number {
+        Some(number) => json!(number),
+        None => json!(string),
+    };
+    let (op, value) = match operator {
+        Operator::Equal(number, string) => ("=", equality(number, string)),
+        Operator::NotEqual(number, string) => ("!=", equality(number, string)),
+        Operator::GreaterThan(number) => (">", json!(number)),
+        Operator::GreaterThanOrEqual(number) => (">=", json!(number)),
+        Operator::LowerThan(number) => ("<", json!(number)),
+        Operator::LowerThanOrEqual(number) => ("<=", json!(number)),
+        Operator::Between(from, to) => ("between", json!([from, to])),
+    };
+
+    json!({
+        "field": fields_ids_map.name(*field_id).unwrap_or_default(),
+        "op": op,
+        "value": value,
+    })
+}
+
+type Combinator = fn(Box<FilterCondition>, Box<FilterCondition>) -> FilterCondition;
+
+/// Returns the number and the normalized string values used by milli to compare facets.
+fn equality_value(value: &Value, pointer: &str) -> Result<(Option<f64>, String)> {
+    let value = match value {
+        Value::String(s) => s.trim().to_lowercase(),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        _ => return Err(invalid(pointer, "expected a string, a number or a boolean").into()),
+    };
+    Ok((value.parse().ok(), value))
+}
+
+fn number_value(value: &Value, pointer: &str) -> Result<f64> {
+    match value.as_f64() {
+        Some(number) => Ok(number),
+        None => Err(invalid(pointer, "expected a number").into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn compiler() -> FilterCompiler {
+        let mut fields_ids_map = FieldsIdsMap::new();
+        fields_ids_map.insert("price").unwrap();
+        fields_ids_map.insert("brand").unwrap();
+        fields_ids_map.insert("title").unwrap();
+        let filterable_fields = vec!["price", "brand"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        FilterCompiler {
+            filterable_fields,
+            fields_ids_map,
+            date_attributes: DateAttributes::new(),
+            now: Utc::now(),
+        }
+    }
+
+    fn error_pointer(filter: Value) -> String {
+        match compiler().compile(&filter, "") {
+            Err(IndexError::Facet(FacetError::InvalidFilter { pointer, .. })) => pointer,
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn render_compiled_filter() {
+        let compiler = compiler();
+        let filter = json!({
+            "and": [
+                { "field": "price", "op": ">=", "value": 20 },
+                { "field": "price", "op": "<", "value": 50 },
+                { "or": [
+                    { "field": "brand", "op": "=", "value": "Nike" },
+                    { "field": "brand", "op": "!=", "value": 12 },
+                    { "field": "price", "op": "between", "value": [100, 200] },
+                ]},
+            ]
+        });
+
+        let condition = compiler.compile(&filter, "").unwrap();
+        let expected = json!({
+            "and": [
+                { "field": "price", "op": ">=", "value": 20.0 },
+                { "field": "price", "op": "<", "value": 50.0 },
+                { "or": [
+                    { "field": "brand", "op": "=", "value": "nike" },
+                    { "field": "brand", "op": "!=", "value": 12.0 },
+                    { "field": "price", "op": "between", "value": [100.0, 200.0] },
+                ]},
+            ]
+        });
+        assert_eq!(
+            filter_to_json(&condition, &compiler.fields_ids_map),
+            expected
+        );
+    }
+
+    #[test]
+    fn compile_nested_filter() {
+        let filter = json!({
+            "and": [
+                { "field": "price", "op": "<", "value": 20 },
+                { "or": [
+                    { "field": "brand", "op": "=", "value": "Nike" },
+                    { "field": "price", "op": "between", "value": [100, 200] },
+                ]},
+            ]
+        });
+
+        let condition = compiler().compile(&filter, "").unwrap();
+        match condition {
+            FilterCondition::And(lhs, rhs) => {
+                assert!(matches!(
+                    *lhs,
+                    FilterCondition::Operator(0, Operator::LowerThan(n)) if n == 20.0
+                ));
+                match *rhs {
+                    FilterCondition::Or(lhs, rhs) => {
+                        assert!(matches!(
+                            *lhs,
+                            FilterCondition::Operator(1, Operator::Equal(None, ref s)) if s == "nike"
+                        ));
+                        assert!(matches!(
+                            *rhs,
+                            FilterCondition::Operator(0, Operator::Between(from, to))
+                                if from == 100.0 && to == 200.0
+                        ));
+                    }
+                    other => panic!("unexpected condition: {:?}", other),
+                }
+            }
+            other => panic!("unexpected condition: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn report_invalid_node_pointer() {
+        assert_eq!(error_pointer(json!("price < 20")), "/");
+        assert_eq!(
+            error_pointer(json!({ "field": "price", "op": "<", "value": 20, "a/b~c": 1 })),
+            "/a~1b~0c"
+        );
+        assert_eq!(error_pointer(json!({ "and": [] })), "/and");
+        assert_eq!(
+            error_pointer(json!({ "and": [
+                { "field": "price", "op": "<", "value": 20 },
+                { "or": [{ "field": "price", "op": "~", "value": 20 }] },
+            ]})),
+            "/and/1/or/0/op"
+        );
+        assert_eq!(
+            error_pointer(json!({ "field": "title", "op": "=", "value": "shoes" })),
+            "/field"
+        );
+        assert_eq!(
+            error_pointer(json!({ "field": "price", "op": "<", "value": "cheap" })),
+            "/value"
+        );
+        assert_eq!(
+            error_pointer(json!({ "field": "price", "op": "between", "value": [1, "2"] })),
+            "/value/1"
+        );
+        assert_eq!(
+            error_pointer(json!({ "field": "price", "op": "=", "value": 2, "not": true })),
+            "/not"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use heed::RoTxn;
+use indexmap::IndexMap;
+use milli::{DocumentId, FilterCondition, Search};
+use roaring::RoaringBitmap;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::error::{FacetError, Result};
+use super::facets::quote_facet_value;
+use super::search::Deadline;
+use super::Index;
+
+/// The number of ranked documents fetched from milli at once while filling the groups.
+pub const GROUP_BY_CHUNK_SIZE: usize = 1000;
+
+const fn default_group_size() -> usize {
+    1
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GroupBy {
+    pub field: String,
+    #[serde(default = "default_group_size")]
+    pub size: usize,
+}
+
+/// A group of ranked documents sharing the same value for the grouping field.
+pub struct RankedGroup {
+    pub value: Value,
+    pub documents_ids: Vec<DocumentId>,
+    /// The number of candidates that belong to this group.
+    pub total: u64,
+}
+
+impl RankedGroup {
+    fn is_complete(&self, size: usize) -> bool {
+        self.documents_ids.len() as u64 >= self.total.min(size as u64)
+    }
+}
+
+impl Index {
+    /// Groups the ranked documents by the value of the `group_by` field.
+    ///
+    /// Groups are ordered by their best ranked document and only the groups in the
+    /// `offset..offset + limit` window are returned. The ranked documents are fetched from the
+    /// `search` by chunks, starting with `documents_ids`, until all the requested groups are
+    /// filled, or until the `deadline` is exceeded. Documents without a scalar value for the field
+    /// don't belong to any group.
+    pub fn group_documents(
+        &self,
+        txn: &RoTxn,
+        search: &mut Search,
+        documents_ids: Vec<DocumentId>,
+        candidates: &RoaringBitmap,
+        group_by: &GroupBy,
+        offset: usize,
+        limit: usize,
+        deadline: &Deadline,
+    ) -> Result<Vec<RankedGroup>> {
+        if !self.faceted_fields(txn)?.contains(&group_by.field) {
+            return Err(FacetError::NotFilterable(group_by.field.clone()).into());
+        }
+
+        let field_id = match self.fields_ids_map(txn)?.id(&group_by.field) {
+            Some(id) => id,
+            None => return Ok(Vec::new()),
+        };
+
+        let wanted = offset + limit;
+        let mut groups: IndexMap<String, RankedGroup> = IndexMap::new();
+        let mut chunk = documents_ids;
+        let mut seen = 0;
+
+        loop {
+            for (id, obkv) in self.documents(txn, chunk.iter().copied())? {
+                let value: Value = match obkv.get(field_id) {
+                    Some(value) => serde_json::from_slice(value)?,
+                    None => continue,
+                };
+
+                // Facet values are normalized by milli, so are the groups keys.
+                let key = match value {
+                    Value::String(ref s) => s.to_lowercase(),
+                    Value::Number(ref n) => n.to_string(),
+                    Value::Bool(b) => b.to_string(),
+                    _ => continue,
+                };
+
+                if !groups.contains_key(&key) {
+                    if groups.len() >= wanted {
+                        continue;
+                    }
+                    let total = self.group_total(txn, &group_by.field, &key, candidates)?;
+                    let group = RankedGroup {
+                        value,
+                        documents_ids: Vec::new(),
+                        total,
+                    };
+                    groups.insert(key.clone(), group);
+                }
+
+                let group = &mut groups[&key];
+                if group.documents_ids.len() < group_by.size {
+                    group.documents_ids.push(id);
+                }
+            }
+
+            seen += chunk.len();
+
+            let filled =
+                groups.len() >= wanted && groups.values().all(|g| g.is_complete(group_by.size));
+            if filled || chunk.len() < GROUP_BY_CHUNK_SIZE || deadline.is_exceeded() {
+                break;
+            }
+
+            search.offset(seen);
+            search.limit(GROUP_BY_CHUNK_SIZE);
+            chunk = search.execute()?.documents_ids;
+        }
+
+        Ok(groups
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(_, group)| group)
+            .collect())
+    }
+
+    fn group_total(
+        &self,
+        txn: &RoTxn,
+        field: &str,
+        value: &str,
+        candidates: &RoaringBitmap,
+    ) -> Result<u64> {
+        let expr = format!(
+            "{} = {}",
+            quote_facet_value(field)?,
+            quote_facet_value(value)?
+        );
+        let condition = FilterCondition::from_str(txn, self, &expr)?;
+        let docids = condition.evaluate(txn, self)? & candidates;
+        Ok(docids.len())
+    }
+}
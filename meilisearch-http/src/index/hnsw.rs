@@ -0,0 +1,498 @@
+//! A hierarchical navigable small world graph, the approximate nearest neighbours structure of
+//! the vectors of the documents.
+//!
+//! Each node is linked to its nearest neighbours on every layer up to its level, the levels
+//! being drawn at random with an exponentially decreasing probability. A search starts from the
+//! entry point, the node with the highest level, and greedily walks down the layers towards the
+//! nodes closest to the query.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet};
+
+use milli::DocumentId;
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+
+use super::error::Result;
+
+/// The number of neighbours of a node on the upper layers, twice as many are kept on layer 0.
+const M: usize = 16;
+/// The number of candidates considered when linking a new node.
+const EF_CONSTRUCTION: usize = 100;
+const MAX_LEVEL: usize = 16;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Node {
+    /// The neighbours of the node on each layer, starting from layer 0.
+    pub neighbours: Vec<Vec<DocumentId>>,
+}
+
+impl Node {
+    fn new(level: usize) -> Self {
+        Self {
+            neighbours: vec![Vec::new(); level + 1],
+        }
+    }
+
+    fn level(&self) -> usize {
+        self.neighbours.len().saturating_sub(1)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct EntryPoint {
+    pub id: DocumentId,
+    pub level: usize,
+}
+
+/// Where the graph and the vectors of its nodes are stored.
+pub trait GraphStore {
+    fn vector(&self, id: DocumentId) -> Result<Option<Vec<f32>>>;
+    fn node(&self, id: DocumentId) -> Result<Option<Node>>;
+    fn entry_point(&self) -> Result<Option<EntryPoint>>;
+    fn ids(&self) -> Result<RoaringBitmap>;
+}
+
+pub trait GraphStoreMut: GraphStore {
+    fn put_vector(&mut self, id: DocumentId, vector: &[f32]) -> Result<()>;
+    fn put_node(&mut self, id: DocumentId, node: &Node) -> Result<()>;
+    /// Deletes the vector and the node of a document.
+    fn delete(&mut self, id: DocumentId) -> Result<()>;
+    fn set_entry_point(&mut self, entry_point: Option<EntryPoint>) -> Result<()>;
+}
+
+/// A node found by a search, with its distance to the query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbour {
+    pub distance: f32,
+    pub id: DocumentId,
+}
+
+impl Eq for Neighbour {}
+
+impl PartialOrd for Neighbour {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Neighbour {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .partial_cmp(&other.distance)
+            .unwrap_or(Ordering::Equal)
+            .then(self.id.cmp(&other.id))
+    }
+}
+
+/// The cosine distance between two vectors, from 0 for vectors pointing in the same direction
+/// to 2 for opposite vectors.
+pub fn distance(a: &[f32], b: &[f32]) -> f32 {
+    let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 1.0;
+    }
+    1.0 - dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+fn max_neighbours(layer: usize) -> usize {
+    if layer == 0 {
+        2 * M
+    } else {
+        M
+    }
+}
+
+fn random_level() -> usize {
+    let uniform: f64 = rand::random();
+    let level = -uniform.max(f64::MIN_POSITIVE).ln() / (M as f64).ln();
+    (level.floor() as usize).min(MAX_LEVEL)
+}
+
+fn neighbour(store: &impl GraphStore, query: &[f32], id: DocumentId) -> Result<Option<Neighbour>> {
+    Ok(store.vector(id)?.map(|vector| Neighbour {
+        distance: distance(query, &vector),
+        id,
+    }))
+}
+
+/// Returns the `ef` nodes of `layer` closest to `query` reachable from the `entry_points`,
+/// sorted by distance.
+fn search_layer(
+    store: &impl GraphStore,
+    query: &[f32],
+    entry_points: &[Neighbour],
+    ef: usize,
+    layer: usize,
+) -> Result<Vec<Neighbour>> {
+    let mut visited: HashSet<_> = entry_points.iter().map(|n| n.id).collect();
+    let mut candidates: BinaryHeap<_> = entry_points.iter().copied().map(Reverse).collect();
+    let mut results: BinaryHeap<_> = entry_points.iter().copied().collect();
+
+    while let Some(Reverse(current)) = candidates.pop() {
+        match results.peek() {
+            Some(farthest) if results.len() >= ef && current.distance > farthest.distance => break,
+            _ => (),
+        }
+
+        let node = match store.node(current.id)? {
+            Some(node) => node,
+            None => continue,
+        };
+
+        for &id in node.neighbours.get(layer).into_iter().flatten() {
+            if !visited.insert(id) {
+                continue;
+            }
+
+            // The links to the nodes that have been removed are skipped.
+            let neighbour = match neighbour(store, query, id)? {
+                Some(neighbour) => neighbour,
+                None => continue,
+            };
+
+            let is_closer = results
+                .peek()
+                .map_or(true, |farthest| neighbour.distance < farthest.distance);
+            if results.len() < ef || is_closer {
+                candidates.push(Reverse(neighbour));
+                results.push(neighbour);
+                if results.len() > ef {
+                    results.pop();
+                }
+            }
+        }
+    }
+
+    Ok(results.into_sorted_vec())
+}
+
+/// Keeps the `max` links of the node `id` closest to it.
+fn shrink(
+    store: &impl GraphStore,
+    id: DocumentId,
+    links: &mut Vec<DocumentId>,
+    max: usize,
+) -> Result<()> {
+    if links.len() <= max {
+        return Ok(());
+    }
+
+    let vector = match store.vector(id)? {
+        Some(vector) => vector,
+        None => return Ok(()),
+    };
+
+    let mut neighbours = Vec::with_capacity(links.len());
+    for &link in links.iter() {
+        if let Some(neighbour) = neighbour(store, &vector, link)? {
+            neighbours.push(neighbour);
+        }
+    }
+    neighbours.sort();
+    *links = neighbours.into_iter().take(max).map(|n| n.id).collect();
+
+    Ok(())
+}
+
+/// Links the node `from` to the node `to` on `layer`.
+fn connect(
+    store: &mut impl GraphStoreMut,
+    from: DocumentId,
+    to: DocumentId,
+    layer: usize,
+) -> Result<()> {
+    let mut node = match store.node(from)? {
+        Some(node) => node,
+        None => return Ok(()),
+    };
+
+    if let Some(links) = node.neighbours.get_mut(layer) {
+        if links.contains(&to) {
+            return Ok(());
+        }
+        links.push(to);
+        shrink(&*store, from, links, max_neighbours(layer))?;
+    }
+
+    store.put_node(from, &node)
+}
+
+/// Adds the vector of a document to the graph, replacing its previous vector.
+pub fn insert(store: &mut impl GraphStoreMut, id: DocumentId, vector: &[f32]) -> Result<()> {
+    if store.node(id)?.is_some() {
+        remove(store, id)?;
+    }
+
+    let level = random_level();
+    store.put_vector(id, vector)?;
+
+    let entry = match store.entry_point()? {
+        Some(entry) => entry,
+        None => {
+            store.put_node(id, &Node::new(level))?;
+            return store.set_entry_point(Some(EntryPoint { id, level }));
+        }
+    };
+
+    let mut entry_points: Vec<_> = neighbour(&*store, vector, entry.id)?.into_iter().collect();
+    for layer in (level + 1..=entry.level).rev() {
+        entry_points = search_layer(&*store, vector, &entry_points, 1, layer)?;
+    }
+
+    let mut node = Node::new(level);
+    for layer in (0..=level.min(entry.level)).rev() {
+        let found = search_layer(&*store, vector, &entry_points, EF_CONSTRUCTION, layer)?;
+        node.neighbours[layer] = found
+            .iter()
+            .filter(|n| n.id != id)
+            .take(max_neighbours(layer))
+            .map(|n| n.id)
+            .collect();
+        for &neighbour in &node.neighbours[layer] {
+            connect(store, neighbour, id, layer)?;
+        }
+        entry_points = found;
+    }
+    store.put_node(id, &node)?;
+
+    if level > entry.level {
+        store.set_entry_point(Some(EntryPoint { id, level }))?;
+    }
+
+    Ok(())
+}
+
+/// Removes the vector of a document from the graph. Its neighbours are linked to each other,
+/// so the graph stays navigable.
+pub fn remove(store: &mut impl GraphStoreMut, id: DocumentId) -> Result<()> {
+    let node = match store.node(id)? {
+        Some(node) => node,
+        None => return Ok(()),
+    };
+    store.delete(id)?;
+
+    let mut new_entry: Option<EntryPoint> = None;
+    for (layer, links) in node.neighbours.iter().enumerate() {
+        for &neighbour in links {
+            let mut neighbour_node = match store.node(neighbour)? {
+                Some(node) => node,
+                None => continue,
+            };
+
+            if let Some(neighbour_links) = neighbour_node.neighbours.get_mut(layer) {
+                neighbour_links.retain(|&link| link != id);
+                for &other in links {
+                    if other != neighbour && !neighbour_links.contains(&other) {
+                        neighbour_links.push(other);
+                    }
+                }
+                shrink(&*store, neighbour, neighbour_links, max_neighbours(layer))?;
+            }
+            store.put_node(neighbour, &neighbour_node)?;
+
+            let level = neighbour_node.level();
+            if new_entry.map_or(true, |entry| level > entry.level) {
+                new_entry = Some(EntryPoint {
+                    id: neighbour,
+                    level,
+                });
+            }
+        }
+    }
+
+    if store.entry_point()?.map_or(false, |entry| entry.id == id) {
+        // A node that wasn't linked to any other is only left when the graph is fragmented,
+        // any remaining node is then a valid entry point.
+        if new_entry.is_none() {
+            if let Some(other) = store.ids()?.iter().next() {
+                let level = store.node(other)?.map_or(0, |node| node.level());
+                new_entry = Some(EntryPoint { id: other, level });
+            }
+        }
+        store.set_entry_point(new_entry)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the `k` nodes closest to `query`, sorted by distance. `ef` is the number of
+/// candidates considered on the bottom layer, the higher the more accurate.
+pub fn search(
+    store: &impl GraphStore,
+    query: &[f32],
+    k: usize,
+    ef: usize,
+) -> Result<Vec<Neighbour>> {
+    let entry = match store.entry_point()? {
+        Some(entry) => entry,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut entry_points: Vec<_> = neighbour(store, query, entry.id)?.into_iter().collect();
+    for layer in (1..=entry.level).rev() {
+        entry_points = search_layer(store, query, &entry_points, 1, layer)?;
+    }
+
+    let mut found = search_layer(store, query, &entry_points, ef.max(k), 0)?;
+    found.truncate(k);
+    Ok(found)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        vectors: HashMap<DocumentId, Vec<f32>>,
+        nodes: HashMap<DocumentId, Node>,
+        entry_point: Option<EntryPoint>,
+    }
+
+    impl GraphStore for MemoryStore {
+        fn vector(&self, id: DocumentId) -> Result<Option<Vec<f32>>> {
+            Ok(self.vectors.get(&id).cloned())
+        }
+
+        fn node(&self, id: DocumentId) -> Result<Option<Node>> {
+            Ok(self.nodes.get(&id).cloned())
+        }
+
+        fn entry_point(&self) -> Result<Option<EntryPoint>> {
+            Ok(self.entry_point)
+        }
+
+        fn ids(&self) -> Result<RoaringBitmap> {
+            Ok(self.vectors.keys().copied().collect())
+        }
+    }
+
+    impl GraphStoreMut for MemoryStore {
+        fn put_vector(&mut self, id: DocumentId, vector: &[f32]) -> Result<()> {
+            self.vectors.insert(id, vector.to_vec());
+            Ok(())
+        }
+
+        fn put_node(&mut self, id: DocumentId, node: &Node) -> Result<()> {
+            self.nodes.insert(id, node.clone());
+            Ok(())
+        }
+
+        fn delete(&mut self, id: DocumentId) -> Result<()> {
+            self.vectors.remove(&id);
+            self.nodes.remove(&id);
+            Ok(())
+        }
+
+        fn set_entry_point(&mut self, entry_point: Option<EntryPoint>) -> Result<()> {
+            self.entry_point = entry_point;
+            Ok(())
+        }
+    }
+
+    fn random_vectors(count: usize) -> Vec<Vec<f32>> {
+        let mut rng = StdRng::seed_from_u64(42);
+        (0..count)
+            .map(|_| (0..8).map(|_| rng.gen_range(-1.0, 1.0)).collect())
+            .collect()
+    }
+
+    fn exact_search(store: &MemoryStore, query: &[f32], k: usize) -> Vec<DocumentId> {
+        let mut all: Vec<_> = store
+            .vectors
+            .iter()
+            .map(|(&id, vector)| Neighbour {
+                distance: distance(query, vector),
+                id,
+            })
+            .collect();
+        all.sort();
+        all.into_iter().take(k).map(|n| n.id).collect()
+    }
+
+    #[test]
+    fn cosine_distance() {
+        assert_eq!(distance(&[1.0, 0.0], &[2.0, 0.0]), 0.0);
+        assert_eq!(distance(&[1.0, 0.0], &[0.0, 1.0]), 1.0);
+        assert_eq!(distance(&[1.0, 0.0], &[-1.0, 0.0]), 2.0);
+        assert_eq!(distance(&[0.0, 0.0], &[1.0, 0.0]), 1.0);
+    }
+
+    #[test]
+    fn search_finds_nearest_neighbours() {
+        let vectors = random_vectors(500);
+        let mut store = MemoryStore::default();
+        for (id, vector) in vectors.iter().enumerate() {
+            insert(&mut store, id as DocumentId, vector).unwrap();
+        }
+
+        let mut found = 0;
+        for vector in vectors.iter().take(50) {
+            let expected = exact_search(&store, vector, 10);
+            let result: Vec<_> = search(&store, vector, 10, 64)
+                .unwrap()
+                .into_iter()
+                .map(|n| n.id)
+                .collect();
+            assert_eq!(result[0], expected[0]);
+            found += result.iter().filter(|id| expected.contains(id)).count();
+        }
+
+        // The search is approximate, but must find almost all the nearest neighbours.
+        assert!(found >= 450, "recall too low: {}/500", found);
+    }
+
+    #[test]
+    fn removed_vectors_are_not_found() {
+        let vectors = random_vectors(200);
+        let mut store = MemoryStore::default();
+        for (id, vector) in vectors.iter().enumerate() {
+            insert(&mut store, id as DocumentId, vector).unwrap();
+        }
+
+        let entry_point = store.entry_point.unwrap().id;
+        remove(&mut store, entry_point).unwrap();
+        for id in (0..200).step_by(2) {
+            remove(&mut store, id).unwrap();
+        }
+        assert!(store.entry_point.is_some());
+
+        for (id, vector) in vectors.iter().enumerate() {
+            let result = search(&store, vector, 5, 64).unwrap();
+            assert!(result.iter().all(|n| n.id % 2 == 1 && n.id != entry_point));
+            if id % 2 == 1 && id as DocumentId != entry_point {
+                assert_eq!(result[0].id, id as DocumentId);
+            }
+        }
+
+        for id in 0..200 {
+            remove(&mut store, id).unwrap();
+        }
+        assert_eq!(store.entry_point, None);
+        assert!(search(&store, &vectors[0], 5, 64).unwrap().is_empty());
+    }
+
+    #[test]
+    fn replace_vector() {
+        let mut store = MemoryStore::default();
+        insert(&mut store, 0, &[1.0, 0.0]).unwrap();
+        insert(&mut store, 1, &[0.0, 1.0]).unwrap();
+        insert(&mut store, 0, &[0.0, 0.9]).unwrap();
+
+        let result = search(&store, &[0.1, 1.0], 2, 16).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(store.nodes.len(), 2);
+    }
+}
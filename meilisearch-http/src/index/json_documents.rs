@@ -0,0 +1,147 @@
+use std::io::{self, BufRead, BufReader};
+
+use serde::de::Error as _;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::error::Result;
+
+/// The documents of a JSON array payload, parsed one at a time so that the whole array is never
+/// held in memory.
+pub struct JsonArrayDocuments<R> {
+    reader: BufReader<R>,
+    state: State,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Start,
+    First,
+    Next,
+    Done,
+}
+
+impl<R: io::Read> JsonArrayDocuments<R> {
+    pub fn new(content: R) -> Self {
+        Self {
+            reader: BufReader::new(content),
+            state: State::Start,
+        }
+    }
+
+    /// Skips the whitespaces and returns the next byte, without consuming it.
+    fn peek(&mut self) -> Result<Option<u8>> {
+        loop {
+            let buffer = self.reader.fill_buf()?;
+            if buffer.is_empty() {
+                return Ok(None);
+            }
+            match buffer.iter().position(|b| !b.is_ascii_whitespace()) {
+                Some(i) => {
+                    let byte = buffer[i];
+                    self.reader.consume(i);
+                    return Ok(Some(byte));
+                }
+                None => {
+                    let len = buffer.len();
+                    self.reader.consume(len);
+                }
+            }
+        }
+    }
+
+    /// Consumes the next byte if it is `expected`.
+    fn expect(&mut self, expected: u8, message: &str) -> Result<()> {
+        match self.peek()? {
+            Some(byte) if byte == expected => {
+                self.reader.consume(1);
+                Ok(())
+            }
+            _ => Err(serde_json::Error::custom(message).into()),
+        }
+    }
+
+    fn next_document(&mut self) -> Result<Option<Map<String, Value>>> {
+        match self.state {
+            State::Start => {
+                self.expect(b'[', "expected an array of documents")?;
+                self.state = State::First;
+            }
+            State::Done => return Ok(None),
+            _ => (),
+        }
+
+        if self.peek()? == Some(b']') {
+            self.reader.consume(1);
+            self.state = State::Done;
+            if self.peek()?.is_some() {
+                return Err(serde_json::Error::custom("trailing characters").into());
+            }
+            return Ok(None);
+        }
+
+        if self.state == State::Next {
+            self.expect(b',', "expected `,` or `]`")?;
+        }
+        self.state = State::Next;
+
+        // An object ends with its closing brace, the deserializer doesn't read past it.
+        let mut deserializer = serde_json::Deserializer::from_reader(&mut self.reader);
+        let document = Map::deserialize(&mut deserializer)?;
+        Ok(Some(document))
+    }
+}
+
+impl<R: io::Read> Iterator for JsonArrayDocuments<R> {
+    type Item = Result<Map<String, Value>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let document = self.next_document();
+        if document.is_err() {
+            self.state = State::Done;
+        }
+        document.transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn parse(content: &str) -> Result<Vec<Value>> {
+        JsonArrayDocuments::new(content.as_bytes())
+            .map(|document| document.map(Value::Object))
+            .collect()
+    }
+
+    #[test]
+    fn stream_array() {
+        let content = r#" [ { "id": 1, "tags": ["a", "b"] } ,
+            {"id":2,"author":{"name":"x"}}]
+        "#;
+        assert_eq!(
+            parse(content).unwrap(),
+            vec![
+                json!({ "id": 1, "tags": ["a", "b"] }),
+                json!({ "id": 2, "author": { "name": "x" } }),
+            ]
+        );
+        assert!(parse("[]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn reject_invalid_arrays() {
+        for content in &[
+            r#"{ "id": 1 }"#,
+            r#"[{ "id": 1 } { "id": 2 }]"#,
+            r#"[{ "id": 1 },]"#,
+            r#"[1, 2]"#,
+            r#"[{ "id": 1 }"#,
+            r#"[{ "id": 1 }] []"#,
+        ] {
+            assert!(parse(content).is_err(), "{}", content);
+        }
+    }
+}
@@ -5,15 +5,33 @@ use std::ops::Deref;
 use std::path::Path;
 use std::sync::Arc;
 
-use heed::{EnvOpenOptions, RoTxn};
+use chrono::{DateTime, Utc};
+use heed::types::{SerdeJson, Str};
+use heed::{EnvOpenOptions, RoTxn, RwTxn};
 use milli::obkv_to_json;
 use serde::{de::Deserializer, Deserialize};
 use serde_json::{Map, Value};
 
 use crate::helpers::EnvSizer;
+use dates::{original_dates, restore_dates};
 use error::Result;
-
-pub use search::{default_crop_length, SearchQuery, SearchResult, DEFAULT_SEARCH_LIMIT};
+use nested::{field_ids, unflatten};
+use rules::CompiledPatterns;
+
+pub use compression::{decode, decode_file, decoded_size, ContentEncoding};
+pub use csv_documents::{CsvDocuments, DEFAULT_CSV_DELIMITER};
+pub use dates::has_relative_dates;
+pub use explain::SearchExplanation;
+pub use export::ExportFormat;
+pub use facets::HierarchicalFacets;
+pub use fetch::{
+    BrowseDocumentsQuery, BrowseDocumentsResult, FetchDocumentsQuery, FetchDocumentsResult,
+};
+pub use pagination::Pagination;
+pub use rules::{Rule, Rules};
+pub use schema::{DocumentSchema, InvalidDocuments};
+pub use search::{SearchHit, SearchQuery, SearchResult};
+pub use search_defaults::SearchDefaults;
 pub use updates::{Checked, Facets, Settings, Unchecked};
 
 use self::error::IndexError;
@@ -21,14 +39,37 @@ use self::error::IndexError;
 pub mod error;
 pub mod update_handler;
 
+mod boost;
+mod compression;
+mod csv_documents;
+mod dates;
 mod dump;
+mod explain;
+mod export;
+mod facets;
+mod fetch;
+mod filter;
+mod group;
+mod hnsw;
+mod json_documents;
+mod nested;
+mod pagination;
+mod rules;
+mod schema;
 mod search;
+mod search_defaults;
 mod updates;
+mod vectors;
 
 pub type Document = Map<String, Value>;
 
+/// The key under which the update time of the data we store next to milli's is kept. It is
+/// apart from the `updated-at` key of milli, whose format is milli's own.
+const UPDATED_AT_KEY: &str = "meilisearch-updated-at";
+
+/// A milli index, along with the compiled query patterns of its rules.
 #[derive(Clone)]
-pub struct Index(pub Arc<milli::Index>);
+pub struct Index(pub Arc<milli::Index>, CompiledPatterns);
 
 impl Deref for Index {
     type Target = milli::Index;
@@ -47,12 +88,16 @@ where
 }
 
 impl Index {
+    pub fn new(index: Arc<milli::Index>) -> Self {
+        Index(index, CompiledPatterns::default())
+    }
+
     pub fn open(path: impl AsRef<Path>, size: usize) -> Result<Self> {
         create_dir_all(&path)?;
         let mut options = EnvOpenOptions::new();
         options.map_size(size);
         let index = milli::Index::new(options, &path)?;
-        Ok(Index(Arc::new(index)))
+        Ok(Index::new(Arc::new(index)))
     }
 
     pub fn settings(&self) -> Result<Settings<Checked>> {
@@ -99,6 +144,12 @@ impl Index {
             })
             .collect();
 
+        let hierarchical_facets = self.hierarchical_facets(&txn)?;
+        let date_attributes = self.date_attributes(&txn)?;
+        let search_defaults = self.search_defaults(&txn)?;
+        let pagination = self.pagination(&txn)?;
+        let document_schema = self.document_schema(&txn)?;
+
         Ok(Settings {
             displayed_attributes: Some(displayed_attributes),
             searchable_attributes: Some(searchable_attributes),
@@ -107,35 +158,15 @@ impl Index {
             stop_words: Some(Some(stop_words)),
             distinct_attribute: Some(distinct_field),
             synonyms: Some(Some(synonyms)),
+            hierarchical_facets: Some(Some(hierarchical_facets)),
+            date_attributes: Some(Some(date_attributes)),
+            search_defaults: Some(Some(search_defaults)),
+            pagination: Some(Some(pagination)),
+            document_schema: Some(document_schema),
             _kind: PhantomData,
         })
     }
 
-    pub fn retrieve_documents<S: AsRef<str>>(
-        &self,
-        offset: usize,
-        limit: usize,
-        attributes_to_retrieve: Option<Vec<S>>,
-    ) -> Result<Vec<Map<String, Value>>> {
-        let txn = self.read_txn()?;
-
-        let fields_ids_map = self.fields_ids_map(&txn)?;
-        let fields_to_display =
-            self.fields_to_display(&txn, &attributes_to_retrieve, &fields_ids_map)?;
-
-        let iter = self.documents.range(&txn, &(..))?.skip(offset).take(limit);
-
-        let mut documents = Vec::new();
-
-        for entry in iter {
-            let (_id, obkv) = entry?;
-            let object = obkv_to_json(&fields_to_display, &fields_ids_map, obkv)?;
-            documents.push(object);
-        }
-
-        Ok(documents)
-    }
-
     pub fn retrieve_document<S: AsRef<str>>(
         &self,
         doc_id: String,
@@ -160,30 +191,51 @@ impl Index {
             .map(|(_, d)| d)
             .ok_or(IndexError::DocumentNotFound(doc_id))?;
 
-        let document = obkv_to_json(&fields_to_display, &fields_ids_map, document)?;
+        let original_dates = original_dates(&fields_ids_map, document)?;
+        let mut document = obkv_to_json(&fields_to_display, &fields_ids_map, document)?;
+        restore_dates(
+            document.iter_mut(),
+            &self.date_attributes(&txn)?,
+            &original_dates,
+        );
 
-        Ok(document)
+        Ok(unflatten(document, &self.nested_fields(&txn)?))
     }
 
     pub fn size(&self) -> u64 {
         self.env.size()
     }
 
+    /// Returns the last time the index was updated, either by milli or by a change to the data we
+    /// store next to milli's.
+    pub fn updated_at(&self, txn: &RoTxn) -> Result<DateTime<Utc>> {
+        let updated_at = self.0.updated_at(txn)?;
+        let touched_at = self
+            .main
+            .get::<_, Str, SerdeJson<DateTime<Utc>>>(txn, UPDATED_AT_KEY)?;
+        Ok(touched_at.map_or(updated_at, |touched_at| touched_at.max(updated_at)))
+    }
+
+    /// Sets the update time of the index to now. The milli updates already do it, this is for
+    /// the data we store next to them, that changes the results of the searches.
+    pub fn touch(&self, txn: &mut RwTxn) -> Result<()> {
+        self.main
+            .put::<_, Str, SerdeJson<DateTime<Utc>>>(txn, UPDATED_AT_KEY, &Utc::now())?;
+        Ok(())
+    }
+
     fn fields_to_display<S: AsRef<str>>(
         &self,
         txn: &heed::RoTxn,
         attributes_to_retrieve: &Option<Vec<S>>,
         fields_ids_map: &milli::FieldsIdsMap,
     ) -> Result<Vec<u8>> {
-        let mut displayed_fields_ids = match self.displayed_fields_ids(&txn)? {
-            Some(ids) => ids.into_iter().collect::<Vec<_>>(),
-            None => fields_ids_map.iter().map(|(id, _)| id).collect(),
-        };
+        let mut displayed_fields_ids = self.displayed_ids(txn, fields_ids_map)?;
 
         let attributes_to_retrieve_ids = match attributes_to_retrieve {
             Some(attrs) => attrs
                 .iter()
-                .filter_map(|f| fields_ids_map.id(f.as_ref()))
+                .flat_map(|f| field_ids(fields_ids_map, f.as_ref()))
                 .collect::<HashSet<_>>(),
             None => fields_ids_map.iter().map(|(id, _)| id).collect(),
         };
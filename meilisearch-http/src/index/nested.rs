@@ -0,0 +1,270 @@
+use std::collections::{BTreeSet, HashSet};
+
+use heed::types::{SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use milli::{FieldId, FieldsIdsMap};
+use serde_json::map::Entry;
+use serde_json::{Map, Value};
+
+use super::dates::ORIGINAL_DATES_FIELD;
+use super::error::{DocumentFormatError, Result};
+use super::{Document, Index};
+
+const NESTED_FIELDS_KEY: &str = "nested-fields";
+
+/// The names of the fields of an index that come from nested objects, like `author.name`.
+pub type NestedFields = BTreeSet<String>;
+
+impl Index {
+    pub fn nested_fields(&self, txn: &RoTxn) -> Result<NestedFields> {
+        let fields = self
+            .main
+            .get::<_, Str, SerdeJson<NestedFields>>(txn, NESTED_FIELDS_KEY)?
+            .unwrap_or_default();
+        Ok(fields)
+    }
+
+    pub fn put_nested_fields(&self, txn: &mut RwTxn, fields: &NestedFields) -> Result<()> {
+        self.main
+            .put::<_, Str, SerdeJson<NestedFields>>(txn, NESTED_FIELDS_KEY, fields)?;
+        Ok(())
+    }
+
+    /// Returns the ids of the displayed fields, in the order of the `displayedAttributes`. A
+    /// displayed object displays all the fields nested in it. The original dates are never
+    /// displayed, they replace the timestamps of the date attributes.
+    pub(super) fn displayed_ids(
+        &self,
+        txn: &RoTxn,
+        fields_ids_map: &FieldsIdsMap,
+    ) -> Result<Vec<FieldId>> {
+        let original_dates_id = fields_ids_map.id(ORIGINAL_DATES_FIELD);
+        let mut ids: Vec<_> = match self.displayed_fields(txn)? {
+            Some(names) => {
+                let mut seen = HashSet::new();
+                names
+                    .into_iter()
+                    .flat_map(|name| field_ids(fields_ids_map, name))
+                    .filter(|id| seen.insert(*id))
+                    .collect()
+            }
+            None => fields_ids_map.iter().map(|(id, _)| id).collect(),
+        };
+        ids.retain(|id| Some(*id) != original_dates_id);
+        Ok(ids)
+    }
+}
+
+/// Returns the ids of the fields designated by `attribute`: the field of this name, and the
+/// fields nested in it when it is an object.
+pub fn field_ids<'a>(
+    fields_ids_map: &'a FieldsIdsMap,
+    attribute: &'a str,
+) -> impl Iterator<Item = FieldId> + 'a {
+    fields_ids_map
+        .iter()
+        .filter(move |(_, name)| match name.strip_prefix(attribute) {
+            Some(rest) => rest.is_empty() || rest.starts_with('.'),
+            None => false,
+        })
+        .map(|(id, _)| id)
+}
+
+/// Moves the fields of the objects of `document` to the top level of the document, under their
+/// dot path: `{"author": {"name": "Jane"}}` becomes `{"author.name": "Jane"}`, so that they can
+/// be searched, filtered and displayed like any other field. The arrays and the empty objects
+/// are kept as they are. The names of the moved fields are added to `nested`.
+pub fn flatten(document: &mut Document, nested: &mut NestedFields) -> Result<()> {
+    let has_objects = document
+        .values()
+        .any(|value| matches!(value, Value::Object(object) if !object.is_empty()));
+    if !has_objects {
+        return Ok(());
+    }
+
+    for (key, value) in std::mem::take(document) {
+        insert_flattened(document, key, value, false, nested)?;
+    }
+
+    Ok(())
+}
+
+fn insert_flattened(
+    document: &mut Document,
+    path: String,
+    value: Value,
+    is_nested: bool,
+    nested: &mut NestedFields,
+) -> Result<()> {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                let path = format!("{}.{}", path, key);
+                insert_flattened(document, path, value, true, nested)?;
+            }
+        }
+        value => {
+            if is_nested && !nested.contains(&path) {
+                nested.insert(path.clone());
+            }
+            match document.entry(path) {
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+                Entry::Occupied(entry) => {
+                    return Err(DocumentFormatError::ConflictingField(entry.key().clone()).into())
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Puts the `fields` of a document that come from nested objects back in their objects, which
+/// take the place of their first field. This restores the documents as they were sent.
+pub fn unflatten<K: Into<String>>(
+    fields: impl IntoIterator<Item = (K, Value)>,
+    nested: &NestedFields,
+) -> Document {
+    let mut document = Document::new();
+
+    for (key, value) in fields {
+        let key = key.into();
+        if nested.contains(&key) && can_nest(&document, &key) {
+            let mut segments: Vec<_> = key.split('.').collect();
+            let last = segments.pop().expect("split returns at least one segment");
+            let mut object = &mut document;
+            for segment in segments {
+                let parent = object
+                    .entry(segment)
+                    .or_insert_with(|| Value::Object(Map::new()));
+                object = match parent {
+                    Value::Object(parent) => parent,
+                    _ => unreachable!("the path was checked by `can_nest`"),
+                };
+            }
+            object.insert(last.to_string(), value);
+        } else {
+            document.insert(key, value);
+        }
+    }
+
+    document
+}
+
+/// Whether the objects on the path of `key` are missing from `document`, or are objects. A field
+/// can't be nested in a value of another type, it is then kept at the top level.
+fn can_nest(document: &Document, key: &str) -> bool {
+    let mut segments: Vec<_> = key.split('.').collect();
+    segments.pop();
+
+    let mut object = document;
+    for segment in segments {
+        match object.get(segment) {
+            None => return true,
+            Some(Value::Object(parent)) => object = parent,
+            Some(_) => return false,
+        }
+    }
+
+    !object.contains_key(key.rsplit('.').next().unwrap_or(key))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn document(value: Value) -> Document {
+        match value {
+            Value::Object(object) => object,
+            _ => panic!("not an object"),
+        }
+    }
+
+    #[test]
+    fn flatten_objects() {
+        let mut nested = NestedFields::new();
+        let mut doc = document(json!({
+            "id": 1,
+            "author": { "name": "Jane", "address": { "city": "Paris" } },
+            "tags": [{ "name": "a" }],
+            "meta": {},
+        }));
+        flatten(&mut doc, &mut nested).unwrap();
+
+        assert_eq!(
+            Value::Object(doc),
+            json!({
+                "id": 1,
+                "author.name": "Jane",
+                "author.address.city": "Paris",
+                "tags": [{ "name": "a" }],
+                "meta": {},
+            })
+        );
+        assert_eq!(
+            nested.into_iter().collect::<Vec<_>>(),
+            vec!["author.address.city", "author.name"]
+        );
+    }
+
+    #[test]
+    fn flatten_conflicting_fields() {
+        let mut nested = NestedFields::new();
+        let mut doc = document(json!({ "author.name": "Jane", "author": { "name": "John" } }));
+        assert!(flatten(&mut doc, &mut nested).is_err());
+    }
+
+    #[test]
+    fn unflatten_restores_documents() {
+        let original = json!({
+            "id": 1,
+            "author": { "name": "Jane", "address": { "city": "Paris" } },
+            "title": "Hello",
+            "specs": { "weight": 3 },
+        });
+        let mut nested = NestedFields::new();
+        let mut doc = document(original.clone());
+        flatten(&mut doc, &mut nested).unwrap();
+
+        let restored = unflatten(doc, &nested);
+        assert_eq!(Value::Object(restored), original);
+    }
+
+    #[test]
+    fn unflatten_keeps_dotted_keys() {
+        let nested: NestedFields = vec!["author.name".to_string()].into_iter().collect();
+
+        let doc = document(json!({ "author": "Jane", "author.name": "John" }));
+        let restored = unflatten(doc, &nested);
+        assert_eq!(
+            Value::Object(restored),
+            json!({ "author": "Jane", "author.name": "John" })
+        );
+
+        let doc = document(json!({ "web.site": "example.com" }));
+        let restored = unflatten(doc, &nested);
+        assert_eq!(
+            Value::Object(restored),
+            json!({ "web.site": "example.com" })
+        );
+    }
+
+    #[test]
+    fn select_nested_fields() {
+        let mut fields_ids_map = FieldsIdsMap::new();
+        let author = fields_ids_map.insert("author").unwrap();
+        let name = fields_ids_map.insert("author.name").unwrap();
+        let city = fields_ids_map.insert("author.address.city").unwrap();
+        fields_ids_map.insert("authors").unwrap();
+
+        let ids: Vec<_> = field_ids(&fields_ids_map, "author").collect();
+        assert_eq!(ids, vec![author, name, city]);
+        let ids: Vec<_> = field_ids(&fields_ids_map, "author.name").collect();
+        assert_eq!(ids, vec![name]);
+        assert_eq!(field_ids(&fields_ids_map, "auth").count(), 0);
+    }
+}
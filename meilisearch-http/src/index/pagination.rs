@@ -0,0 +1,93 @@
+use heed::types::{SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+
+use super::error::Result;
+use super::Index;
+
+const PAGINATION_KEY: &str = "pagination";
+
+pub const DEFAULT_MAX_TOTAL_HITS: usize = 1000;
+
+/// How deep the results of a search can be browsed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Pagination {
+    /// The number of ranked documents a search can return, and count exactly.
+    #[serde(default = "default_max_total_hits")]
+    pub max_total_hits: usize,
+}
+
+const fn default_max_total_hits() -> usize {
+    DEFAULT_MAX_TOTAL_HITS
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            max_total_hits: DEFAULT_MAX_TOTAL_HITS,
+        }
+    }
+}
+
+impl Pagination {
+    /// Bounds the page starting at `offset` so that it doesn't go past the `maxTotalHits` first
+    /// documents. Returns the bounded offset and limit.
+    pub fn bound(&self, offset: usize, limit: usize) -> (usize, usize) {
+        let offset = offset.min(self.max_total_hits);
+        let limit = limit.min(self.max_total_hits - offset);
+        (offset, limit)
+    }
+
+    pub fn total_hits(&self, nb_hits: u64) -> u64 {
+        nb_hits.min(self.max_total_hits as u64)
+    }
+}
+
+/// The number of pages of `hits_per_page` documents needed to show `total_hits` documents.
+pub fn total_pages(total_hits: u64, hits_per_page: usize) -> u64 {
+    match hits_per_page as u64 {
+        0 => 0,
+        hits_per_page => (total_hits + hits_per_page - 1) / hits_per_page,
+    }
+}
+
+impl Index {
+    pub fn pagination(&self, txn: &RoTxn) -> Result<Pagination> {
+        let pagination = self
+            .main
+            .get::<_, Str, SerdeJson<Pagination>>(txn, PAGINATION_KEY)?
+            .unwrap_or_default();
+        Ok(pagination)
+    }
+
+    pub fn put_pagination(&self, txn: &mut RwTxn, pagination: &Pagination) -> Result<()> {
+        self.main
+            .put::<_, Str, SerdeJson<Pagination>>(txn, PAGINATION_KEY, pagination)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bound_pages() {
+        let pagination = Pagination { max_total_hits: 25 };
+
+        assert_eq!(pagination.bound(0, 20), (0, 20));
+        assert_eq!(pagination.bound(20, 20), (20, 5));
+        assert_eq!(pagination.bound(40, 20), (25, 0));
+        assert_eq!(pagination.total_hits(12), 12);
+        assert_eq!(pagination.total_hits(120), 25);
+    }
+
+    #[test]
+    fn count_pages() {
+        assert_eq!(total_pages(0, 10), 0);
+        assert_eq!(total_pages(10, 10), 1);
+        assert_eq!(total_pages(11, 10), 2);
+        assert_eq!(total_pages(11, 0), 0);
+    }
+}
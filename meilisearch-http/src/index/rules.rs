@@ -0,0 +1,578 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use heed::types::{SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use milli::DocumentId;
+use parking_lot::RwLock;
+use regex::{Regex, RegexBuilder};
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::index_controller::UpdateResult;
+
+use super::error::{Result, RuleError};
+use super::search::parse_filter;
+use super::{Index, SearchQuery};
+
+/// The merchandising rules of an index, identified by their id.
+pub type Rules = BTreeMap<String, Rule>;
+
+const RULES_KEY: &str = "rules";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Rule {
+    #[serde(default)]
+    pub conditions: RuleConditions,
+    #[serde(default)]
+    pub consequences: RuleConsequences,
+}
+
+/// A rule fires when all its conditions are met. A rule without conditions always fires.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RuleConditions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<QueryPattern>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+/// Matches the query of a search, after it has been trimmed and lowercased. The regexes are
+/// matched case-insensitively.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum QueryPattern {
+    Exact(String),
+    Contains(String),
+    Regex(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RuleConsequences {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pin: Vec<Pin>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hide: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove_words: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub facets_distribution: Vec<String>,
+}
+
+/// Pins the document with the external id `id` at the zero-based `position` of the results.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Pin {
+    pub id: String,
+    pub position: usize,
+}
+
+impl QueryPattern {
+    fn matches(&self, query: &str, patterns: &CompiledPatterns) -> bool {
+        match self {
+            QueryPattern::Exact(pattern) => query == pattern.trim().to_lowercase(),
+            QueryPattern::Contains(pattern) => query.contains(&pattern.trim().to_lowercase()),
+            QueryPattern::Regex(pattern) => patterns
+                .get(pattern)
+                .map_or(false, |regex| regex.is_match(query)),
+        }
+    }
+}
+
+/// The compiled regexes of the query patterns of the rules, by pattern, so that each of them is
+/// compiled once rather than on every search.
+#[derive(Clone, Default)]
+pub struct CompiledPatterns(Arc<RwLock<HashMap<String, Regex>>>);
+
+impl CompiledPatterns {
+    /// Returns the compiled `pattern`, compiling it on its first use. The patterns have been
+    /// validated when their rule was stored.
+    fn get(&self, pattern: &str) -> Option<Regex> {
+        if let Some(regex) = self.0.read().get(pattern) {
+            return Some(regex.clone());
+        }
+        let regex = compile_pattern(pattern).ok()?;
+        self.0.write().insert(pattern.to_string(), regex.clone());
+        Some(regex)
+    }
+
+    /// Forgets the patterns that aren't used by any of the `rules` anymore.
+    fn retain(&self, rules: &Rules) {
+        let used: HashSet<_> = rules
+            .values()
+            .filter_map(|rule| match rule.conditions.query {
+                Some(QueryPattern::Regex(ref pattern)) => Some(pattern.as_str()),
+                _ => None,
+            })
+            .collect();
+        self.0
+            .write()
+            .retain(|pattern, _| used.contains(pattern.as_str()));
+    }
+}
+
+fn compile_pattern(pattern: &str) -> std::result::Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+impl Rule {
+    fn validate(&self, index: &Index, txn: &RoTxn) -> Result<()> {
+        let conditions = &self.conditions;
+
+        if let Some(QueryPattern::Regex(ref pattern)) = conditions.query {
+            if let Err(e) = compile_pattern(pattern) {
+                return Err(RuleError::InvalidRegex(e.to_string()).into());
+            }
+        }
+
+        if let (Some(from), Some(until)) = (conditions.valid_from, conditions.valid_until) {
+            if from > until {
+                return Err(RuleError::InvalidDateWindow(from, until).into());
+            }
+        }
+
+        if let Some(ref filter) = self.consequences.filter {
+            parse_filter(filter, index, txn)?;
+        }
+
+        Ok(())
+    }
+
+    fn is_fired(&self, query: &str, now: DateTime<Utc>, patterns: &CompiledPatterns) -> bool {
+        let conditions = &self.conditions;
+        conditions
+            .query
+            .as_ref()
+            .map_or(true, |pattern| pattern.matches(query, patterns))
+            && conditions.valid_from.map_or(true, |from| now >= from)
+            && conditions.valid_until.map_or(true, |until| now <= until)
+    }
+}
+
+/// A rewrite of the search query applied by a rule, echoed back in the search result.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRewrite {
+    pub rule: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_words: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub added_filter: Option<Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added_facets: Vec<String>,
+}
+
+impl QueryRewrite {
+    fn is_empty(&self) -> bool {
+        self.removed_words.is_empty() && self.added_filter.is_none() && self.added_facets.is_empty()
+    }
+}
+
+/// The merged consequences of all the rules fired by a search.
+#[derive(Debug, Default)]
+pub struct FiredRules {
+    /// The ids of the fired rules, in the order they have been applied.
+    pub ids: Vec<String>,
+    /// The consequences of the fired rules on the search query, by rule id.
+    pub rewrites: Vec<(String, RuleConsequences)>,
+    /// The pinned documents, sorted by position.
+    pub pinned: Vec<(usize, DocumentId)>,
+    pub hidden: RoaringBitmap,
+}
+
+impl FiredRules {
+    /// Rewrites the `q`, `filter` and `facetsDistribution` of the query with the consequences
+    /// of the fired rules, and returns the rewrites that changed the query.
+    pub fn rewrite_query(&self, query: &mut SearchQuery) -> Vec<QueryRewrite> {
+        let mut words: Vec<_> = query
+            .q
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        let mut rewrites = Vec::new();
+
+        for (rule, consequences) in &self.rewrites {
+            let removed_words = consequences
+                .remove_words
+                .iter()
+                .filter(|phrase| remove_phrase(&mut words, phrase))
+                .cloned()
+                .collect();
+
+            if let Some(ref filter) = consequences.filter {
+                query.filter = Some(merge_filters(query.filter.take(), filter.clone()));
+            }
+
+            let mut added_facets = Vec::new();
+            for facet in &consequences.facets_distribution {
+                let facets = query.facets_distribution.get_or_insert_with(Vec::new);
+                if !facets.contains(facet) {
+                    facets.push(facet.clone());
+                    added_facets.push(facet.clone());
+                }
+            }
+
+            let rewrite = QueryRewrite {
+                rule: rule.clone(),
+                removed_words,
+                added_filter: consequences.filter.clone(),
+                added_facets,
+            };
+            if !rewrite.is_empty() {
+                rewrites.push(rewrite);
+            }
+        }
+
+        if rewrites.iter().any(|r| !r.removed_words.is_empty()) {
+            query.q = Some(words.join(" "));
+        }
+
+        rewrites
+    }
+
+    /// Whether the ranked documents must be reordered by pinning or hiding documents.
+    pub fn reorders(&self) -> bool {
+        !self.pinned.is_empty() || !self.hidden.is_empty()
+    }
+
+    /// Returns how many more ranked documents than requested must be fetched to fill a page once
+    /// the documents are pinned and hidden.
+    pub fn extra_documents(&self) -> usize {
+        self.pinned.len() + self.hidden.len() as usize
+    }
+
+    /// Inserts the pinned documents at their positions in `ranked`, removes the hidden ones and
+    /// returns the `offset..offset + limit` window of the result. `ranked` must start at the first
+    /// ranked document.
+    pub fn apply(&self, ranked: Vec<DocumentId>, offset: usize, limit: usize) -> Vec<DocumentId> {
+        let pinned_ids: HashSet<_> = self.pinned.iter().map(|(_, id)| *id).collect();
+        let mut organic = ranked
+            .into_iter()
+            .filter(|id| !self.hidden.contains(*id) && !pinned_ids.contains(id));
+        let mut pinned = self.pinned.iter().peekable();
+
+        let mut documents = Vec::new();
+        while documents.len() < offset + limit {
+            let next = match pinned.peek() {
+                Some((position, _)) if *position <= documents.len() => {
+                    pinned.next().map(|(_, id)| *id)
+                }
+                _ => organic.next().or_else(|| pinned.next().map(|(_, id)| *id)),
+            };
+
+            match next {
+                Some(id) => documents.push(id),
+                None => break,
+            }
+        }
+
+        documents.into_iter().skip(offset).collect()
+    }
+}
+
+impl Index {
+    pub fn rules(&self, txn: &RoTxn) -> Result<Rules> {
+        let rules = self
+            .main
+            .get::<_, Str, SerdeJson<Rules>>(txn, RULES_KEY)?
+            .unwrap_or_default();
+        Ok(rules)
+    }
+
+    /// Returns the last time, up to `now`, a rule started or stopped being applied. The results
+    /// of the searches may have changed since then, even though the index wasn't updated.
+    pub fn rules_changed_at(
+        &self,
+        txn: &RoTxn,
+        now: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let changed_at = self
+            .rules(txn)?
+            .values()
+            .flat_map(|rule| {
+                let conditions = &rule.conditions;
+                let started = conditions.valid_from.filter(|from| *from <= now);
+                // A rule is still applied at the end of its window.
+                let stopped = conditions.valid_until.filter(|until| *until < now);
+                started.into_iter().chain(stopped)
+            })
+            .max();
+        Ok(changed_at)
+    }
+
+    pub fn put_rules(&self, txn: &mut RwTxn, rules: &Rules) -> Result<()> {
+        self.main
+            .put::<_, Str, SerdeJson<Rules>>(txn, RULES_KEY, rules)?;
+        self.1.retain(rules);
+        // The rules change the search results, the cached ones must be invalidated.
+        self.touch(txn)
+    }
+
+    /// Validates and stores a rule, replacing any rule with the same id.
+    pub fn put_rule(&self, id: String, rule: Rule) -> Result<UpdateResult> {
+        let mut txn = self.write_txn()?;
+        rule.validate(self, &txn)?;
+        let mut rules = self.rules(&txn)?;
+        rules.insert(id, rule);
+        self.put_rules(&mut txn, &rules)?;
+        txn.commit()?;
+        Ok(UpdateResult::Other)
+    }
+
+    pub fn delete_rule(&self, id: &str) -> Result<UpdateResult> {
+        let mut txn = self.write_txn()?;
+        let mut rules = self.rules(&txn)?;
+        if rules.remove(id).is_none() {
+            return Err(RuleError::NotFound(id.to_string()).into());
+        }
+        self.put_rules(&mut txn, &rules)?;
+        txn.commit()?;
+        Ok(UpdateResult::Other)
+    }
+
+    /// Returns the consequences of the rules fired by `query` at the time `now`.
+    ///
+    /// The rules are applied in the order of their ids. Pins and hides referencing unknown
+    /// documents are ignored, and a document that is both pinned and hidden is hidden.
+    pub fn fire_rules(&self, txn: &RoTxn, query: &str, now: DateTime<Utc>) -> Result<FiredRules> {
+        let query = query.trim().to_lowercase();
+        let rules = self.rules(txn)?;
+
+        let mut fired = FiredRules::default();
+        if rules.is_empty() {
+            return Ok(fired);
+        }
+
+        let external_ids = self.external_documents_ids(txn)?;
+        for (id, rule) in rules {
+            if !rule.is_fired(&query, now, &self.1) {
+                continue;
+            }
+
+            let mut consequences = rule.consequences;
+            for Pin { id, position } in consequences.pin.drain(..) {
+                if let Some(docid) = external_ids.get(id.as_bytes()) {
+                    fired.pinned.push((position, docid));
+                }
+            }
+            for id in consequences.hide.drain(..) {
+                if let Some(docid) = external_ids.get(id.as_bytes()) {
+                    fired.hidden.insert(docid);
+                }
+            }
+            fired.ids.push(id.clone());
+            fired.rewrites.push((id, consequences));
+        }
+
+        let hidden = &fired.hidden;
+        let mut seen = HashSet::new();
+        fired
+            .pinned
+            .retain(|(_, docid)| !hidden.contains(*docid) && seen.insert(*docid));
+        // The sort is stable, so the first rule wins when two documents are pinned at the same
+        // position.
+        fired.pinned.sort_by_key(|(position, _)| *position);
+
+        Ok(fired)
+    }
+}
+
+/// Removes all the occurrences of `phrase` from `words`, ignoring the case. Returns whether the
+/// phrase was found.
+fn remove_phrase(words: &mut Vec<String>, phrase: &str) -> bool {
+    let phrase: Vec<_> = phrase.split_whitespace().map(str::to_lowercase).collect();
+    if phrase.is_empty() {
+        return false;
+    }
+
+    let mut found = false;
+    let mut i = 0;
+    while i + phrase.len() <= words.len() {
+        let matches = words[i..i + phrase.len()]
+            .iter()
+            .zip(&phrase)
+            .all(|(word, expected)| word.to_lowercase() == *expected);
+
+        if matches {
+            words.drain(i..i + phrase.len());
+            found = true;
+        } else {
+            i += 1;
+        }
+    }
+
+    found
+}
+
+/// Adds the `added` filter to the `existing` one, using the array form of filters. The
+/// structured filters become elements of the array.
+fn merge_filters(existing: Option<Value>, added: Value) -> Value {
+    fn into_ands(filter: Value) -> Vec<Value> {
+        match filter {
+            Value::Array(ands) => ands,
+            other => vec![other],
+        }
+    }
+
+    match existing {
+        Some(existing) => {
+            let mut ands = into_ands(existing);
+            ands.extend(into_ands(added));
+            Value::Array(ands)
+        }
+        None => added,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fired(pinned: Vec<(usize, DocumentId)>, hidden: &[DocumentId]) -> FiredRules {
+        FiredRules {
+            ids: Vec::new(),
+            rewrites: Vec::new(),
+            pinned,
+            hidden: hidden.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn pin_and_hide_documents() {
+        let rules = fired(vec![(0, 7), (2, 8)], &[2]);
+        let ranked = vec![1, 2, 3, 7, 4, 5];
+
+        assert_eq!(rules.apply(ranked.clone(), 0, 10), vec![7, 1, 8, 3, 4, 5]);
+        assert_eq!(rules.apply(ranked.clone(), 1, 2), vec![1, 8]);
+        assert_eq!(rules.apply(ranked, 4, 10), vec![4, 5]);
+    }
+
+    #[test]
+    fn pin_beyond_ranked_documents() {
+        let rules = fired(vec![(10, 9)], &[]);
+        assert_eq!(rules.apply(vec![1, 2], 0, 20), vec![1, 2, 9]);
+    }
+
+    #[test]
+    fn match_query_patterns() {
+        let patterns = CompiledPatterns::default();
+
+        let exact = QueryPattern::Exact(String::from("iPhone "));
+        assert!(exact.matches("iphone", &patterns));
+        assert!(!exact.matches("iphone case", &patterns));
+
+        let contains = QueryPattern::Contains(String::from("iphone"));
+        assert!(contains.matches("red iphone case", &patterns));
+        assert!(!contains.matches("android", &patterns));
+
+        let regex = QueryPattern::Regex(String::from(r"^iphone \d+$"));
+        assert!(regex.matches("iphone 12", &patterns));
+        assert!(!regex.matches("iphone pro", &patterns));
+
+        let regex = QueryPattern::Regex(String::from(r"^iPhone \d+$"));
+        assert!(regex.matches("iphone 12", &patterns));
+    }
+
+    #[test]
+    fn compile_patterns_once() {
+        let patterns = CompiledPatterns::default();
+        let regex = QueryPattern::Regex(String::from(r"^iphone \d+$"));
+        assert!(regex.matches("iphone 12", &patterns));
+        assert_eq!(patterns.0.read().len(), 1);
+
+        let mut rules = Rules::new();
+        patterns.retain(&rules);
+        assert!(patterns.0.read().is_empty());
+
+        let rule = Rule {
+            conditions: RuleConditions {
+                query: Some(regex.clone()),
+                ..RuleConditions::default()
+            },
+            ..Rule::default()
+        };
+        rules.insert(String::from("iphone"), rule);
+        assert!(regex.matches("iphone 12", &patterns));
+        patterns.retain(&rules);
+        assert_eq!(patterns.0.read().len(), 1);
+    }
+
+    #[test]
+    fn fire_within_date_window() {
+        let rule: Rule = serde_json::from_value(serde_json::json!({
+            "conditions": {
+                "validFrom": "2021-06-01T00:00:00Z",
+                "validUntil": "2021-06-30T00:00:00Z"
+            }
+        }))
+        .unwrap();
+
+        let patterns = CompiledPatterns::default();
+        assert!(rule.is_fired("", "2021-06-15T00:00:00Z".parse().unwrap(), &patterns));
+        assert!(!rule.is_fired("", "2021-07-01T00:00:00Z".parse().unwrap(), &patterns));
+    }
+
+    #[test]
+    fn rewrite_query() {
+        let cheap: RuleConsequences = serde_json::from_value(serde_json::json!({
+            "removeWords": ["cheap"],
+            "filter": "price < 20",
+            "facetsDistribution": ["brand"]
+        }))
+        .unwrap();
+        let shop: RuleConsequences = serde_json::from_value(serde_json::json!({
+            "removeWords": ["buy", "shop", "online store"]
+        }))
+        .unwrap();
+        let rules = FiredRules {
+            rewrites: vec![(String::from("cheap"), cheap), (String::from("shop"), shop)],
+            ..FiredRules::default()
+        };
+
+        let mut query: SearchQuery = serde_json::from_value(serde_json::json!({
+            "q": "Buy cheap shoes Online  Store",
+            "filter": ["size = 42"]
+        }))
+        .unwrap();
+
+        let rewrites = rules.rewrite_query(&mut query);
+
+        assert_eq!(query.q.as_deref(), Some("shoes"));
+        assert_eq!(
+            query.filter,
+            Some(serde_json::json!(["size = 42", "price < 20"]))
+        );
+        assert_eq!(query.facets_distribution, Some(vec![String::from("brand")]));
+        assert_eq!(rewrites.len(), 2);
+        assert_eq!(rewrites[0].removed_words, vec![String::from("cheap")]);
+        assert_eq!(
+            rewrites[1].removed_words,
+            vec![String::from("buy"), String::from("online store")]
+        );
+    }
+
+    #[test]
+    fn rules_without_rewrites_keep_the_query() {
+        let rules = FiredRules {
+            rewrites: vec![(String::from("pin"), RuleConsequences::default())],
+            ..FiredRules::default()
+        };
+        let mut query: SearchQuery =
+            serde_json::from_value(serde_json::json!({ "q": "shoes" })).unwrap();
+
+        assert!(rules.rewrite_query(&mut query).is_empty());
+        assert_eq!(query.q.as_deref(), Some("shoes"));
+        assert!(query.filter.is_none());
+    }
+}
@@ -0,0 +1,624 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use heed::types::{SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+
+use super::error::{Result, SchemaError};
+use super::{Document, Index};
+
+const DOCUMENT_SCHEMA_KEY: &str = "document-schema";
+
+/// The number of invalid documents described in an update status or error, the others are only
+/// counted.
+pub const MAX_REPORTED_INVALID_DOCUMENTS: usize = 20;
+
+/// The schema the documents added to an index are validated against, as they were sent. The
+/// documents of a partial update are validated once merged with the stored ones, so a field
+/// `required` by the schema can be left out of the update of an existing document.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DocumentSchema {
+    pub schema: Schema,
+    #[serde(default)]
+    pub on_invalid: OnInvalidDocument,
+}
+
+/// What becomes of an update containing documents that don't match the schema.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum OnInvalidDocument {
+    /// The update fails, and no document is added.
+    Fail,
+    /// The invalid documents are left out of the update, the others are added.
+    Skip,
+}
+
+impl Default for OnInvalidDocument {
+    fn default() -> Self {
+        OnInvalidDocument::Fail
+    }
+}
+
+/// The subset of JSON Schema supported to describe the documents. The keywords out of this subset
+/// are rejected rather than ignored, so that a schema never validates less than it says.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Schema {
+    #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
+    pub meta_schema: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub types: Option<JsonTypes>,
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<Value>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<Number>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<IndexMap<String, Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_properties: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum JsonTypes {
+    One(JsonType),
+    Many(Vec<JsonType>),
+}
+
+impl JsonTypes {
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            JsonTypes::One(t) => t.matches(value),
+            JsonTypes::Many(types) => types.iter().any(|t| t.matches(value)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonType {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl JsonType {
+    fn matches(self, value: &Value) -> bool {
+        match (self, value) {
+            (JsonType::Null, Value::Null) => true,
+            (JsonType::Boolean, Value::Bool(_)) => true,
+            (JsonType::Integer, Value::Number(n)) => {
+                n.is_i64() || n.is_u64() || n.as_f64().map_or(false, |n| n.fract() == 0.0)
+            }
+            (JsonType::Number, Value::Number(_)) => true,
+            (JsonType::String, Value::String(_)) => true,
+            (JsonType::Array, Value::Array(_)) => true,
+            (JsonType::Object, Value::Object(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A place where a document doesn't match the schema: the JSON pointer of the value in the
+/// document, and the JSON pointer of the keyword it breaks in the schema.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Violation {
+    pub instance_path: String,
+    pub schema_path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidDocument {
+    /// The id of the document, missing when the document has no valid id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_id: Option<String>,
+    pub errors: Vec<Violation>,
+}
+
+/// The documents of an update that don't match the schema.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidDocuments {
+    pub count: usize,
+    /// The first invalid documents, in the order of the update.
+    pub documents: Vec<InvalidDocument>,
+}
+
+impl InvalidDocuments {
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    fn push(&mut self, document_id: Option<&str>, errors: Vec<Violation>) {
+        self.count += 1;
+        if self.documents.len() < MAX_REPORTED_INVALID_DOCUMENTS {
+            self.documents.push(InvalidDocument {
+                document_id: document_id.map(String::from),
+                errors,
+            });
+        }
+    }
+}
+
+impl fmt::Display for InvalidDocuments {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} document(s) don't match the document schema:",
+            self.count
+        )?;
+        for (i, document) in self.documents.iter().enumerate() {
+            let separator = if i == 0 { " " } else { "; " };
+            match document.document_id {
+                Some(ref id) => write!(f, "{}document `{}`", separator, id)?,
+                None => write!(f, "{}a document without id", separator)?,
+            }
+            for error in &document.errors {
+                write!(
+                    f,
+                    ", `{}` breaks `{}`",
+                    display_pointer(&error.instance_path),
+                    error.schema_path
+                )?;
+            }
+        }
+        if self.count > self.documents.len() {
+            write!(f, "; and {} more", self.count - self.documents.len())?;
+        }
+        write!(f, ".")
+    }
+}
+
+fn display_pointer(pointer: &str) -> &str {
+    if pointer.is_empty() {
+        "/"
+    } else {
+        pointer
+    }
+}
+
+impl Index {
+    pub fn document_schema(&self, txn: &RoTxn) -> Result<Option<DocumentSchema>> {
+        let schema = self
+            .main
+            .get::<_, Str, SerdeJson<DocumentSchema>>(txn, DOCUMENT_SCHEMA_KEY)?;
+        Ok(schema)
+    }
+
+    pub fn put_document_schema(
+        &self,
+        txn: &mut RwTxn,
+        schema: Option<&DocumentSchema>,
+    ) -> Result<()> {
+        match schema {
+            Some(schema) => {
+                self.main.put::<_, Str, SerdeJson<DocumentSchema>>(
+                    txn,
+                    DOCUMENT_SCHEMA_KEY,
+                    schema,
+                )?;
+            }
+            None => {
+                self.main.delete::<_, Str>(txn, DOCUMENT_SCHEMA_KEY)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DocumentSchema {
+    /// Compiles the schema, failing when it can't be used to validate documents.
+    pub fn validator(&self) -> Result<Validator> {
+        let mut patterns = HashMap::new();
+        compile(&self.schema, &mut String::new(), &mut patterns)?;
+        Ok(Validator {
+            document_schema: self,
+            patterns,
+            invalid: InvalidDocuments::default(),
+        })
+    }
+}
+
+fn compile(
+    schema: &Schema,
+    schema_path: &mut String,
+    patterns: &mut HashMap<String, Regex>,
+) -> Result<()> {
+    let invalid = |path: &str, message: String| SchemaError::InvalidSchema {
+        path: display_pointer(path).to_string(),
+        message,
+    };
+
+    if let Some(ref pattern) = schema.pattern {
+        let path = format!("{}/pattern", schema_path);
+        let regex = Regex::new(pattern).map_err(|e| invalid(&path, e.to_string()))?;
+        patterns.insert(path, regex);
+    }
+
+    for (min, max, name) in &[
+        (schema.min_length, schema.max_length, "minLength"),
+        (schema.min_items, schema.max_items, "minItems"),
+    ] {
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                let path = format!("{}/{}", schema_path, name);
+                return Err(invalid(&path, "the minimum is above the maximum".to_string()).into());
+            }
+        }
+    }
+
+    if let Some(ref items) = schema.items {
+        with_segment(schema_path, "items", |path| compile(items, path, patterns))?;
+    }
+
+    if let Some(ref properties) = schema.properties {
+        for (name, property) in properties {
+            with_segment(schema_path, "properties", |path| {
+                with_segment(path, name, |path| compile(property, path, patterns))
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates the documents of an update against a schema, and keeps track of the invalid ones.
+pub struct Validator<'a> {
+    document_schema: &'a DocumentSchema,
+    /// The compiled patterns, by the path of their keyword in the schema.
+    patterns: HashMap<String, Regex>,
+    invalid: InvalidDocuments,
+}
+
+impl Validator<'_> {
+    /// Validates `document`, and returns whether it is kept in the update. In `fail` mode the
+    /// invalid documents are only recorded, the update is failed by [`Validator::finish`].
+    pub fn validate(&mut self, document: &Document, document_id: Option<&str>) -> bool {
+        let schema = &self.document_schema.schema;
+        let mut violations = Vec::new();
+
+        if let Some(ref types) = schema.types {
+            if !types.matches(&Value::Object(Map::new())) {
+                violations.push(violation("", "", "type"));
+            }
+        }
+        if let Some(ref values) = schema.values {
+            if !values
+                .iter()
+                .any(|value| value.as_object() == Some(document))
+            {
+                violations.push(violation("", "", "enum"));
+            }
+        }
+        self.check_object(
+            schema,
+            document,
+            &mut String::new(),
+            &mut String::new(),
+            &mut violations,
+        );
+
+        if violations.is_empty() {
+            true
+        } else {
+            self.invalid.push(document_id, violations);
+            false
+        }
+    }
+
+    /// Returns the documents left out of the update, or fails when the update must be failed.
+    pub fn finish(self) -> Result<InvalidDocuments> {
+        match self.document_schema.on_invalid {
+            OnInvalidDocument::Fail if !self.invalid.is_empty() => {
+                Err(SchemaError::InvalidDocuments(self.invalid).into())
+            }
+            _ => Ok(self.invalid),
+        }
+    }
+
+    fn check(
+        &self,
+        schema: &Schema,
+        value: &Value,
+        instance: &mut String,
+        schema_path: &mut String,
+        out: &mut Vec<Violation>,
+    ) {
+        if let Some(ref types) = schema.types {
+            if !types.matches(value) {
+                out.push(violation(instance, schema_path, "type"));
+            }
+        }
+        if let Some(ref values) = schema.values {
+            if !values.contains(value) {
+                out.push(violation(instance, schema_path, "enum"));
+            }
+        }
+
+        match value {
+            Value::Number(number) => {
+                let number = number.as_f64().unwrap_or_default();
+                let bound = |bound: &Option<Number>| bound.as_ref().and_then(Number::as_f64);
+                if bound(&schema.minimum).map_or(false, |min| number < min) {
+                    out.push(violation(instance, schema_path, "minimum"));
+                }
+                if bound(&schema.maximum).map_or(false, |max| number > max) {
+                    out.push(violation(instance, schema_path, "maximum"));
+                }
+            }
+            Value::String(string) => {
+                let length = string.chars().count();
+                if schema.min_length.map_or(false, |min| length < min) {
+                    out.push(violation(instance, schema_path, "minLength"));
+                }
+                if schema.max_length.map_or(false, |max| length > max) {
+                    out.push(violation(instance, schema_path, "maxLength"));
+                }
+                if schema.pattern.is_some() {
+                    let path = format!("{}/pattern", schema_path);
+                    if !self.patterns[&path].is_match(string) {
+                        out.push(violation(instance, schema_path, "pattern"));
+                    }
+                }
+            }
+            Value::Array(values) => {
+                if schema.min_items.map_or(false, |min| values.len() < min) {
+                    out.push(violation(instance, schema_path, "minItems"));
+                }
+                if schema.max_items.map_or(false, |max| values.len() > max) {
+                    out.push(violation(instance, schema_path, "maxItems"));
+                }
+                if let Some(ref items) = schema.items {
+                    with_segment(schema_path, "items", |schema_path| {
+                        for (i, value) in values.iter().enumerate() {
+                            with_segment(instance, &i.to_string(), |instance| {
+                                self.check(items, value, instance, schema_path, out)
+                            });
+                        }
+                    });
+                }
+            }
+            Value::Object(object) => self.check_object(schema, object, instance, schema_path, out),
+            Value::Null | Value::Bool(_) => (),
+        }
+    }
+
+    fn check_object(
+        &self,
+        schema: &Schema,
+        object: &Map<String, Value>,
+        instance: &mut String,
+        schema_path: &mut String,
+        out: &mut Vec<Violation>,
+    ) {
+        if let Some(ref required) = schema.required {
+            for name in required.iter().filter(|name| !object.contains_key(*name)) {
+                with_segment(instance, name, |instance| {
+                    out.push(violation(instance, schema_path, "required"))
+                });
+            }
+        }
+
+        for (name, value) in object {
+            let property = schema
+                .properties
+                .as_ref()
+                .and_then(|properties| properties.get(name));
+            with_segment(instance, name, |instance| match property {
+                Some(property) => with_segment(schema_path, "properties", |schema_path| {
+                    with_segment(schema_path, name, |schema_path| {
+                        self.check(property, value, instance, schema_path, out)
+                    })
+                }),
+                None if schema.additional_properties == Some(false) => {
+                    out.push(violation(instance, schema_path, "additionalProperties"))
+                }
+                None => (),
+            });
+        }
+    }
+}
+
+fn violation(instance: &str, schema_path: &str, keyword: &str) -> Violation {
+    Violation {
+        instance_path: instance.to_string(),
+        schema_path: format!("{}/{}", schema_path, keyword),
+    }
+}
+
+/// Runs `f` with `segment` appended to the JSON pointer `path`.
+fn with_segment<T>(path: &mut String, segment: &str, f: impl FnOnce(&mut String) -> T) -> T {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    let result = f(path);
+    path.truncate(len);
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn document_schema(schema: Value, on_invalid: &str) -> DocumentSchema {
+        serde_json::from_value(json!({ "schema": schema, "onInvalid": on_invalid })).unwrap()
+    }
+
+    fn document(value: Value) -> Document {
+        match value {
+            Value::Object(object) => object,
+            _ => panic!("not an object"),
+        }
+    }
+
+    fn violations(validator: &mut Validator, value: Value) -> Vec<(String, String)> {
+        validator.validate(&document(value), None);
+        let invalid = std::mem::take(&mut validator.invalid);
+        invalid
+            .documents
+            .into_iter()
+            .flat_map(|d| d.errors)
+            .map(|e| (e.instance_path, e.schema_path))
+            .collect()
+    }
+
+    fn pair(instance: &str, schema: &str) -> (String, String) {
+        (instance.to_string(), schema.to_string())
+    }
+
+    #[test]
+    fn validate_documents() {
+        let schema = document_schema(
+            json!({
+                "type": "object",
+                "required": ["id", "title"],
+                "properties": {
+                    "id": { "type": ["integer", "string"] },
+                    "title": { "type": "string", "minLength": 1, "maxLength": 10 },
+                    "price": { "type": "number", "minimum": 0 },
+                    "sku": { "type": "string", "pattern": "^[A-Z]{3}-[0-9]+$" },
+                    "tags": { "type": "array", "maxItems": 2, "items": { "type": "string" } },
+                    "author": {
+                        "type": "object",
+                        "additionalProperties": false,
+                        "properties": { "name": { "type": "string" } }
+                    },
+                    "color": { "enum": ["red", "blue"] }
+                }
+            }),
+            "fail",
+        );
+        let mut validator = schema.validator().unwrap();
+
+        let valid = json!({
+            "id": 1,
+            "title": "shirt",
+            "price": 10.5,
+            "sku": "ABC-12",
+            "tags": ["a", "b"],
+            "author": { "name": "Jane" },
+            "color": "red",
+            "extra": true,
+        });
+        assert!(violations(&mut validator, valid).is_empty());
+
+        let invalid = json!({
+            "id": 1.5,
+            "price": -1,
+            "sku": "abc",
+            "tags": ["a", 2, "c"],
+            "author": { "name": "Jane", "age": 42 },
+            "color": "green",
+        });
+        assert_eq!(
+            violations(&mut validator, invalid),
+            vec![
+                pair("/title", "/required"),
+                pair("/id", "/properties/id/type"),
+                pair("/price", "/properties/price/minimum"),
+                pair("/sku", "/properties/sku/pattern"),
+                pair("/tags", "/properties/tags/maxItems"),
+                pair("/tags/1", "/properties/tags/items/type"),
+                pair("/author/age", "/properties/author/additionalProperties"),
+                pair("/color", "/properties/color/enum"),
+            ]
+        );
+    }
+
+    #[test]
+    fn escape_pointers() {
+        let schema = document_schema(
+            json!({ "properties": { "a/b": { "type": "string" } } }),
+            "fail",
+        );
+        let mut validator = schema.validator().unwrap();
+        assert_eq!(
+            violations(&mut validator, json!({ "a/b": 1 })),
+            vec![pair("/a~1b", "/properties/a~1b/type")]
+        );
+    }
+
+    #[test]
+    fn fail_or_skip_invalid_documents() {
+        let schema = json!({ "properties": { "price": { "type": "number" } } });
+
+        let schema = document_schema(schema, "skip");
+        let mut validator = schema.validator().unwrap();
+        assert!(validator.validate(&document(json!({ "price": 1 })), Some("1")));
+        assert!(!validator.validate(&document(json!({ "price": "1" })), Some("2")));
+        let invalid = validator.finish().unwrap();
+        assert_eq!(invalid.count, 1);
+        assert_eq!(invalid.documents[0].document_id.as_deref(), Some("2"));
+
+        let schema = DocumentSchema {
+            on_invalid: OnInvalidDocument::Fail,
+            ..schema
+        };
+        let mut validator = schema.validator().unwrap();
+        validator.validate(&document(json!({ "price": "1" })), Some("2"));
+        assert!(validator.finish().is_err());
+    }
+
+    #[test]
+    fn report_a_bounded_number_of_documents() {
+        let schema = document_schema(json!({ "required": ["id"] }), "skip");
+        let mut validator = schema.validator().unwrap();
+        for _ in 0..MAX_REPORTED_INVALID_DOCUMENTS + 5 {
+            validator.validate(&Document::new(), None);
+        }
+        let invalid = validator.finish().unwrap();
+        assert_eq!(invalid.count, MAX_REPORTED_INVALID_DOCUMENTS + 5);
+        assert_eq!(invalid.documents.len(), MAX_REPORTED_INVALID_DOCUMENTS);
+    }
+
+    #[test]
+    fn reject_invalid_schemas() {
+        let schema = document_schema(json!({ "pattern": "(" }), "fail");
+        assert!(schema.validator().is_err());
+
+        let schema = document_schema(json!({ "minLength": 3, "maxLength": 2 }), "fail");
+        assert!(schema.validator().is_err());
+
+        let schema: std::result::Result<DocumentSchema, _> =
+            serde_json::from_value(json!({ "schema": { "oneOf": [] } }));
+        assert!(schema.is_err());
+    }
+}
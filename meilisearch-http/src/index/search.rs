@@ -1,17 +1,31 @@
-use std::collections::{BTreeMap, BTreeSet, HashSet};
-use std::time::Instant;
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use chrono::Utc;
 use either::Either;
 use heed::RoTxn;
 use indexmap::IndexMap;
 use meilisearch_tokenizer::{Analyzer, AnalyzerConfig, Token};
-use milli::{FieldId, FieldsIdsMap, FilterCondition, MatchingWords};
+use milli::{DocumentId, FieldId, FieldsIdsMap, FilterCondition, MatchingWords, Search};
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::index::error::FacetError;
 
-use super::error::Result;
+use super::boost::{Boost, BOOST_WINDOW_SIZE};
+use super::dates::{convert_filter_dates, has_relative_dates, original_dates, restore_dates};
+use super::error::{IndexError, Result};
+use super::facets::{FacetRangeCount, FacetRanges, FacetsDistribution};
+use super::filter::{compile_filter, filter_to_json};
+use super::group::{GroupBy, GROUP_BY_CHUNK_SIZE};
+use super::nested::{field_ids, unflatten};
+use super::pagination::total_pages;
+use super::rules::QueryRewrite;
+use super::vectors::{hybrid_rank, semantic_search, Hybrid};
 use super::Index;
 
 pub type Document = IndexMap<String, Value>;
@@ -24,32 +38,39 @@ pub struct MatchInfo {
 }
 
 pub const DEFAULT_SEARCH_LIMIT: usize = 20;
-const fn default_search_limit() -> usize {
-    DEFAULT_SEARCH_LIMIT
-}
-
 pub const DEFAULT_CROP_LENGTH: usize = 200;
-pub const fn default_crop_length() -> usize {
-    DEFAULT_CROP_LENGTH
-}
+
+/// The number of documents ranked by the first milli search of a search having a deadline. Each
+/// following milli search ranks twice as many documents as the previous one.
+const FIRST_RANKED_CHUNK_SIZE: usize = 20;
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct SearchQuery {
     pub q: Option<String>,
     pub offset: Option<usize>,
-    #[serde(default = "default_search_limit")]
-    pub limit: usize,
+    pub limit: Option<usize>,
+    pub page: Option<usize>,
+    pub hits_per_page: Option<usize>,
     pub attributes_to_retrieve: Option<BTreeSet<String>>,
     pub attributes_to_crop: Option<Vec<String>>,
-    #[serde(default = "default_crop_length")]
-    pub crop_length: usize,
-    pub attributes_to_highlight: Option<HashSet<String>>,
+    pub crop_length: Option<usize>,
+    pub attributes_to_highlight: Option<BTreeSet<String>>,
     // Default to false
     #[serde(default = "Default::default")]
     pub matches: bool,
     pub filter: Option<Value>,
     pub facets_distribution: Option<Vec<String>>,
+    pub hierarchical_facets: Option<BTreeMap<String, Vec<String>>>,
+    pub facet_ranges: Option<BTreeMap<String, FacetRanges>>,
+    pub group_by: Option<GroupBy>,
+    pub boost: Option<Vec<Boost>>,
+    pub timeout_ms: Option<u64>,
+    /// Set when the search is superseded, to stop it as if its deadline was exceeded.
+    #[serde(skip)]
+    pub cancelled: Option<Arc<AtomicBool>>,
+    pub vector: Option<Vec<f32>>,
+    pub hybrid: Option<Hybrid>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -62,20 +83,103 @@ pub struct SearchHit {
     pub matches_info: Option<MatchesInfo>,
 }
 
+#[derive(Serialize, Debug)]
+pub struct SearchGroup {
+    pub value: Value,
+    pub hits: Vec<SearchHit>,
+    pub total: u64,
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchResult {
     pub hits: Vec<SearchHit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<SearchGroup>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub applied_rules: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rewrites: Vec<QueryRewrite>,
     pub nb_hits: u64,
     pub exhaustive_nb_hits: bool,
     pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewritten_query: Option<String>,
     pub limit: usize,
     pub offset: usize,
-    pub processing_time_ms: u128,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub facets_distribution: Option<BTreeMap<String, BTreeMap<String, u64>>>,
+    pub page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hits_per_page: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_hits: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_pages: Option<u64>,
+    pub processing_time_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets_distribution: Option<FacetsDistribution>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facet_ranges: Option<BTreeMap<String, Vec<FacetRangeCount>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub exhaustive_facets_count: Option<bool>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    /// Whether the filter, once rewritten by the rules, holds dates relative to the time of the
+    /// search, so the same search may find other documents later.
+    #[serde(skip)]
+    pub relative_dates: bool,
+}
+
+/// The point in time after which a search must stop refining its results and return what it
+/// has already ranked.
+///
+/// A single milli search can't be interrupted. The documents of a search having a deadline are
+/// ranked by successive milli searches, and the deadline is checked between them, as well as
+/// between the ranking passes that are done on our side: reordering the boosted window and
+/// filling the groups. A cancelled search exceeds its deadline.
+pub struct Deadline {
+    at: Option<Instant>,
+    cancelled: Option<Arc<AtomicBool>>,
+    exceeded: Cell<bool>,
+}
+
+impl Deadline {
+    /// A timeout too long to be represented is no deadline.
+    pub fn new(
+        start: Instant,
+        timeout_ms: Option<u64>,
+        cancelled: Option<Arc<AtomicBool>>,
+    ) -> Self {
+        Self {
+            at: timeout_ms.and_then(|ms| start.checked_add(Duration::from_millis(ms))),
+            cancelled,
+            exceeded: Cell::new(false),
+        }
+    }
+
+    /// Returns whether the search may be stopped before it ranked all the requested documents.
+    fn is_bounded(&self) -> bool {
+        self.at.is_some() || self.cancelled.is_some()
+    }
+
+    /// Returns whether the deadline is exceeded, and remembers it for the search result.
+    pub fn is_exceeded(&self) -> bool {
+        if let Some(at) = self.at {
+            if Instant::now() >= at {
+                self.exceeded.set(true);
+            }
+        }
+        if let Some(ref cancelled) = self.cancelled {
+            if cancelled.load(Ordering::Relaxed) {
+                self.exceeded.set(true);
+            }
+        }
+        self.exceeded.get()
+    }
+
+    pub fn was_exceeded(&self) -> bool {
+        self.exceeded.get()
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -84,39 +188,203 @@ struct FormatOptions {
     crop: Option<usize>,
 }
 
+/// What happened during a search, recorded to explain it.
+#[derive(Default)]
+pub struct SearchTrace {
+    /// The compiled filter, in the structured filter format.
+    pub filter: Option<Value>,
+    pub candidates: RoaringBitmap,
+    /// The time spent parsing, compiling and evaluating the filter.
+    pub filtering: Duration,
+    /// The time spent in milli, evaluating the filter and sorting the candidates in buckets, and
+    /// reordering them according to the rules and the boosts.
+    pub ranking: Duration,
+    pub fetching: Duration,
+    pub formatting: Duration,
+}
+
 impl Index {
     pub fn perform_search(&self, query: SearchQuery) -> Result<SearchResult> {
+        self.perform_search_traced(query, None)
+    }
+
+    pub fn perform_search_traced(
+        &self,
+        mut query: SearchQuery,
+        mut trace: Option<&mut SearchTrace>,
+    ) -> Result<SearchResult> {
         let before_search = Instant::now();
+        let deadline = Deadline::new(before_search, query.timeout_ms, query.cancelled.clone());
         let rtxn = self.read_txn()?;
 
+        let paged = query.page.is_some() || query.hits_per_page.is_some();
+        if paged && (query.offset.is_some() || query.limit.is_some()) {
+            return Err(IndexError::MixedPagination);
+        }
+
+        // The defaults of the index fill the parameters the request left out.
+        self.search_defaults(&rtxn)?.apply(&mut query);
+
+        // The pages are translated to an offset and a limit, the default limit being the default
+        // number of hits per page. The page 0 is always empty.
+        let page = paged.then(|| query.page.unwrap_or(1));
+        let hits_per_page = paged.then(|| {
+            query
+                .hits_per_page
+                .or(query.limit)
+                .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        });
+        if let (Some(page), Some(hits_per_page)) = (page, hits_per_page) {
+            query.offset = Some(page.saturating_sub(1).saturating_mul(hits_per_page));
+            query.limit = Some(if page == 0 { 0 } else { hits_per_page });
+        }
+
+        // No document ranked after `maxTotalHits` is returned.
+        let pagination = self.pagination(&rtxn)?;
+        let (offset, limit) = pagination.bound(
+            query.offset.unwrap_or_default(),
+            query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        );
+        query.offset = Some(offset);
+
+        // The rules are fired by the query sent by the user, and rewrite it before the search.
+        let original_query = query.q.clone().unwrap_or_default();
+        let rules = self.fire_rules(&rtxn, &original_query, Utc::now())?;
+        let rewrites = rules.rewrite_query(&mut query);
+
         let mut search = self.search(&rtxn);
 
         if let Some(ref query) = query.q {
             search.query(query);
         }
 
-        search.limit(query.limit);
-        search.offset(query.offset.unwrap_or_default());
+        let semantic = semantic_search(&query)?;
 
+        let boosts = match query.boost {
+            Some(_) if query.group_by.is_some() => return Err(IndexError::BoostWithGroupBy),
+            Some(_) if semantic.is_some() || !self.boostable(&rtxn, query.q.as_deref())? => {
+                return Err(IndexError::UnboostableSearch)
+            }
+            Some(ref boosts) => Some(self.compile_boosts(&rtxn, boosts)?),
+            None => None,
+        };
+
+        // Pinning and hiding documents don't apply to grouped results.
+        let reorder = rules.reorders() && query.group_by.is_none();
+        let reranked =
+            (reorder || boosts.is_some() || semantic.is_some()) && query.group_by.is_none();
+        let window = query.offset.unwrap_or_default() + limit + rules.extra_documents();
+
+        // When grouping, the pagination applies to the groups and the ranked documents are
+        // fetched by chunks from the start. When the rules or the boosts reorder the documents,
+        // the page is computed from the start of the ranked documents.
+        let (ranked_offset, ranked_limit) = match query.group_by {
+            Some(_) => (0, GROUP_BY_CHUNK_SIZE),
+            None if reranked => (0, window),
+            None => (query.offset.unwrap_or_default(), limit),
+        };
+
+        let relative_dates = query.filter.as_ref().map_or(false, has_relative_dates);
+
+        let before_filtering = Instant::now();
+        let mut filtered = None;
         if let Some(ref filter) = query.filter {
             if let Some(facets) = parse_filter(filter, self, &rtxn)? {
+                if let Some(ref mut trace) = trace {
+                    trace.filter = Some(filter_to_json(&facets, &self.fields_ids_map(&rtxn)?));
+                }
+                // The vector search is filtered apart from milli. The filter of a traced search
+                // is evaluated apart too, to time its evaluation.
+                if semantic.is_some() || trace.is_some() {
+                    filtered = Some(facets.evaluate(&rtxn, self)?);
+                }
                 search.filter(facets);
             }
         }
 
+        let before_ranking = Instant::now();
+        let filtering = before_ranking - before_filtering;
+
         let milli::SearchResult {
-            documents_ids,
+            mut documents_ids,
             matching_words,
-            candidates,
+            mut candidates,
             ..
-        } = search.execute()?;
+        } = execute_search(&mut search, ranked_offset, ranked_limit, &deadline)?;
+
+        // Every document having a vector matches a semantic search, the nearest ones are merged
+        // with the documents ranked by milli.
+        if let Some((vector, semantic_ratio)) = semantic {
+            let allowed = match filtered {
+                Some(filtered) => filtered,
+                None => self.documents_ids(&rtxn)?,
+            };
+            let neighbours = self.nearest_documents(&rtxn, vector, window, &allowed)?;
+            let matches = allowed & self.vectors_ids(&rtxn)?;
+            if semantic_ratio >= 1.0 {
+                candidates = matches;
+            } else if semantic_ratio > 0.0 {
+                candidates |= matches;
+            }
+            documents_ids = hybrid_rank(&documents_ids, &neighbours, semantic_ratio);
+        }
+
+        // The boosts reorder the whole ranking buckets that end within the first
+        // `BOOST_WINDOW_SIZE` documents, the documents are ranked until the bucket of the last
+        // document of the page ends. When the search already took too long, the boosts are not
+        // applied and the documents are kept in the order milli ranked them.
+        if let Some(ref boosts) = boosts {
+            let mut exhausted = documents_ids.len() < ranked_limit;
+            let considered = documents_ids.len().min(BOOST_WINDOW_SIZE);
+            let mut buckets = self.ranking_buckets(&rtxn, &documents_ids[..considered])?;
+            while !exhausted
+                && buckets.last().map_or(false, |last| last.start < window)
+                && documents_ids.len() < BOOST_WINDOW_SIZE
+                && !deadline.is_exceeded()
+            {
+                let chunk = documents_ids
+                    .len()
+                    .max(FIRST_RANKED_CHUNK_SIZE)
+                    .min(BOOST_WINDOW_SIZE - documents_ids.len());
+                let ranked = execute_search(&mut search, documents_ids.len(), chunk, &deadline)?;
+                exhausted = ranked.documents_ids.len() < chunk;
+                documents_ids.extend(ranked.documents_ids);
+                buckets = self.ranking_buckets(&rtxn, &documents_ids)?;
+            }
+
+            // The last bucket is only whole when all the documents were ranked.
+            let complete = exhausted && documents_ids.len() <= BOOST_WINDOW_SIZE;
+            let end = documents_ids.len().min(BOOST_WINDOW_SIZE);
+            buckets.retain(|bucket| bucket.end < end || complete);
+            if !deadline.is_exceeded() {
+                boosts.apply(&mut documents_ids, &buckets);
+            }
+        }
+
+        if reranked {
+            documents_ids = rules.apply(documents_ids, query.offset.unwrap_or_default(), limit);
+        }
+
+        if reorder {
+            candidates |= rules
+                .pinned
+                .iter()
+                .map(|(_, id)| *id)
+                .collect::<RoaringBitmap>();
+            candidates -= &rules.hidden;
+        }
+
+        let ranking = before_ranking.elapsed();
 
         let fields_ids_map = self.fields_ids_map(&rtxn).unwrap();
+        let date_attributes = self.date_attributes(&rtxn)?;
+
+        let nested_fields = self.nested_fields(&rtxn)?;
 
-        let displayed_ids = self
-            .displayed_fields_ids(&rtxn)?
-            .map(|fields| fields.into_iter().collect::<BTreeSet<_>>())
-            .unwrap_or_else(|| fields_ids_map.iter().map(|(id, _)| id).collect());
+        let displayed_ids: BTreeSet<_> = self
+            .displayed_ids(&rtxn, &fields_ids_map)?
+            .into_iter()
+            .collect();
 
         let fids = |attrs: &BTreeSet<String>| {
             let mut ids = BTreeSet::new();
@@ -126,9 +394,7 @@ impl Index {
                     break;
                 }
 
-                if let Some(id) = fields_ids_map.id(attr) {
-                    ids.insert(id);
-                }
+                ids.extend(field_ids(&fields_ids_map, attr));
             }
             ids
         };
@@ -158,7 +424,7 @@ impl Index {
         let formatted_options = compute_formatted_options(
             &attr_to_highlight,
             &attr_to_crop,
-            query.crop_length,
+            query.crop_length.unwrap_or(DEFAULT_CROP_LENGTH),
             &to_retrieve_ids,
             &fields_ids_map,
             &displayed_ids,
@@ -171,65 +437,175 @@ impl Index {
 
         let formatter = Formatter::new(&analyzer, (String::from("<em>"), String::from("</em>")));
 
-        let mut documents = Vec::new();
-
-        let documents_iter = self.documents(&rtxn, documents_ids)?;
-
-        for (_id, obkv) in documents_iter {
-            let document = make_document(&to_retrieve_ids, &fields_ids_map, obkv)?;
+        let fetching = Cell::new(Duration::default());
+        let formatting = Cell::new(Duration::default());
+
+        let make_hits = |documents_ids: Vec<DocumentId>| -> Result<Vec<SearchHit>> {
+            let mut documents = Vec::new();
+
+            let before_fetching = Instant::now();
+            let documents_iter = self.documents(&rtxn, documents_ids)?;
+            fetching.set(fetching.get() + before_fetching.elapsed());
+
+            for (_id, obkv) in documents_iter {
+                let before_fetching = Instant::now();
+                let mut document = make_document(&to_retrieve_ids, &fields_ids_map, obkv)?;
+                let original_dates = original_dates(&fields_ids_map, obkv)?;
+                restore_dates(document.iter_mut(), &date_attributes, &original_dates);
+                fetching.set(fetching.get() + before_fetching.elapsed());
+
+                let before_formatting = Instant::now();
+                let matches_info = query
+                    .matches
+                    .then(|| compute_matches(&matching_words, &document, &analyzer));
+
+                let mut formatted = format_fields(
+                    &fields_ids_map,
+                    obkv,
+                    &formatter,
+                    &matching_words,
+                    &formatted_options,
+                )?;
+                restore_dates(formatted.iter_mut(), &date_attributes, &original_dates);
+                formatting.set(formatting.get() + before_formatting.elapsed());
+
+                let hit = SearchHit {
+                    document: unflatten(document, &nested_fields),
+                    formatted: unflatten(formatted, &nested_fields),
+                    matches_info,
+                };
+                documents.push(hit);
+            }
 
-            let matches_info = query
-                .matches
-                .then(|| compute_matches(&matching_words, &document, &analyzer));
+            Ok(documents)
+        };
 
-            let formatted = format_fields(
-                &fields_ids_map,
-                obkv,
-                &formatter,
-                &matching_words,
-                &formatted_options,
-            )?;
+        let (documents, groups) = match query.group_by {
+            Some(ref group_by) => {
+                let ranked_groups = self.group_documents(
+                    &rtxn,
+                    &mut search,
+                    documents_ids,
+                    &candidates,
+                    group_by,
+                    query.offset.unwrap_or_default(),
+                    limit,
+                    &deadline,
+                )?;
+
+                let mut groups = Vec::new();
+                for group in ranked_groups {
+                    groups.push(SearchGroup {
+                        value: group.value,
+                        hits: make_hits(group.documents_ids)?,
+                        total: group.total,
+                    });
+                }
 
-            let hit = SearchHit {
-                document,
-                formatted,
-                matches_info,
-            };
-            documents.push(hit);
-        }
+                (Vec::new(), Some(groups))
+            }
+            None => (make_hits(documents_ids)?, None),
+        };
 
         let nb_hits = candidates.len();
+        let total_hits = pagination.total_hits(nb_hits);
 
-        let facets_distribution = match query.facets_distribution {
+        let mut facets_distribution = match query.facets_distribution {
             Some(ref fields) => {
                 let mut facets_distribution = self.facets_distribution(&rtxn);
                 if fields.iter().all(|f| f != "*") {
                     facets_distribution.facets(fields);
                 }
-                let distribution = facets_distribution.candidates(candidates).execute()?;
+                let distribution = facets_distribution
+                    .candidates(candidates.clone())
+                    .execute()?;
 
                 Some(distribution)
             }
             None => None,
         };
 
+        if let Some(ref selected) = query.hierarchical_facets {
+            let distribution =
+                self.hierarchical_facets_distribution(&rtxn, selected, &candidates)?;
+            facets_distribution
+                .get_or_insert_with(BTreeMap::new)
+                .extend(distribution);
+        }
+
+        let facet_ranges = match query.facet_ranges {
+            Some(ref ranges) => Some(self.facet_ranges(&rtxn, ranges, &candidates)?),
+            None => None,
+        };
+
         let exhaustive_facets_count = facets_distribution.as_ref().map(|_| false); // not implemented yet
 
+        if let Some(trace) = trace {
+            trace.filtering = filtering;
+            trace.ranking = ranking;
+            trace.fetching = fetching.get();
+            trace.formatting = formatting.get();
+            trace.candidates = candidates;
+        }
+
         let result = SearchResult {
             exhaustive_nb_hits: false, // not implemented yet
             hits: documents,
+            groups,
+            applied_rules: rules.ids,
+            rewrites,
             nb_hits,
-            query: query.q.clone().unwrap_or_default(),
-            limit: query.limit,
+            rewritten_query: query.q.filter(|q| *q != original_query),
+            query: original_query,
+            limit,
             offset: query.offset.unwrap_or_default(),
-            processing_time_ms: before_search.elapsed().as_millis(),
+            processing_time_ms: before_search.elapsed().as_millis() as u64,
+            page,
+            hits_per_page,
+            total_hits: paged.then(|| total_hits),
+            total_pages: hits_per_page.map(|hits_per_page| total_pages(total_hits, hits_per_page)),
             facets_distribution,
+            facet_ranges,
             exhaustive_facets_count,
+            timed_out: deadline.was_exceeded(),
+            relative_dates,
         };
         Ok(result)
     }
 }
 
+/// Ranks the `limit` documents following `offset` with the milli `search`. When the search may
+/// be stopped by its `deadline`, the documents are ranked by chunks of increasing size, so that
+/// no more than twice the documents are ranked, and the deadline is checked after each chunk.
+/// The documents already ranked are returned when the deadline is exceeded.
+fn execute_search(
+    search: &mut Search,
+    offset: usize,
+    limit: usize,
+    deadline: &Deadline,
+) -> Result<milli::SearchResult> {
+    let mut requested = match deadline.is_bounded() {
+        true => limit.min(FIRST_RANKED_CHUNK_SIZE),
+        false => limit,
+    };
+    search.offset(offset);
+    search.limit(requested);
+    let mut result = search.execute()?;
+
+    // A chunk shorter than requested means that all the documents are ranked.
+    while result.documents_ids.len() == requested && requested < limit && !deadline.is_exceeded() {
+        let chunk = requested.min(limit - requested);
+        search.offset(offset + requested);
+        search.limit(chunk);
+        let ranked = search.execute()?;
+        result.documents_ids.extend(ranked.documents_ids);
+        result.candidates |= ranked.candidates;
+        requested += chunk;
+    }
+
+    Ok(result)
+}
+
 fn compute_matches<A: AsRef<[u8]>>(
     matcher: &impl Matcher,
     document: &Document,
@@ -278,7 +654,7 @@ fn compute_value_matches<'a, A: AsRef<[u8]>>(
 }
 
 fn compute_formatted_options(
-    attr_to_highlight: &HashSet<String>,
+    attr_to_highlight: &BTreeSet<String>,
     attr_to_crop: &[String],
     query_crop_length: usize,
     to_retrieve_ids: &BTreeSet<u8>,
@@ -312,7 +688,7 @@ fn compute_formatted_options(
 
 fn add_highlight_to_formatted_options(
     formatted_options: &mut BTreeMap<FieldId, FormatOptions>,
-    attr_to_highlight: &HashSet<String>,
+    attr_to_highlight: &BTreeSet<String>,
     fields_ids_map: &FieldsIdsMap,
     displayed_ids: &BTreeSet<u8>,
 ) {
@@ -329,7 +705,7 @@ fn add_highlight_to_formatted_options(
             break;
         }
 
-        if let Some(id) = fields_ids_map.id(&attr) {
+        for id in field_ids(fields_ids_map, attr) {
             if displayed_ids.contains(&id) {
                 formatted_options.insert(id, new_format);
             }
@@ -366,7 +742,7 @@ fn add_crop_to_formatted_options(
             }
         }
 
-        if let Some(id) = fields_ids_map.id(&attr_name) {
+        for id in field_ids(fields_ids_map, attr_name) {
             if displayed_ids.contains(&id) {
                 formatted_options
                     .entry(id)
@@ -595,14 +971,20 @@ impl<'a, A: AsRef<[u8]>> Formatter<'a, A> {
     }
 }
 
-fn parse_filter(facets: &Value, index: &Index, txn: &RoTxn) -> Result<Option<FilterCondition>> {
+pub(super) fn parse_filter(
+    facets: &Value,
+    index: &Index,
+    txn: &RoTxn,
+) -> Result<Option<FilterCondition>> {
     match facets {
         Value::String(expr) => {
-            let condition = FilterCondition::from_str(txn, index, expr)?;
+            let expr = convert_filter_dates(expr, &index.date_attributes(txn)?, Utc::now())?;
+            let condition = FilterCondition::from_str(txn, index, &expr)?;
             Ok(Some(condition))
         }
         Value::Array(arr) => parse_filter_array(txn, index, arr),
-        v => Err(FacetError::InvalidExpression(&["Array"], v.clone()).into()),
+        Value::Object(_) => Ok(Some(compile_filter(index, txn, facets)?)),
+        v => Err(FacetError::InvalidExpression(&["String", "Array", "Object"], v.clone()).into()),
     }
 }
 
@@ -611,15 +993,23 @@ fn parse_filter_array(
     index: &Index,
     arr: &[Value],
 ) -> Result<Option<FilterCondition>> {
+    let date_attributes = index.date_attributes(txn)?;
+    let now = Utc::now();
+    let convert = |expr: &str| convert_filter_dates(expr, &date_attributes, now);
+
+    // The structured filters of the array, added by the rules to a filter in the array form,
+    // are compiled apart and joined to the rest of the filter.
+    let mut structured = Vec::new();
     let mut ands = Vec::new();
     for value in arr {
         match value {
-            Value::String(s) => ands.push(Either::Right(s.clone())),
+            Value::String(s) => ands.push(Either::Right(convert(s)?)),
+            Value::Object(_) => structured.push(compile_filter(index, txn, value)?),
             Value::Array(arr) => {
                 let mut ors = Vec::new();
                 for value in arr {
                     match value {
-                        Value::String(s) => ors.push(s.clone()),
+                        Value::String(s) => ors.push(convert(s)?),
                         v => {
                             return Err(FacetError::InvalidExpression(&["String"], v.clone()).into())
                         }
@@ -628,14 +1018,28 @@ fn parse_filter_array(
                 ands.push(Either::Left(ors));
             }
             v => {
-                return Err(
-                    FacetError::InvalidExpression(&["String", "[String]"], v.clone()).into(),
+                return Err(FacetError::InvalidExpression(
+                    &["String", "[String]", "Object"],
+                    v.clone(),
                 )
+                .into())
             }
         }
     }
 
-    Ok(FilterCondition::from_array(txn, &index.0, ands)?)
+    let condition = FilterCondition::from_array(txn, &index.0, ands)?;
+    let condition =
+        structured
+            .into_iter()
+            .fold(condition, |condition, structured| match condition {
+                Some(condition) => Some(FilterCondition::And(
+                    Box::new(condition),
+                    Box::new(structured),
+                )),
+                None => Some(structured),
+            });
+
+    Ok(condition)
 }
 
 #[cfg(test)]
@@ -0,0 +1,104 @@
+use std::collections::BTreeSet;
+
+use heed::types::{SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+
+use super::error::Result;
+use super::{Index, SearchQuery};
+
+const SEARCH_DEFAULTS_KEY: &str = "search-defaults";
+
+/// The search parameters used when a search request doesn't specify them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SearchDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attributes_to_retrieve: Option<BTreeSet<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attributes_to_highlight: Option<BTreeSet<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attributes_to_crop: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crop_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl SearchDefaults {
+    /// Fills the parameters `query` doesn't specify with the defaults, the parameters of the
+    /// query always win.
+    pub fn apply(&self, query: &mut SearchQuery) {
+        fn fill<T: Clone>(param: &mut Option<T>, default: &Option<T>) {
+            if param.is_none() {
+                *param = default.clone();
+            }
+        }
+
+        fill(
+            &mut query.attributes_to_retrieve,
+            &self.attributes_to_retrieve,
+        );
+        fill(
+            &mut query.attributes_to_highlight,
+            &self.attributes_to_highlight,
+        );
+        fill(&mut query.attributes_to_crop, &self.attributes_to_crop);
+        fill(&mut query.crop_length, &self.crop_length);
+        fill(&mut query.limit, &self.limit);
+    }
+}
+
+impl Index {
+    pub fn search_defaults(&self, txn: &RoTxn) -> Result<SearchDefaults> {
+        let defaults = self
+            .main
+            .get::<_, Str, SerdeJson<SearchDefaults>>(txn, SEARCH_DEFAULTS_KEY)?
+            .unwrap_or_default();
+        Ok(defaults)
+    }
+
+    pub fn put_search_defaults(&self, txn: &mut RwTxn, defaults: &SearchDefaults) -> Result<()> {
+        self.main
+            .put::<_, Str, SerdeJson<SearchDefaults>>(txn, SEARCH_DEFAULTS_KEY, defaults)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn request_wins_over_defaults() {
+        let defaults: SearchDefaults = serde_json::from_value(json!({
+            "attributesToRetrieve": ["title", "overview"],
+            "attributesToHighlight": ["title"],
+            "cropLength": 10,
+            "limit": 5,
+        }))
+        .unwrap();
+
+        let mut query: SearchQuery = serde_json::from_value(json!({
+            "q": "shirt",
+            "attributesToRetrieve": ["title"],
+            "limit": 50,
+        }))
+        .unwrap();
+        defaults.apply(&mut query);
+
+        assert_eq!(
+            query.attributes_to_retrieve,
+            Some(vec!["title".to_string()].into_iter().collect())
+        );
+        assert_eq!(query.limit, Some(50));
+        assert_eq!(
+            query.attributes_to_highlight,
+            Some(vec!["title".to_string()].into_iter().collect())
+        );
+        assert_eq!(query.attributes_to_crop, None);
+        assert_eq!(query.crop_length, Some(10));
+    }
+}
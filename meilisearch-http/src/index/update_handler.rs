@@ -1,6 +1,6 @@
 use std::fs::File;
 
-use crate::index::Index;
+use crate::index::{decode_file, Index};
 use grenad::CompressionType;
 use milli::update::UpdateBuilder;
 use rayon::ThreadPool;
@@ -71,17 +71,31 @@ impl UpdateHandler {
             DocumentsAddition {
                 method,
                 format,
+                csv_delimiter,
+                encoding,
                 primary_key,
-            } => index.update_documents(
-                *format,
-                *method,
-                content,
-                update_builder,
-                primary_key.as_deref(),
-            ),
+            } => content
+                .map(|content| decode_file(content, *encoding))
+                .transpose()
+                .map_err(Into::into)
+                .and_then(|content| {
+                    index.update_documents(
+                        *format,
+                        *csv_delimiter,
+                        *method,
+                        content,
+                        update_builder,
+                        primary_key.as_deref(),
+                    )
+                }),
             ClearDocuments => index.clear_documents(update_builder),
             DeleteDocuments { ids } => index.delete_documents(ids, update_builder),
+            DeleteDocumentsByFilter { filter } => {
+                index.delete_documents_by_filter(filter, update_builder)
+            }
             Settings(settings) => index.update_settings(&settings.clone().check(), update_builder),
+            PutRule { id, rule } => index.put_rule(id.clone(), rule.clone()),
+            DeleteRule { id } => index.delete_rule(id),
         };
 
         match result {
@@ -1,17 +1,29 @@
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::io;
 use std::marker::PhantomData;
 use std::num::NonZeroUsize;
 
-use flate2::read::GzDecoder;
 use log::{debug, info, trace};
 use milli::update::{IndexDocumentsMethod, UpdateBuilder, UpdateFormat};
+use milli::{ExternalDocumentsIds, FieldsIdsMap};
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
 
 use crate::index_controller::UpdateResult;
 
-use super::error::Result;
-use super::{deserialize_some, Index};
+use super::dates::{
+    convert_dates, original_dates, restore_dates, DateAttributes, ORIGINAL_DATES_FIELD,
+};
+use super::error::{DocumentFormatError, Result};
+use super::nested::{flatten, unflatten, NestedFields};
+use super::search::parse_filter;
+use super::vectors::extract_vectors;
+use super::DEFAULT_CSV_DELIMITER;
+use super::{
+    deserialize_some, Document, DocumentSchema, HierarchicalFacets, Index, InvalidDocuments,
+    Pagination, SearchDefaults,
+};
 
 fn serialize_with_wildcard<S>(
     field: &Option<Option<Vec<String>>>,
@@ -81,6 +93,36 @@ pub struct Settings<T> {
         skip_serializing_if = "Option::is_none"
     )]
     pub distinct_attribute: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hierarchical_facets: Option<Option<HierarchicalFacets>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub date_attributes: Option<Option<DateAttributes>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub search_defaults: Option<Option<SearchDefaults>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pagination: Option<Option<Pagination>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub document_schema: Option<Option<DocumentSchema>>,
 
     #[serde(skip)]
     pub _kind: PhantomData<T>,
@@ -96,6 +138,11 @@ impl Settings<Checked> {
             stop_words: Some(None),
             synonyms: Some(None),
             distinct_attribute: Some(None),
+            hierarchical_facets: Some(None),
+            date_attributes: Some(None),
+            search_defaults: Some(None),
+            pagination: Some(None),
+            document_schema: Some(None),
             _kind: PhantomData,
         }
     }
@@ -109,6 +156,11 @@ impl Settings<Checked> {
             stop_words,
             synonyms,
             distinct_attribute,
+            hierarchical_facets,
+            date_attributes,
+            search_defaults,
+            pagination,
+            document_schema,
             ..
         } = self;
 
@@ -120,6 +172,11 @@ impl Settings<Checked> {
             stop_words,
             synonyms,
             distinct_attribute,
+            hierarchical_facets,
+            date_attributes,
+            search_defaults,
+            pagination,
+            document_schema,
             _kind: PhantomData,
         }
     }
@@ -157,6 +214,11 @@ impl Settings<Unchecked> {
             stop_words: self.stop_words,
             synonyms: self.synonyms,
             distinct_attribute: self.distinct_attribute,
+            hierarchical_facets: self.hierarchical_facets,
+            date_attributes: self.date_attributes,
+            search_defaults: self.search_defaults,
+            pagination: self.pagination,
+            document_schema: self.document_schema,
             _kind: PhantomData,
         }
     }
@@ -174,8 +236,9 @@ impl Index {
     pub fn update_documents(
         &self,
         format: UpdateFormat,
+        csv_delimiter: Option<u8>,
         method: IndexDocumentsMethod,
-        content: Option<impl io::Read>,
+        content: Option<impl io::Read + io::Seek>,
         update_builder: UpdateBuilder,
         primary_key: Option<&str>,
     ) -> Result<UpdateResult> {
@@ -183,6 +246,7 @@ impl Index {
         let result = self.update_documents_txn(
             &mut txn,
             format,
+            csv_delimiter,
             method,
             content,
             update_builder,
@@ -196,8 +260,9 @@ impl Index {
         &'a self,
         txn: &mut heed::RwTxn<'a, 'b>,
         format: UpdateFormat,
+        csv_delimiter: Option<u8>,
         method: IndexDocumentsMethod,
-        content: Option<impl io::Read>,
+        content: Option<impl io::Read + io::Seek>,
         update_builder: UpdateBuilder,
         primary_key: Option<&str>,
     ) -> Result<UpdateResult> {
@@ -210,25 +275,129 @@ impl Index {
             builder.execute(|_, _| ())?;
         }
 
-        let mut builder = update_builder.index_documents(txn, self);
-        builder.update_format(format);
-        builder.index_documents_method(method);
+        let date_attributes = self.date_attributes(txn)?;
+        let primary_key = self.primary_key(txn)?.map(String::from);
+        let mut nested_fields = self.nested_fields(txn)?;
+        let nested_fields_count = nested_fields.len();
+        let document_schema = self.document_schema(txn)?;
+        let mut validator = document_schema
+            .as_ref()
+            .map(|schema| schema.validator())
+            .transpose()?;
+        let validates = validator.is_some();
+        // milli merges the fields of a partial update with the stored ones, the nested fields
+        // of an object that is replaced would be kept. When an update replaces objects, the
+        // documents are merged here instead, and replaced. The other updates are left to milli.
+        let stored_documents = match method {
+            IndexDocumentsMethod::UpdateDocuments => Some(StoredDocuments::new(self, txn)?),
+            IndexDocumentsMethod::ReplaceDocuments => None,
+        };
+        let mut merged_documents: HashMap<String, Document> = HashMap::new();
+        let mut converted = false;
 
         let indexing_callback =
             |indexing_step, update_id| debug!("update {}: {:?}", update_id, indexing_step);
 
-        let gzipped = false;
-        let addition = match content {
-            Some(content) if gzipped => {
-                builder.execute(GzDecoder::new(content), indexing_callback)?
+        let (addition, vector_changes, skipped) = match content {
+            // The partial documents are merged with the stored ones, the documents are validated
+            // as they will be stored, the vectors are stored apart from the documents, the nested
+            // objects are flattened so that their fields are indexed on their own, and the dates
+            // are parsed before indexing, so they are indexed as numbers.
+            Some(content) => {
+                let (format, content, vector_changes) = extract_vectors(
+                    format,
+                    csv_delimiter.unwrap_or(DEFAULT_CSV_DELIMITER),
+                    content,
+                    method,
+                    primary_key.as_deref(),
+                    |document| {
+                        validates
+                            || document.values().any(Value::is_object)
+                            || stored_documents
+                                .as_ref()
+                                .map_or(false, |stored| stored.replaces_nested(document))
+                            || document.contains_key(ORIGINAL_DATES_FIELD)
+                            || date_attributes.iter().any(|a| document.contains_key(a))
+                    },
+                    |document, id| {
+                        converted = true;
+                        if document.contains_key(ORIGINAL_DATES_FIELD) {
+                            let field = ORIGINAL_DATES_FIELD.to_string();
+                            return Err(DocumentFormatError::ReservedField(field).into());
+                        }
+                        let merged_id = match (&stored_documents, id) {
+                            (Some(stored_documents), Some(id)) => {
+                                // A document updated several times in the addition is merged
+                                // with its previous update, which replaces the stored one.
+                                let previous = match merged_documents.get(id) {
+                                    Some(previous) => Some(previous.clone()),
+                                    None => stored_documents.get(id)?,
+                                };
+                                if let Some(mut previous) = previous {
+                                    // The fields of the update replace the previous ones, with
+                                    // all the fields nested in them.
+                                    previous.extend(std::mem::take(document));
+                                    *document = previous;
+                                }
+                                Some(id)
+                            }
+                            _ => None,
+                        };
+                        if let Some(ref mut validator) = validator {
+                            if !validator.validate(document, id) {
+                                return Ok(false);
+                            }
+                        }
+                        if let Some(id) = merged_id {
+                            merged_documents.insert(id.to_string(), document.clone());
+                        }
+                        flatten(document, &mut nested_fields)?;
+                        convert_dates(document, &date_attributes);
+                        Ok(true)
+                    },
+                )?;
+                // Nothing is indexed when the update must fail on its invalid documents.
+                let skipped = match validator {
+                    Some(validator) => validator.finish()?,
+                    None => InvalidDocuments::default(),
+                };
+                let mut builder = update_builder.index_documents(txn, self);
+                builder.update_format(format);
+                // The converted partial documents are already merged with the stored ones.
+                if converted {
+                    builder.index_documents_method(IndexDocumentsMethod::ReplaceDocuments);
+                } else {
+                    builder.index_documents_method(method);
+                }
+                let addition = builder.execute(content, indexing_callback)?;
+                (addition, vector_changes, skipped)
+            }
+            None => {
+                let mut builder = update_builder.index_documents(txn, self);
+                builder.update_format(format);
+                builder.index_documents_method(method);
+                (
+                    builder.execute(std::io::empty(), indexing_callback)?,
+                    Vec::new(),
+                    InvalidDocuments::default(),
+                )
             }
-            Some(content) => builder.execute(content, indexing_callback)?,
-            None => builder.execute(std::io::empty(), indexing_callback)?,
         };
 
+        self.apply_vector_changes(txn, vector_changes)?;
+        if nested_fields.len() != nested_fields_count {
+            self.put_nested_fields(txn, &nested_fields)?;
+        }
+        self.hide_original_dates(txn)?;
+
         info!("document addition done: {:?}", addition);
 
-        Ok(UpdateResult::DocumentsAddition(addition))
+        if skipped.is_empty() {
+            Ok(UpdateResult::DocumentsAddition(addition))
+        } else {
+            info!("{} invalid documents skipped", skipped.count);
+            Ok(UpdateResult::DocumentsAdditionSkipped { addition, skipped })
+        }
     }
 
     pub fn clear_documents(&self, update_builder: UpdateBuilder) -> Result<UpdateResult> {
@@ -237,6 +406,7 @@ impl Index {
         let builder = update_builder.clear_documents(&mut wtxn, self);
 
         let _count = builder.execute()?;
+        self.clear_vectors(&mut wtxn)?;
 
         wtxn.commit()
             .and(Ok(UpdateResult::Other))
@@ -249,6 +419,50 @@ impl Index {
         settings: &Settings<Checked>,
         update_builder: UpdateBuilder,
     ) -> Result<UpdateResult> {
+        if let Some(Some(ref document_schema)) = settings.document_schema {
+            document_schema.validator()?;
+        }
+
+        let hierarchical_facets = settings
+            .hierarchical_facets
+            .as_ref()
+            .map(|facets| facets.clone().unwrap_or_default());
+
+        let date_attributes = settings
+            .date_attributes
+            .as_ref()
+            .map(|attributes| attributes.clone().unwrap_or_default());
+        let previous_date_attributes = self.date_attributes(txn)?;
+
+        // The levels of the hierarchical facets and the date attributes must always be
+        // filterable, so we add them to the filterable attributes whenever one of these settings
+        // changes.
+        let filterable_attributes = if settings.filterable_attributes.is_some()
+            || hierarchical_facets.is_some()
+            || date_attributes.is_some()
+        {
+            let mut fields = match settings.filterable_attributes {
+                Some(ref fields) => fields.clone().unwrap_or_else(HashSet::new),
+                None => self.faceted_fields(txn)?,
+            };
+            let facets = match hierarchical_facets {
+                Some(ref facets) => facets.clone(),
+                None => self.hierarchical_facets(txn)?,
+            };
+            fields.extend(facets.into_iter().flat_map(|(_, levels)| levels));
+            match date_attributes {
+                Some(ref attributes) => fields.extend(attributes.iter().cloned()),
+                None => fields.extend(previous_date_attributes.iter().cloned()),
+            }
+            Some(fields)
+        } else {
+            None
+        };
+
+        if settings.searchable_attributes.is_some() {
+            self.put_searchable_wildcard(txn, false)?;
+        }
+
         // We must use the write transaction of the update here.
         let mut builder = update_builder.settings(txn, self);
 
@@ -266,8 +480,7 @@ impl Index {
             }
         }
 
-        if let Some(ref facet_types) = settings.filterable_attributes {
-            let facet_types = facet_types.clone().unwrap_or_else(HashSet::new);
+        if let Some(facet_types) = filterable_attributes {
             builder.set_filterable_fields(facet_types);
         }
 
@@ -303,6 +516,31 @@ impl Index {
             debug!("update {}: {:?}", update_id, indexing_step)
         })?;
 
+        if let Some(ref facets) = hierarchical_facets {
+            self.put_hierarchical_facets(txn, facets)?;
+        }
+
+        if let Some(ref attributes) = date_attributes {
+            if *attributes != previous_date_attributes {
+                self.reindex_dates(txn, &previous_date_attributes, attributes)?;
+            }
+            self.put_date_attributes(txn, attributes)?;
+        }
+
+        if let Some(ref defaults) = settings.search_defaults {
+            self.put_search_defaults(txn, &defaults.clone().unwrap_or_default())?;
+        }
+
+        if let Some(ref pagination) = settings.pagination {
+            self.put_pagination(txn, &pagination.clone().unwrap_or_default())?;
+        }
+
+        if let Some(ref document_schema) = settings.document_schema {
+            self.put_document_schema(txn, document_schema.as_ref())?;
+        }
+
+        self.hide_original_dates(txn)?;
+
         Ok(UpdateResult::Other)
     }
 
@@ -323,18 +561,114 @@ impl Index {
         update_builder: UpdateBuilder,
     ) -> Result<UpdateResult> {
         let mut txn = self.write_txn()?;
-        let mut builder = update_builder.delete_documents(&mut txn, self)?;
 
         // We ignore unexisting document ids
-        document_ids.iter().for_each(|id| {
-            builder.delete_external_id(id);
-        });
+        let ids = {
+            let external_ids = self.external_documents_ids(&txn)?;
+            document_ids
+                .iter()
+                .filter_map(|id| external_ids.get(id))
+                .collect()
+        };
+
+        let deleted = self.delete_documents_txn(&mut txn, &ids, update_builder)?;
+        txn.commit()
+            .and(Ok(UpdateResult::DocumentDeletion { deleted }))
+            .map_err(Into::into)
+    }
 
-        let deleted = builder.execute()?;
+    /// Deletes the documents matching `filter`. The filter is evaluated in the transaction of the
+    /// update, the deleted documents are the ones matching it when the update is processed.
+    pub fn delete_documents_by_filter(
+        &self,
+        filter: &Value,
+        update_builder: UpdateBuilder,
+    ) -> Result<UpdateResult> {
+        let mut txn = self.write_txn()?;
+
+        let ids = match parse_filter(filter, self, &txn)? {
+            Some(condition) => condition.evaluate(&txn, self)?,
+            None => RoaringBitmap::new(),
+        };
+
+        let deleted = self.delete_documents_txn(&mut txn, &ids, update_builder)?;
         txn.commit()
             .and(Ok(UpdateResult::DocumentDeletion { deleted }))
             .map_err(Into::into)
     }
+
+    fn delete_documents_txn<'a, 'b>(
+        &'a self,
+        txn: &mut heed::RwTxn<'a, 'b>,
+        ids: &RoaringBitmap,
+        update_builder: UpdateBuilder,
+    ) -> Result<u64> {
+        // The vectors are deleted first, milli reuses the ids of the deleted documents.
+        self.delete_vectors(txn, ids)?;
+
+        let mut builder = update_builder.delete_documents(txn, self)?;
+        builder.delete_documents(ids);
+        Ok(builder.execute()?)
+    }
+}
+
+/// The stored documents of an index, read to merge the partial updates into them.
+struct StoredDocuments<'t> {
+    index: &'t Index,
+    txn: &'t heed::RoTxn<'t>,
+    external_ids: ExternalDocumentsIds<'t>,
+    fields_ids_map: FieldsIdsMap,
+    nested_fields: NestedFields,
+    date_attributes: DateAttributes,
+}
+
+impl<'t> StoredDocuments<'t> {
+    fn new(index: &'t Index, txn: &'t heed::RoTxn<'t>) -> Result<Self> {
+        Ok(Self {
+            index,
+            txn,
+            external_ids: index.external_documents_ids(txn)?,
+            fields_ids_map: index.fields_ids_map(txn)?,
+            nested_fields: index.nested_fields(txn)?,
+            date_attributes: index.date_attributes(txn)?,
+        })
+    }
+
+    /// Whether the partial update `document` replaces a field that holds an object in the
+    /// stored documents, whose nested fields would be kept by milli.
+    fn replaces_nested(&self, document: &Document) -> bool {
+        self.nested_fields.iter().any(|field| {
+            document
+                .keys()
+                .any(|key| field.starts_with(key.as_str()) && field[key.len()..].starts_with('.'))
+        })
+    }
+
+    /// Returns the document `external_id` as it was sent, with its objects and its dates.
+    fn get(&self, external_id: &str) -> Result<Option<Document>> {
+        let id = match self.external_ids.get(external_id) {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        let obkv = match self.index.documents(self.txn, Some(id))?.pop() {
+            Some((_, obkv)) => obkv,
+            None => return Ok(None),
+        };
+
+        let mut fields = Document::new();
+        for (fid, bytes) in obkv.iter() {
+            match self.fields_ids_map.name(fid) {
+                Some(ORIGINAL_DATES_FIELD) | None => (),
+                Some(name) => {
+                    fields.insert(name.to_string(), serde_json::from_slice(bytes)?);
+                }
+            }
+        }
+        let original_dates = original_dates(&self.fields_ids_map, obkv)?;
+        restore_dates(fields.iter_mut(), &self.date_attributes, &original_dates);
+
+        Ok(Some(unflatten(fields, &self.nested_fields)))
+    }
 }
 
 #[cfg(test)]
@@ -352,6 +686,11 @@ mod test {
             stop_words: None,
             synonyms: None,
             distinct_attribute: None,
+            hierarchical_facets: None,
+            date_attributes: None,
+            search_defaults: None,
+            pagination: None,
+            document_schema: None,
             _kind: PhantomData::<Unchecked>,
         };
 
@@ -372,6 +711,11 @@ mod test {
             stop_words: None,
             synonyms: None,
             distinct_attribute: None,
+            hierarchical_facets: None,
+            date_attributes: None,
+            search_defaults: None,
+            pagination: None,
+            document_schema: None,
             _kind: PhantomData::<Unchecked>,
         };
 
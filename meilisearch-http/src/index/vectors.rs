@@ -0,0 +1,721 @@
+use std::collections::HashMap;
+use std::io::{self, Seek, SeekFrom, Write};
+
+use heed::types::{ByteSlice, SerdeJson, Str};
+use heed::{RoTxn, RwTxn};
+use milli::update::{IndexDocumentsMethod, UpdateFormat};
+use milli::DocumentId;
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::csv_documents::CsvDocuments;
+use super::error::{Result, VectorError};
+use super::hnsw::{self, EntryPoint, GraphStore, GraphStoreMut, Node};
+use super::json_documents::JsonArrayDocuments;
+use super::{Index, SearchQuery};
+
+/// The reserved field holding the vector of a document, its embedding.
+pub const VECTORS_FIELD: &str = "_vectors";
+
+/// Below this number of candidates, the nearest documents are found by comparing the query to
+/// the vectors of all the candidates instead of searching the graph.
+const EXACT_SEARCH_THRESHOLD: u64 = 1000;
+/// The number of candidates considered when searching the graph.
+const EF_SEARCH: usize = 100;
+
+const VECTORS_META_KEY: &str = "vectors-meta";
+const VECTORS_IDS_KEY: &str = "vectors-ids";
+
+fn vector_key(id: DocumentId) -> String {
+    format!("vector-{}", id)
+}
+
+fn node_key(id: DocumentId) -> String {
+    format!("vector-node-{}", id)
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct VectorsMeta {
+    /// The number of dimensions of the vectors of the index, set by the first vector stored.
+    dimensions: Option<usize>,
+    entry_point: Option<EntryPoint>,
+}
+
+/// What an addition does to the vector of a document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VectorChange {
+    Put(Vec<f32>),
+    Delete,
+}
+
+impl Index {
+    fn vectors_meta(&self, txn: &RoTxn) -> Result<VectorsMeta> {
+        let meta = self
+            .main
+            .get::<_, Str, SerdeJson<VectorsMeta>>(txn, VECTORS_META_KEY)?
+            .unwrap_or_default();
+        Ok(meta)
+    }
+
+    fn put_vectors_meta(&self, txn: &mut RwTxn, meta: &VectorsMeta) -> Result<()> {
+        self.main
+            .put::<_, Str, SerdeJson<VectorsMeta>>(txn, VECTORS_META_KEY, meta)?;
+        Ok(())
+    }
+
+    /// Returns the ids of the documents having a vector.
+    pub fn vectors_ids(&self, txn: &RoTxn) -> Result<RoaringBitmap> {
+        match self.main.get::<_, Str, ByteSlice>(txn, VECTORS_IDS_KEY)? {
+            Some(bytes) => Ok(RoaringBitmap::deserialize_from(bytes)?),
+            None => Ok(RoaringBitmap::new()),
+        }
+    }
+
+    fn put_vectors_ids(&self, txn: &mut RwTxn, ids: &RoaringBitmap) -> Result<()> {
+        let mut bytes = Vec::with_capacity(ids.serialized_size());
+        ids.serialize_into(&mut bytes)?;
+        self.main
+            .put::<_, Str, ByteSlice>(txn, VECTORS_IDS_KEY, &bytes)?;
+        Ok(())
+    }
+
+    pub fn vector(&self, txn: &RoTxn, id: DocumentId) -> Result<Option<Vec<f32>>> {
+        let vector = self
+            .main
+            .get::<_, Str, ByteSlice>(txn, &vector_key(id))?
+            .map(|bytes| {
+                bytes
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect()
+            });
+        Ok(vector)
+    }
+
+    /// Returns a store of the vectors of the index whose ids are loaded once, and written back
+    /// by [`VectorStore::finish`].
+    fn vector_store<'t, 'e, 'p>(
+        &'t self,
+        txn: &'t mut RwTxn<'e, 'p>,
+    ) -> Result<VectorStore<'t, &'t mut RwTxn<'e, 'p>>> {
+        let ids = self.vectors_ids(txn)?;
+        Ok(VectorStore {
+            index: self,
+            txn,
+            ids,
+        })
+    }
+
+    /// Deletes the vectors of the documents `ids`.
+    pub fn delete_vectors(&self, txn: &mut RwTxn, ids: &RoaringBitmap) -> Result<()> {
+        let mut store = self.vector_store(txn)?;
+        for id in ids {
+            hnsw::remove(&mut store, id)?;
+        }
+        store.finish()
+    }
+
+    pub fn clear_vectors(&self, txn: &mut RwTxn) -> Result<()> {
+        for id in self.vectors_ids(txn)? {
+            self.main.delete::<_, Str>(txn, &vector_key(id))?;
+            self.main.delete::<_, Str>(txn, &node_key(id))?;
+        }
+        self.main.delete::<_, Str>(txn, VECTORS_IDS_KEY)?;
+        self.main.delete::<_, Str>(txn, VECTORS_META_KEY)?;
+        Ok(())
+    }
+
+    /// Applies the changes of the vectors of the documents of an addition, identified by their
+    /// external ids. A stored vector replaces the previous one of its document, and all the
+    /// vectors of an index must have the same number of dimensions.
+    pub fn apply_vector_changes(
+        &self,
+        txn: &mut RwTxn,
+        changes: Vec<(String, VectorChange)>,
+    ) -> Result<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let changes: Vec<_> = {
+            let external_ids = self.external_documents_ids(txn)?;
+            changes
+                .into_iter()
+                .filter_map(|(external_id, change)| Some((external_ids.get(&external_id)?, change)))
+                .collect()
+        };
+
+        let mut dimensions = self.vectors_meta(txn)?.dimensions;
+        let mut store = self.vector_store(txn)?;
+        for (id, change) in changes {
+            match change {
+                VectorChange::Put(vector) => {
+                    match dimensions {
+                        Some(expected) if expected != vector.len() => {
+                            return Err(VectorError::DimensionMismatch {
+                                expected,
+                                found: vector.len(),
+                            }
+                            .into())
+                        }
+                        Some(_) => (),
+                        None => {
+                            let mut meta = store.index.vectors_meta(store.txn)?;
+                            meta.dimensions = Some(vector.len());
+                            store.index.put_vectors_meta(store.txn, &meta)?;
+                            dimensions = meta.dimensions;
+                        }
+                    }
+                    hnsw::insert(&mut store, id, &vector)?;
+                }
+                VectorChange::Delete => hnsw::remove(&mut store, id)?,
+            }
+        }
+        store.finish()
+    }
+
+    /// Returns the `k` documents among `candidates` whose vectors are the most similar to
+    /// `query`, with their similarity, from 0 for opposite vectors to 1 for vectors pointing in
+    /// the same direction.
+    pub fn nearest_documents(
+        &self,
+        txn: &RoTxn,
+        query: &[f32],
+        k: usize,
+        candidates: &RoaringBitmap,
+    ) -> Result<Vec<(DocumentId, f32)>> {
+        let meta = self.vectors_meta(txn)?;
+        match meta.dimensions {
+            Some(expected) if expected != query.len() => {
+                return Err(VectorError::DimensionMismatch {
+                    expected,
+                    found: query.len(),
+                }
+                .into())
+            }
+            Some(_) => (),
+            None => return Ok(Vec::new()),
+        }
+
+        let ids = self.vectors_ids(txn)?;
+        let candidates = candidates & &ids;
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let similarity = |distance: f32| 1.0 - distance / 2.0;
+
+        if candidates.len() <= EXACT_SEARCH_THRESHOLD {
+            let mut neighbours = Vec::new();
+            for id in candidates {
+                if let Some(vector) = self.vector(txn, id)? {
+                    neighbours.push(hnsw::Neighbour {
+                        distance: hnsw::distance(query, &vector),
+                        id,
+                    });
+                }
+            }
+            neighbours.sort();
+            return Ok(neighbours
+                .into_iter()
+                .take(k)
+                .map(|n| (n.id, similarity(n.distance)))
+                .collect());
+        }
+
+        // The graph is searched for more neighbours than needed when the candidates are only a
+        // part of the documents, the ones that are not candidates being discarded.
+        let selectivity = ids.len() as f64 / candidates.len() as f64;
+        let wanted = ((k as f64 * selectivity).ceil() as usize).min(ids.len() as usize);
+        let store = VectorStore {
+            index: self,
+            txn,
+            ids,
+        };
+        let neighbours = hnsw::search(&store, query, wanted, EF_SEARCH)?;
+
+        Ok(neighbours
+            .into_iter()
+            .filter(|n| candidates.contains(n.id))
+            .take(k)
+            .map(|n| (n.id, similarity(n.distance)))
+            .collect())
+    }
+}
+
+/// The vectors of an index and their graph, stored in the main database of the index.
+struct VectorStore<'a, T> {
+    index: &'a Index,
+    txn: T,
+    /// The ids of the documents having a vector, kept in memory while the graph is updated.
+    ids: RoaringBitmap,
+}
+
+macro_rules! impl_graph_store {
+    ($txn:ty) => {
+        impl GraphStore for VectorStore<'_, $txn> {
+            fn vector(&self, id: DocumentId) -> Result<Option<Vec<f32>>> {
+                self.index.vector(&self.txn, id)
+            }
+
+            fn node(&self, id: DocumentId) -> Result<Option<Node>> {
+                let node = self
+                    .index
+                    .main
+                    .get::<_, Str, SerdeJson<Node>>(&self.txn, &node_key(id))?;
+                Ok(node)
+            }
+
+            fn entry_point(&self) -> Result<Option<EntryPoint>> {
+                Ok(self.index.vectors_meta(&self.txn)?.entry_point)
+            }
+
+            fn ids(&self) -> Result<RoaringBitmap> {
+                Ok(self.ids.clone())
+            }
+        }
+    };
+}
+
+impl_graph_store!(&RoTxn<'_>);
+impl_graph_store!(&mut RwTxn<'_, '_>);
+
+impl VectorStore<'_, &mut RwTxn<'_, '_>> {
+    /// Writes back the ids of the documents having a vector. The next vector stored decides the
+    /// number of dimensions of an index left without vectors.
+    fn finish(self) -> Result<()> {
+        if self.ids.is_empty() {
+            self.index
+                .put_vectors_meta(self.txn, &VectorsMeta::default())?;
+        }
+        self.index.put_vectors_ids(self.txn, &self.ids)
+    }
+}
+
+impl GraphStoreMut for VectorStore<'_, &mut RwTxn<'_, '_>> {
+    fn put_vector(&mut self, id: DocumentId, vector: &[f32]) -> Result<()> {
+        let bytes: Vec<u8> = vector.iter().flat_map(|x| x.to_le_bytes()).collect();
+        self.index
+            .main
+            .put::<_, Str, ByteSlice>(self.txn, &vector_key(id), &bytes)?;
+
+        self.ids.insert(id);
+        Ok(())
+    }
+
+    fn put_node(&mut self, id: DocumentId, node: &Node) -> Result<()> {
+        self.index
+            .main
+            .put::<_, Str, SerdeJson<Node>>(self.txn, &node_key(id), node)?;
+        Ok(())
+    }
+
+    fn delete(&mut self, id: DocumentId) -> Result<()> {
+        self.index
+            .main
+            .delete::<_, Str>(self.txn, &vector_key(id))?;
+        self.index.main.delete::<_, Str>(self.txn, &node_key(id))?;
+
+        self.ids.remove(id);
+        Ok(())
+    }
+
+    fn set_entry_point(&mut self, entry_point: Option<EntryPoint>) -> Result<()> {
+        let mut meta = self.index.vectors_meta(self.txn)?;
+        meta.entry_point = entry_point;
+        self.index.put_vectors_meta(self.txn, &meta)
+    }
+}
+
+/// Returns the primary key of the documents of an addition: the one of the index or of the
+/// addition, or else the first field of the first document containing `id`, as milli infers it.
+fn infer_primary_key(document: &Map<String, Value>) -> Option<String> {
+    document
+        .keys()
+        .find(|key| key.to_lowercase().contains("id"))
+        .cloned()
+}
+
+fn external_id(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) if n.is_i64() || n.is_u64() => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn parse_vector(value: Value, document_id: &str) -> Result<VectorChange> {
+    let invalid = || VectorError::InvalidDocumentVector(document_id.to_string());
+    match value {
+        Value::Null => Ok(VectorChange::Delete),
+        Value::Array(values) => {
+            let vector = values
+                .iter()
+                .map(|v| v.as_f64().map(|x| x as f32))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(invalid)?;
+            if vector.is_empty() {
+                return Err(invalid().into());
+            }
+            Ok(VectorChange::Put(vector))
+        }
+        _ => Err(invalid().into()),
+    }
+}
+
+/// Parses the documents of an addition in the given `format`, one at a time.
+fn parse_documents<'a>(
+    format: UpdateFormat,
+    csv_delimiter: u8,
+    content: impl io::Read + 'a,
+) -> Result<Box<dyn Iterator<Item = Result<Map<String, Value>>> + 'a>> {
+    let documents: Box<dyn Iterator<Item = Result<Map<String, Value>>> + 'a> = match format {
+        UpdateFormat::Json => Box::new(JsonArrayDocuments::new(content)),
+        UpdateFormat::JsonStream => Box::new(
+            serde_json::Deserializer::from_reader(content)
+                .into_iter()
+                .map(|document| document.map_err(Into::into)),
+        ),
+        UpdateFormat::Csv => Box::new(CsvDocuments::new(content, csv_delimiter)?),
+    };
+    Ok(documents)
+}
+
+/// Parses the documents of an addition, applies `convert` to each of them, and writes them as a
+/// JSON stream without their `_vectors` field. `convert` is given the id of the document, if it
+/// has a valid one, and returns whether the document is kept in the addition. An error of
+/// `convert` rejects the addition. Returns the changes of the vectors of the kept documents: a
+/// replaced document without `_vectors` loses its vector, while an updated one keeps it, unless
+/// its `_vectors` is `null`.
+///
+/// The JSON documents are first read without being converted. When none of them has a
+/// `_vectors` field nor `needs_convert`, the content is given back as is instead of being
+/// rewritten.
+///
+/// CSV documents are typed from the headers of their columns, see [`CsvDocuments`].
+pub fn extract_vectors<'a, R: io::Read + io::Seek + 'a>(
+    format: UpdateFormat,
+    csv_delimiter: u8,
+    mut content: R,
+    method: IndexDocumentsMethod,
+    primary_key: Option<&str>,
+    needs_convert: impl Fn(&Map<String, Value>) -> bool,
+    mut convert: impl FnMut(&mut Map<String, Value>, Option<&str>) -> Result<bool>,
+) -> Result<(
+    UpdateFormat,
+    Box<dyn io::Read + 'a>,
+    Vec<(String, VectorChange)>,
+)> {
+    let mut primary_key = primary_key.map(String::from);
+    let mut document_id = |document: &Map<String, Value>| {
+        if primary_key.is_none() {
+            primary_key = infer_primary_key(document);
+        }
+        primary_key
+            .as_ref()
+            .and_then(|key| document.get(key))
+            .and_then(external_id)
+    };
+
+    if format != UpdateFormat::Csv {
+        let mut changes = Vec::new();
+        let mut converted = false;
+        for document in parse_documents(format, csv_delimiter, &mut content)? {
+            let document = document?;
+            if document.contains_key(VECTORS_FIELD) || needs_convert(&document) {
+                converted = true;
+                break;
+            }
+            if method == IndexDocumentsMethod::ReplaceDocuments {
+                if let Some(id) = document_id(&document) {
+                    changes.push((id, VectorChange::Delete));
+                }
+            }
+        }
+
+        content.seek(SeekFrom::Start(0))?;
+        if !converted {
+            changes.sort_by(|(a, _), (b, _)| a.cmp(b));
+            changes.dedup_by(|(a, _), (b, _)| a == b);
+            return Ok((format, Box::new(content), changes));
+        }
+    }
+
+    // A document appearing several times in an addition ends up with its last vector.
+    let mut changes = HashMap::new();
+    let mut file = tempfile::tempfile()?;
+
+    for document in parse_documents(format, csv_delimiter, content)? {
+        let mut document = document?;
+        let vector = document.remove(VECTORS_FIELD);
+        let id = document_id(&document);
+
+        if !convert(&mut document, id.as_deref())? {
+            continue;
+        }
+
+        // The documents without a valid id are rejected by milli.
+        if let Some(id) = id {
+            match vector {
+                Some(vector) => {
+                    let change = parse_vector(vector, &id)?;
+                    changes.insert(id, change);
+                }
+                None if method == IndexDocumentsMethod::ReplaceDocuments => {
+                    changes.insert(id, VectorChange::Delete);
+                }
+                None => (),
+            }
+        }
+
+        serde_json::to_writer(&mut file, &document)?;
+        file.write_all(b"\n")?;
+    }
+    file.seek(SeekFrom::Start(0))?;
+
+    Ok((
+        UpdateFormat::JsonStream,
+        Box::new(file),
+        changes.into_iter().collect(),
+    ))
+}
+
+/// Mixes the keyword search with a vector search.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Hybrid {
+    /// The weight of the vector search in the ranking, from 0 to 1.
+    #[serde(default = "default_semantic_ratio")]
+    pub semantic_ratio: f32,
+}
+
+const fn default_semantic_ratio() -> f32 {
+    0.5
+}
+
+/// Returns the vector of a search and the weight of the vector search in its ranking, when it
+/// is semantic. A search with a `vector` but no `hybrid` is purely semantic.
+pub fn semantic_search(query: &SearchQuery) -> Result<Option<(&[f32], f32)>> {
+    let vector = match (&query.vector, &query.hybrid) {
+        (None, None) => return Ok(None),
+        (None, Some(_)) => return Err(VectorError::MissingVector.into()),
+        (Some(vector), _) => vector,
+    };
+
+    let semantic_ratio = query.hybrid.as_ref().map_or(1.0, |h| h.semantic_ratio);
+    if !(0.0..=1.0).contains(&semantic_ratio) {
+        return Err(VectorError::InvalidSemanticRatio(semantic_ratio).into());
+    }
+    if query.group_by.is_some() {
+        return Err(VectorError::GroupBy.into());
+    }
+
+    Ok(Some((vector, semantic_ratio)))
+}
+
+/// Merges the documents ranked by the keyword search with the documents found by the vector
+/// search. The keyword score of a document decreases linearly with its rank, from 1 for the
+/// first document, and its semantic score is its similarity to the query vector. The documents
+/// are sorted by the mean of both, weighted by `semantic_ratio`. The documents scoring 0, found
+/// by only one of the searches while the other has all the weight, are dropped.
+pub fn hybrid_rank(
+    keyword: &[DocumentId],
+    semantic: &[(DocumentId, f32)],
+    semantic_ratio: f32,
+) -> Vec<DocumentId> {
+    let mut scores: HashMap<DocumentId, f32> = HashMap::new();
+
+    let count = keyword.len() as f32;
+    for (rank, id) in keyword.iter().enumerate() {
+        let score = 1.0 - rank as f32 / count;
+        *scores.entry(*id).or_default() += (1.0 - semantic_ratio) * score;
+    }
+
+    for (id, similarity) in semantic {
+        *scores.entry(*id).or_default() += semantic_ratio * similarity;
+    }
+
+    let mut ranked: Vec<_> = scores.into_iter().filter(|(_, s)| *s > 0.0).collect();
+    ranked.sort_by(|(a_id, a), (b_id, b)| {
+        b.partial_cmp(a)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a_id.cmp(b_id))
+    });
+    ranked.into_iter().map(|(id, _)| id).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn read_stream(mut content: Box<dyn io::Read>) -> Vec<Value> {
+        use std::io::Read;
+
+        let mut s = String::new();
+        content.read_to_string(&mut s).unwrap();
+        s.lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn extract_documents_vectors() {
+        let documents = json!([
+            { "id": 1, "title": "red shirt", "_vectors": [0.1, 0.2] },
+            { "id": "b", "title": "blue shirt", "_vectors": null },
+            { "id": 3, "title": "green shirt" },
+        ]);
+        let content = io::Cursor::new(serde_json::to_vec(&documents).unwrap());
+
+        let (format, content, mut changes) = extract_vectors(
+            UpdateFormat::Json,
+            b',',
+            content,
+            IndexDocumentsMethod::ReplaceDocuments,
+            None,
+            |_| false,
+            |_, _| Ok(true),
+        )
+        .unwrap();
+        changes.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        assert_eq!(format, UpdateFormat::JsonStream);
+        assert_eq!(
+            read_stream(content),
+            vec![
+                json!({ "id": 1, "title": "red shirt" }),
+                json!({ "id": "b", "title": "blue shirt" }),
+                json!({ "id": 3, "title": "green shirt" }),
+            ]
+        );
+        assert_eq!(
+            changes,
+            vec![
+                ("1".to_string(), VectorChange::Put(vec![0.1, 0.2])),
+                ("3".to_string(), VectorChange::Delete),
+                ("b".to_string(), VectorChange::Delete),
+            ]
+        );
+
+        // The documents updated without `_vectors` keep their vector.
+        let content = io::Cursor::new(br#"{ "id": 3, "title": "green shirt" }"#.to_vec());
+        let (_, _, changes) = extract_vectors(
+            UpdateFormat::JsonStream,
+            b',',
+            content,
+            IndexDocumentsMethod::UpdateDocuments,
+            Some("id"),
+            |_| false,
+            |_, _| Ok(true),
+        )
+        .unwrap();
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn keep_unconverted_content() {
+        let documents = json!([
+            { "id": 1, "title": "red shirt" },
+            { "id": 2, "title": "blue shirt" },
+        ]);
+        let bytes = serde_json::to_vec(&documents).unwrap();
+
+        let (format, mut content, changes) = extract_vectors(
+            UpdateFormat::Json,
+            b',',
+            io::Cursor::new(bytes.clone()),
+            IndexDocumentsMethod::ReplaceDocuments,
+            None,
+            |_| false,
+            |_, _| panic!("no document needs to be converted"),
+        )
+        .unwrap();
+
+        let mut kept = Vec::new();
+        content.read_to_end(&mut kept).unwrap();
+        assert_eq!(format, UpdateFormat::Json);
+        assert_eq!(kept, bytes);
+        assert_eq!(
+            changes,
+            vec![
+                ("1".to_string(), VectorChange::Delete),
+                ("2".to_string(), VectorChange::Delete),
+            ]
+        );
+
+        let (format, content, _) = extract_vectors(
+            UpdateFormat::Json,
+            b',',
+            io::Cursor::new(bytes),
+            IndexDocumentsMethod::ReplaceDocuments,
+            None,
+            |document| document["id"] == 2,
+            |_, _| Ok(true),
+        )
+        .unwrap();
+        assert_eq!(format, UpdateFormat::JsonStream);
+        assert_eq!(read_stream(content).len(), 2);
+    }
+
+    #[test]
+    fn skip_documents() {
+        let documents = json!([
+            { "id": 1, "title": "red shirt", "_vectors": [0.1, 0.2] },
+            { "id": 2, "title": "blue shirt", "_vectors": [0.3, 0.4] },
+        ]);
+        let content = io::Cursor::new(serde_json::to_vec(&documents).unwrap());
+
+        let (_, content, changes) = extract_vectors(
+            UpdateFormat::Json,
+            b',',
+            content,
+            IndexDocumentsMethod::ReplaceDocuments,
+            None,
+            |_| false,
+            |_, id| Ok(id != Some("2")),
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_stream(content),
+            vec![json!({ "id": 1, "title": "red shirt" })]
+        );
+        assert_eq!(
+            changes,
+            vec![("1".to_string(), VectorChange::Put(vec![0.1, 0.2]))]
+        );
+    }
+
+    #[test]
+    fn reject_invalid_vectors() {
+        for vector in &[json!("shirt"), json!([]), json!([1.0, "2"])] {
+            let documents = json!([{ "id": 1, "_vectors": vector }]);
+            let content = io::Cursor::new(serde_json::to_vec(&documents).unwrap());
+            let result = extract_vectors(
+                UpdateFormat::Json,
+                b',',
+                content,
+                IndexDocumentsMethod::ReplaceDocuments,
+                None,
+                |_| false,
+                |_, _| Ok(true),
+            );
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn merge_keyword_and_semantic_ranks() {
+        let keyword = [1, 2, 3, 4];
+        let semantic = [(4, 1.0), (5, 0.9), (1, 0.1)];
+
+        assert_eq!(hybrid_rank(&keyword, &semantic, 0.0), vec![1, 2, 3, 4]);
+        assert_eq!(hybrid_rank(&keyword, &semantic, 1.0), vec![4, 5, 1]);
+        assert_eq!(hybrid_rank(&keyword, &semantic, 0.5), vec![4, 1, 5, 2, 3]);
+    }
+}
@@ -90,7 +90,7 @@ fn load_index(
     let mut options = EnvOpenOptions::new();
     options.map_size(size);
     let index = milli::Index::new(options, index_path)?;
-    let index = Index(Arc::new(index));
+    let index = Index::new(Arc::new(index));
 
     // extract `settings.json` file and import content
     let settings = import_settings(&src)?;
@@ -109,6 +109,7 @@ fn load_index(
         index.update_documents_txn(
             &mut txn,
             UpdateFormat::JsonStream,
+            None,
             IndexDocumentsMethod::ReplaceDocuments,
             Some(reader),
             handler.update_builder(0),
@@ -166,6 +167,11 @@ impl From<Settings> for index_controller::Settings<Unchecked> {
             stop_words: settings.stop_words.map(|o| o.map(|vec| vec.into_iter().collect())),
             // we need to convert the old `Vec<String>` into a `BTreeMap<String>`
             synonyms: settings.synonyms.map(|o| o.map(|vec| vec.into_iter().collect())),
+            hierarchical_facets: None,
+            date_attributes: None,
+            search_defaults: None,
+            pagination: None,
+            document_schema: None,
             _kind: PhantomData,
         }
     }
@@ -1,18 +1,23 @@
 use std::fs::File;
+use std::io::{self, BufWriter, Write};
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use actix_web::web::Bytes;
 use async_stream::stream;
 use futures::stream::StreamExt;
 use heed::CompactionOption;
 use log::debug;
 use milli::update::UpdateBuilder;
+use serde_json::Value;
 use tokio::task::spawn_blocking;
 use tokio::{fs, sync::mpsc};
 use uuid::Uuid;
 
 use crate::index::{
-    update_handler::UpdateHandler, Checked, Document, SearchQuery, SearchResult, Settings,
+    update_handler::UpdateHandler, BrowseDocumentsQuery, BrowseDocumentsResult, Checked, Document,
+    ExportFormat, FetchDocumentsQuery, FetchDocumentsResult, Rules, SearchExplanation, SearchQuery,
+    SearchResult, Settings,
 };
 use crate::index_controller::{
     get_arc_ownership_blocking, Failed, IndexStats, Processed, Processing,
@@ -20,10 +25,15 @@ use crate::index_controller::{
 use crate::option::IndexerOpts;
 
 use super::error::{IndexActorError, Result};
-use super::{IndexMeta, IndexMsg, IndexSettings, IndexStore};
+use super::{DocumentsExport, IndexMeta, IndexMsg, IndexSettings, IndexStore};
 
 pub const CONCURRENT_INDEX_MSG: usize = 10;
 
+/// The size of the chunks an export of documents is sent in.
+const EXPORT_CHUNK_SIZE: usize = 64 * 1024;
+/// The number of chunks of an export buffered before the export waits for the client.
+const EXPORT_BUFFERED_CHUNKS: usize = 4;
+
 pub struct IndexActor<S> {
     receiver: Option<mpsc::Receiver<IndexMsg>>,
     update_handler: Arc<UpdateHandler>,
@@ -87,20 +97,25 @@ impl<S: IndexStore + Sync + Send> IndexActor<S> {
             Search { ret, query, uuid } => {
                 let _ = ret.send(self.handle_search(uuid, query).await);
             }
+            Explain { ret, query, uuid } => {
+                let _ = ret.send(self.handle_explain_search(uuid, query).await);
+            }
             Settings { ret, uuid } => {
                 let _ = ret.send(self.handle_settings(uuid).await);
             }
-            Documents {
-                ret,
+            Documents { ret, uuid, query } => {
+                let _ = ret.send(self.handle_fetch_documents(uuid, query).await);
+            }
+            FetchDocuments { uuid, query, ret } => {
+                let _ = ret.send(self.handle_fetch_documents_by(uuid, query).await);
+            }
+            Export {
                 uuid,
-                attributes_to_retrieve,
-                offset,
-                limit,
+                filter,
+                format,
+                ret,
             } => {
-                let _ = ret.send(
-                    self.handle_fetch_documents(uuid, offset, limit, attributes_to_retrieve)
-                        .await,
-                );
+                let _ = ret.send(self.handle_export_documents(uuid, filter, format).await);
             }
             Document {
                 uuid,
@@ -135,6 +150,9 @@ impl<S: IndexStore + Sync + Send> IndexActor<S> {
             GetStats { uuid, ret } => {
                 let _ = ret.send(self.handle_get_stats(uuid).await);
             }
+            GetRules { uuid, ret } => {
+                let _ = ret.send(self.handle_get_rules(uuid).await);
+            }
         }
     }
 
@@ -148,6 +166,20 @@ impl<S: IndexStore + Sync + Send> IndexActor<S> {
         Ok(result)
     }
 
+    async fn handle_explain_search(
+        &self,
+        uuid: Uuid,
+        query: SearchQuery,
+    ) -> Result<SearchExplanation> {
+        let index = self
+            .store
+            .get(uuid)
+            .await?
+            .ok_or(IndexActorError::UnexistingIndex)?;
+        let explanation = spawn_blocking(move || index.explain_search(query)).await??;
+        Ok(explanation)
+    }
+
     async fn handle_create_index(
         &self,
         uuid: Uuid,
@@ -187,22 +219,57 @@ impl<S: IndexStore + Sync + Send> IndexActor<S> {
     async fn handle_fetch_documents(
         &self,
         uuid: Uuid,
-        offset: usize,
-        limit: usize,
-        attributes_to_retrieve: Option<Vec<String>>,
-    ) -> Result<Vec<Document>> {
+        query: BrowseDocumentsQuery,
+    ) -> Result<BrowseDocumentsResult> {
         let index = self
             .store
             .get(uuid)
             .await?
             .ok_or(IndexActorError::UnexistingIndex)?;
-        let result =
-            spawn_blocking(move || index.retrieve_documents(offset, limit, attributes_to_retrieve))
-                .await??;
+        let result = spawn_blocking(move || index.browse_documents(query)).await??;
+
+        Ok(result)
+    }
+
+    async fn handle_fetch_documents_by(
+        &self,
+        uuid: Uuid,
+        query: FetchDocumentsQuery,
+    ) -> Result<FetchDocumentsResult> {
+        let index = self
+            .store
+            .get(uuid)
+            .await?
+            .ok_or(IndexActorError::UnexistingIndex)?;
+        let result = spawn_blocking(move || index.fetch_documents(query)).await??;
 
         Ok(result)
     }
 
+    async fn handle_export_documents(
+        &self,
+        uuid: Uuid,
+        filter: Option<Value>,
+        format: ExportFormat,
+    ) -> Result<DocumentsExport> {
+        let index = self
+            .store
+            .get(uuid)
+            .await?
+            .ok_or(IndexActorError::UnexistingIndex)?;
+
+        let (sender, receiver) = mpsc::channel(EXPORT_BUFFERED_CHUNKS);
+        spawn_blocking(move || {
+            let writer = BufWriter::with_capacity(EXPORT_CHUNK_SIZE, ChannelWriter(sender.clone()));
+            if let Err(e) = index.export_documents(filter.as_ref(), format, writer) {
+                // Nobody is left to receive the error when the client went away.
+                let _ = sender.blocking_send(Err(e.into()));
+            }
+        });
+
+        Ok(receiver)
+    }
+
     async fn handle_fetch_document(
         &self,
         uuid: Uuid,
@@ -345,4 +412,35 @@ impl<S: IndexStore + Sync + Send> IndexActor<S> {
         })
         .await?
     }
+
+    async fn handle_get_rules(&self, uuid: Uuid) -> Result<Rules> {
+        let index = self
+            .store
+            .get(uuid)
+            .await?
+            .ok_or(IndexActorError::UnexistingIndex)?;
+
+        spawn_blocking(move || {
+            let rtxn = index.read_txn()?;
+            Ok(index.rules(&rtxn)?)
+        })
+        .await?
+    }
+}
+
+/// Sends the chunks written to it to an export, blocking while the client is behind. The
+/// writes fail once the client went away, which ends the export.
+struct ChannelWriter(mpsc::Sender<Result<Bytes>>);
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the export was dropped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
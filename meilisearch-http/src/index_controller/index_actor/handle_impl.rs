@@ -1,5 +1,6 @@
 use std::path::{Path, PathBuf};
 
+use serde_json::Value;
 use tokio::sync::{mpsc, oneshot};
 use uuid::Uuid;
 
@@ -8,12 +9,15 @@ use crate::{
     index_controller::{IndexSettings, IndexStats, Processing},
 };
 use crate::{
-    index::{Document, SearchQuery, SearchResult, Settings},
+    index::{
+        BrowseDocumentsQuery, BrowseDocumentsResult, Document, ExportFormat, FetchDocumentsQuery,
+        FetchDocumentsResult, Rules, SearchExplanation, SearchQuery, SearchResult, Settings,
+    },
     index_controller::{Failed, Processed},
 };
 
 use super::error::Result;
-use super::{IndexActor, IndexActorHandle, IndexMeta, IndexMsg, MapIndexStore};
+use super::{DocumentsExport, IndexActor, IndexActorHandle, IndexMeta, IndexMsg, MapIndexStore};
 
 #[derive(Clone)]
 pub struct IndexActorHandleImpl {
@@ -57,6 +61,13 @@ impl IndexActorHandle for IndexActorHandleImpl {
         Ok(receiver.await.expect("IndexActor has been killed")?)
     }
 
+    async fn explain_search(&self, uuid: Uuid, query: SearchQuery) -> Result<SearchExplanation> {
+        let (ret, receiver) = oneshot::channel();
+        let msg = IndexMsg::Explain { uuid, query, ret };
+        let _ = self.sender.send(msg).await;
+        Ok(receiver.await.expect("IndexActor has been killed")?)
+    }
+
     async fn settings(&self, uuid: Uuid) -> Result<Settings<Checked>> {
         let (ret, receiver) = oneshot::channel();
         let msg = IndexMsg::Settings { uuid, ret };
@@ -67,17 +78,37 @@ impl IndexActorHandle for IndexActorHandleImpl {
     async fn documents(
         &self,
         uuid: Uuid,
-        offset: usize,
-        limit: usize,
-        attributes_to_retrieve: Option<Vec<String>>,
-    ) -> Result<Vec<Document>> {
+        query: BrowseDocumentsQuery,
+    ) -> Result<BrowseDocumentsResult> {
+        let (ret, receiver) = oneshot::channel();
+        let msg = IndexMsg::Documents { uuid, query, ret };
+        let _ = self.sender.send(msg).await;
+        Ok(receiver.await.expect("IndexActor has been killed")?)
+    }
+
+    async fn fetch_documents(
+        &self,
+        uuid: Uuid,
+        query: FetchDocumentsQuery,
+    ) -> Result<FetchDocumentsResult> {
         let (ret, receiver) = oneshot::channel();
-        let msg = IndexMsg::Documents {
+        let msg = IndexMsg::FetchDocuments { uuid, query, ret };
+        let _ = self.sender.send(msg).await;
+        Ok(receiver.await.expect("IndexActor has been killed")?)
+    }
+
+    async fn export_documents(
+        &self,
+        uuid: Uuid,
+        filter: Option<Value>,
+        format: ExportFormat,
+    ) -> Result<DocumentsExport> {
+        let (ret, receiver) = oneshot::channel();
+        let msg = IndexMsg::Export {
             uuid,
+            filter,
+            format,
             ret,
-            offset,
-            attributes_to_retrieve,
-            limit,
         };
         let _ = self.sender.send(msg).await;
         Ok(receiver.await.expect("IndexActor has been killed")?)
@@ -145,6 +176,13 @@ impl IndexActorHandle for IndexActorHandleImpl {
         let _ = self.sender.send(msg).await;
         Ok(receiver.await.expect("IndexActor has been killed")?)
     }
+
+    async fn rules(&self, uuid: Uuid) -> Result<Rules> {
+        let (ret, receiver) = oneshot::channel();
+        let msg = IndexMsg::GetRules { uuid, ret };
+        let _ = self.sender.send(msg).await;
+        Ok(receiver.await.expect("IndexActor has been killed")?)
+    }
 }
 
 impl IndexActorHandleImpl {
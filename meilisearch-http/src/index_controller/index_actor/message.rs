@@ -1,13 +1,18 @@
 use std::path::PathBuf;
 
+use serde_json::Value;
 use tokio::sync::oneshot;
 use uuid::Uuid;
 
 use super::error::Result as IndexResult;
-use crate::index::{Checked, Document, SearchQuery, SearchResult, Settings};
+use crate::index::{
+    BrowseDocumentsQuery, BrowseDocumentsResult, Checked, Document, ExportFormat,
+    FetchDocumentsQuery, FetchDocumentsResult, Rules, SearchExplanation, SearchQuery, SearchResult,
+    Settings,
+};
 use crate::index_controller::{Failed, IndexStats, Processed, Processing};
 
-use super::{IndexMeta, IndexSettings};
+use super::{DocumentsExport, IndexMeta, IndexSettings};
 
 #[allow(clippy::large_enum_variant)]
 pub enum IndexMsg {
@@ -27,16 +32,30 @@ pub enum IndexMsg {
         query: SearchQuery,
         ret: oneshot::Sender<IndexResult<SearchResult>>,
     },
+    Explain {
+        uuid: Uuid,
+        query: SearchQuery,
+        ret: oneshot::Sender<IndexResult<SearchExplanation>>,
+    },
     Settings {
         uuid: Uuid,
         ret: oneshot::Sender<IndexResult<Settings<Checked>>>,
     },
     Documents {
         uuid: Uuid,
-        attributes_to_retrieve: Option<Vec<String>>,
-        offset: usize,
-        limit: usize,
-        ret: oneshot::Sender<IndexResult<Vec<Document>>>,
+        query: BrowseDocumentsQuery,
+        ret: oneshot::Sender<IndexResult<BrowseDocumentsResult>>,
+    },
+    FetchDocuments {
+        uuid: Uuid,
+        query: FetchDocumentsQuery,
+        ret: oneshot::Sender<IndexResult<FetchDocumentsResult>>,
+    },
+    Export {
+        uuid: Uuid,
+        filter: Option<Value>,
+        format: ExportFormat,
+        ret: oneshot::Sender<IndexResult<DocumentsExport>>,
     },
     Document {
         uuid: Uuid,
@@ -71,4 +90,8 @@ pub enum IndexMsg {
         uuid: Uuid,
         ret: oneshot::Sender<IndexResult<IndexStats>>,
     },
+    GetRules {
+        uuid: Uuid,
+        ret: oneshot::Sender<IndexResult<Rules>>,
+    },
 }
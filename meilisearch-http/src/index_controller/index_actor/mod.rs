@@ -1,10 +1,13 @@
 use std::fs::File;
 use std::path::PathBuf;
 
+use actix_web::web::Bytes;
 use chrono::{DateTime, Utc};
 #[cfg(test)]
 use mockall::automock;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
 use actor::IndexActor;
@@ -13,7 +16,11 @@ pub use handle_impl::IndexActorHandleImpl;
 use message::IndexMsg;
 use store::{IndexStore, MapIndexStore};
 
-use crate::index::{Checked, Document, Index, SearchQuery, SearchResult, Settings};
+use crate::index::{
+    BrowseDocumentsQuery, BrowseDocumentsResult, Checked, Document, ExportFormat,
+    FetchDocumentsQuery, FetchDocumentsResult, Index, Rules, SearchExplanation, SearchQuery,
+    SearchResult, Settings,
+};
 use crate::index_controller::{Failed, IndexStats, Processed, Processing};
 use error::Result;
 
@@ -25,12 +32,20 @@ mod handle_impl;
 mod message;
 mod store;
 
+/// The chunks of an export of documents, an error ends the export early.
+pub type DocumentsExport = mpsc::Receiver<Result<Bytes>>;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IndexMeta {
     created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub primary_key: Option<String>,
+    /// The version of the search results: the last update of the index, or the last time one of
+    /// its rules started or stopped being applied. A deserialized meta has an unknown version,
+    /// taken as the current time.
+    #[serde(skip, default = "Utc::now")]
+    pub version: DateTime<Utc>,
 }
 
 impl IndexMeta {
@@ -43,10 +58,15 @@ impl IndexMeta {
         let created_at = index.created_at(&txn)?;
         let updated_at = index.updated_at(&txn)?;
         let primary_key = index.primary_key(&txn)?.map(String::from);
+        let version = match index.rules_changed_at(&txn, Utc::now())? {
+            Some(changed_at) => changed_at.max(updated_at),
+            None => updated_at,
+        };
         Ok(Self {
             created_at,
             updated_at,
             primary_key,
+            version,
         })
     }
 }
@@ -62,15 +82,25 @@ pub trait IndexActorHandle {
         data: Option<File>,
     ) -> Result<std::result::Result<Processed, Failed>>;
     async fn search(&self, uuid: Uuid, query: SearchQuery) -> Result<SearchResult>;
+    async fn explain_search(&self, uuid: Uuid, query: SearchQuery) -> Result<SearchExplanation>;
     async fn settings(&self, uuid: Uuid) -> Result<Settings<Checked>>;
 
     async fn documents(
         &self,
         uuid: Uuid,
-        offset: usize,
-        limit: usize,
-        attributes_to_retrieve: Option<Vec<String>>,
-    ) -> Result<Vec<Document>>;
+        query: BrowseDocumentsQuery,
+    ) -> Result<BrowseDocumentsResult>;
+    async fn fetch_documents(
+        &self,
+        uuid: Uuid,
+        query: FetchDocumentsQuery,
+    ) -> Result<FetchDocumentsResult>;
+    async fn export_documents(
+        &self,
+        uuid: Uuid,
+        filter: Option<Value>,
+        format: ExportFormat,
+    ) -> Result<DocumentsExport>;
     async fn document(
         &self,
         uuid: Uuid,
@@ -83,6 +113,7 @@ pub trait IndexActorHandle {
     async fn snapshot(&self, uuid: Uuid, path: PathBuf) -> Result<()>;
     async fn dump(&self, uuid: Uuid, path: PathBuf) -> Result<()>;
     async fn get_index_stats(&self, uuid: Uuid) -> Result<IndexStats>;
+    async fn rules(&self, uuid: Uuid) -> Result<Rules>;
 }
 
 #[cfg(test)]
@@ -111,6 +142,14 @@ mod test {
             self.as_ref().search(uuid, query).await
         }
 
+        async fn explain_search(
+            &self,
+            uuid: Uuid,
+            query: SearchQuery,
+        ) -> Result<SearchExplanation> {
+            self.as_ref().explain_search(uuid, query).await
+        }
+
         async fn settings(&self, uuid: Uuid) -> Result<Settings<Checked>> {
             self.as_ref().settings(uuid).await
         }
@@ -118,13 +157,26 @@ mod test {
         async fn documents(
             &self,
             uuid: Uuid,
-            offset: usize,
-            limit: usize,
-            attributes_to_retrieve: Option<Vec<String>>,
-        ) -> Result<Vec<Document>> {
-            self.as_ref()
-                .documents(uuid, offset, limit, attributes_to_retrieve)
-                .await
+            query: BrowseDocumentsQuery,
+        ) -> Result<BrowseDocumentsResult> {
+            self.as_ref().documents(uuid, query).await
+        }
+
+        async fn fetch_documents(
+            &self,
+            uuid: Uuid,
+            query: FetchDocumentsQuery,
+        ) -> Result<FetchDocumentsResult> {
+            self.as_ref().fetch_documents(uuid, query).await
+        }
+
+        async fn export_documents(
+            &self,
+            uuid: Uuid,
+            filter: Option<Value>,
+            format: ExportFormat,
+        ) -> Result<DocumentsExport> {
+            self.as_ref().export_documents(uuid, filter, format).await
         }
 
         async fn document(
@@ -165,5 +217,9 @@ mod test {
         async fn get_index_stats(&self, uuid: Uuid) -> Result<IndexStats> {
             self.as_ref().get_index_stats(uuid).await
         }
+
+        async fn rules(&self, uuid: Uuid) -> Result<Rules> {
+            self.as_ref().rules(uuid).await
+        }
     }
 }
@@ -10,20 +10,28 @@ use log::error;
 use log::info;
 use milli::FieldDistribution;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tokio::sync::mpsc;
 use tokio::time::sleep;
 use uuid::Uuid;
 
 use dump_actor::DumpActorHandle;
 pub use dump_actor::{DumpInfo, DumpStatus};
+pub use index_actor::DocumentsExport;
 use index_actor::IndexActorHandle;
 use snapshot::{load_snapshot, SnapshotService};
 use update_actor::UpdateActorHandle;
 pub use updates::*;
 use uuid_resolver::{error::UuidResolverError, UuidResolverHandle};
 
+use crate::extractors::documents_format::DocumentsFormat;
 use crate::extractors::payload::Payload;
-use crate::index::{Checked, Document, SearchQuery, SearchResult, Settings};
+use crate::index::error::{IndexError, RuleError};
+use crate::index::{
+    BrowseDocumentsQuery, BrowseDocumentsResult, Checked, Document, ExportFormat,
+    FetchDocumentsQuery, FetchDocumentsResult, Rule, Rules, SearchExplanation, SearchQuery,
+    SearchResult, Settings,
+};
 use crate::option::Opt;
 use error::Result;
 
@@ -115,6 +123,7 @@ impl IndexController {
             index_handle.clone(),
             &path,
             update_store_size,
+            options.http_payload_size_limit.get_bytes() as usize,
         )?;
         let dump_handle = dump_actor::DumpActorHandleImpl::new(
             &options.dumps_dir,
@@ -152,14 +161,16 @@ impl IndexController {
         &self,
         uid: String,
         method: milli::update::IndexDocumentsMethod,
-        format: milli::update::UpdateFormat,
+        format: DocumentsFormat,
         payload: Payload,
         primary_key: Option<String>,
     ) -> Result<UpdateStatus> {
         let perform_update = |uuid| async move {
             let meta = UpdateMeta::DocumentsAddition {
                 method,
-                format,
+                format: format.format,
+                csv_delimiter: format.csv_delimiter,
+                encoding: format.encoding,
                 primary_key,
             };
             let (sender, receiver) = mpsc::channel(10);
@@ -213,6 +224,18 @@ impl IndexController {
         Ok(status)
     }
 
+    pub async fn delete_documents_by_filter(
+        &self,
+        uid: String,
+        filter: serde_json::Value,
+    ) -> Result<UpdateStatus> {
+        let uuid = self.uuid_resolver.get(uid).await?;
+        let meta = UpdateMeta::DeleteDocumentsByFilter { filter };
+        let (_, receiver) = mpsc::channel(1);
+        let status = self.update_handle.update(meta, receiver, uuid).await?;
+        Ok(status)
+    }
+
     pub async fn update_settings(
         &self,
         uid: String,
@@ -281,6 +304,14 @@ impl IndexController {
         Ok(result)
     }
 
+    /// Waits, up to `timeout`, until the update `id` of the index is processed, to read the
+    /// index in a state that includes it.
+    pub async fn wait_update(&self, uid: String, id: u64, timeout: Duration) -> Result<()> {
+        let uuid = self.uuid_resolver.get(uid).await?;
+        self.update_handle.wait_update(uuid, id, timeout).await?;
+        Ok(())
+    }
+
     pub async fn all_update_status(&self, uid: String) -> Result<Vec<UpdateStatus>> {
         let uuid = self.uuid_resolver.get(uid).await?;
         let result = self.update_handle.get_all_updates_status(uuid).await?;
@@ -315,16 +346,35 @@ impl IndexController {
     pub async fn documents(
         &self,
         uid: String,
-        offset: usize,
-        limit: usize,
-        attributes_to_retrieve: Option<Vec<String>>,
-    ) -> Result<Vec<Document>> {
-        let uuid = self.uuid_resolver.get(uid.clone()).await?;
-        let documents = self
+        query: BrowseDocumentsQuery,
+    ) -> Result<BrowseDocumentsResult> {
+        let uuid = self.uuid_resolver.get(uid).await?;
+        let documents = self.index_handle.documents(uuid, query).await?;
+        Ok(documents)
+    }
+
+    pub async fn fetch_documents(
+        &self,
+        uid: String,
+        query: FetchDocumentsQuery,
+    ) -> Result<FetchDocumentsResult> {
+        let uuid = self.uuid_resolver.get(uid).await?;
+        let result = self.index_handle.fetch_documents(uuid, query).await?;
+        Ok(result)
+    }
+
+    pub async fn export_documents(
+        &self,
+        uid: String,
+        filter: Option<Value>,
+        format: ExportFormat,
+    ) -> Result<DocumentsExport> {
+        let uuid = self.uuid_resolver.get(uid).await?;
+        let export = self
             .index_handle
-            .documents(uuid, offset, limit, attributes_to_retrieve)
+            .export_documents(uuid, filter, format)
             .await?;
-        Ok(documents)
+        Ok(export)
     }
 
     pub async fn document(
@@ -367,6 +417,46 @@ impl IndexController {
         Ok(result)
     }
 
+    pub async fn explain_search(
+        &self,
+        uid: String,
+        query: SearchQuery,
+    ) -> Result<SearchExplanation> {
+        let uuid = self.uuid_resolver.get(uid).await?;
+        let explanation = self.index_handle.explain_search(uuid, query).await?;
+        Ok(explanation)
+    }
+
+    pub async fn rules(&self, uid: String) -> Result<Rules> {
+        let uuid = self.uuid_resolver.get(uid).await?;
+        let rules = self.index_handle.rules(uuid).await?;
+        Ok(rules)
+    }
+
+    pub async fn rule(&self, uid: String, id: String) -> Result<Rule> {
+        let mut rules = self.rules(uid).await?;
+        let rule = rules
+            .remove(&id)
+            .ok_or(IndexError::Rule(RuleError::NotFound(id)))?;
+        Ok(rule)
+    }
+
+    pub async fn put_rule(&self, uid: String, id: String, rule: Rule) -> Result<UpdateStatus> {
+        let uuid = self.uuid_resolver.get(uid).await?;
+        let meta = UpdateMeta::PutRule { id, rule };
+        let (_, receiver) = mpsc::channel(1);
+        let status = self.update_handle.update(meta, receiver, uuid).await?;
+        Ok(status)
+    }
+
+    pub async fn delete_rule(&self, uid: String, id: String) -> Result<UpdateStatus> {
+        let uuid = self.uuid_resolver.get(uid).await?;
+        let meta = UpdateMeta::DeleteRule { id };
+        let (_, receiver) = mpsc::channel(1);
+        let status = self.update_handle.update(meta, receiver, uuid).await?;
+        Ok(status)
+    }
+
     pub async fn get_index(&self, uid: String) -> Result<IndexMetadata> {
         let uuid = self.uuid_resolver.get(uid.clone()).await?;
         let meta = self.index_handle.get_index_meta(uuid).await?;
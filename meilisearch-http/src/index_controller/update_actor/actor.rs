@@ -4,9 +4,11 @@ use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
+use actix_web::error::PayloadError;
 use async_stream::stream;
 use futures::StreamExt;
 use log::trace;
+use milli::update::UpdateFormat;
 use oxidized_json_checker::JsonChecker;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
@@ -15,6 +17,8 @@ use uuid::Uuid;
 
 use super::error::{Result, UpdateActorError};
 use super::{PayloadData, UpdateMsg, UpdateStore, UpdateStoreInfo};
+use crate::index::error::IndexError;
+use crate::index::{decode, decoded_size, ContentEncoding, CsvDocuments, DEFAULT_CSV_DELIMITER};
 use crate::index_controller::index_actor::IndexActorHandle;
 use crate::index_controller::{UpdateMeta, UpdateStatus};
 
@@ -24,6 +28,8 @@ pub struct UpdateActor<D, I> {
     inbox: Option<mpsc::Receiver<UpdateMsg<D>>>,
     index_handle: I,
     must_exit: Arc<AtomicBool>,
+    /// The maximum size of the decompressed documents of an addition.
+    payload_limit: usize,
 }
 
 impl<D, I> UpdateActor<D, I>
@@ -33,6 +39,7 @@ where
 {
     pub fn new(
         update_db_size: usize,
+        payload_limit: usize,
         inbox: mpsc::Receiver<UpdateMsg<D>>,
         path: impl AsRef<Path>,
         index_handle: I,
@@ -55,6 +62,7 @@ where
             inbox,
             index_handle,
             must_exit,
+            payload_limit,
         })
     }
 
@@ -113,6 +121,9 @@ where
                     Dump { uuids, path, ret } => {
                         let _ = ret.send(self.handle_dump(uuids, path).await);
                     }
+                    Subscribe { ret } => {
+                        let _ = ret.send(self.store.subscribe());
+                    }
                 }
             })
             .await;
@@ -125,7 +136,12 @@ where
         payload: mpsc::Receiver<PayloadData<D>>,
     ) -> Result<UpdateStatus> {
         let file_path = match meta {
-            UpdateMeta::DocumentsAddition { .. } => {
+            UpdateMeta::DocumentsAddition {
+                format,
+                csv_delimiter,
+                encoding,
+                ..
+            } => {
                 let update_file_id = uuid::Uuid::new_v4();
                 let path = self
                     .path
@@ -162,7 +178,7 @@ where
                 match file_len {
                     Ok(len) if len > 0 => {
                         let file = file.into_std().await;
-                        Some((file, update_file_id))
+                        Some((file, update_file_id, format, csv_delimiter, encoding))
                     }
                     Err(e) => {
                         fs::remove_file(&path).await?;
@@ -178,28 +194,31 @@ where
         };
 
         let update_store = self.store.clone();
+        let payload_limit = self.payload_limit;
 
         tokio::task::spawn_blocking(move || {
-            use std::io::{copy, sink, BufReader, Seek};
+            use std::io::Seek;
 
             // If the payload is empty, ignore the check.
-            let update_uuid = if let Some((mut file, uuid)) = file_path {
-                // set the file back to the beginning
-                file.seek(SeekFrom::Start(0))?;
-                // Check that the json payload is valid:
-                let reader = BufReader::new(&mut file);
-                let mut checker = JsonChecker::new(reader);
-
-                if copy(&mut checker, &mut sink()).is_err() || checker.finish().is_err() {
-                    // The json file is invalid, we use Serde to get a nice error message:
+            let update_uuid =
+                if let Some((mut file, uuid, format, csv_delimiter, encoding)) = file_path {
+                    // set the file back to the beginning
                     file.seek(SeekFrom::Start(0))?;
-                    let _: serde_json::Value = serde_json::from_reader(file)
-                        .map_err(|e| UpdateActorError::InvalidPayload(Box::new(e)))?;
-                }
-                Some(uuid)
-            } else {
-                None
-            };
+                    // The payload limit applies to the decompressed documents too, so that a small
+                    // compressed payload can't fill the disk when indexed.
+                    if !encoding.is_identity() {
+                        let size = decoded_size(&mut file, encoding, payload_limit)
+                            .map_err(|e| UpdateActorError::InvalidPayload(Box::new(e)))?;
+                        if size.is_none() {
+                            return Err(PayloadError::Overflow.into());
+                        }
+                        file.seek(SeekFrom::Start(0))?;
+                    }
+                    check_payload(&mut file, format, csv_delimiter, encoding)?;
+                    Some(uuid)
+                } else {
+                    None
+                };
 
             // The payload is valid, we can register it to the update store.
             let status = update_store
@@ -272,3 +291,49 @@ where
         Ok(info)
     }
 }
+
+/// Checks that the documents of an addition can be parsed in their format, so that a malformed
+/// payload is rejected when it is sent instead of failing the update later.
+fn check_payload(
+    file: &mut std::fs::File,
+    format: UpdateFormat,
+    csv_delimiter: Option<u8>,
+    encoding: ContentEncoding,
+) -> Result<()> {
+    use std::io::{copy, sink, BufReader, Seek};
+
+    let invalid = |e: serde_json::Error| UpdateActorError::InvalidPayload(Box::new(e));
+    match format {
+        UpdateFormat::Json => {
+            let reader = BufReader::new(decode(&mut *file, encoding)?);
+            let mut checker = JsonChecker::new(reader);
+
+            if copy(&mut checker, &mut sink()).is_err() || checker.finish().is_err() {
+                // The json file is invalid, we use Serde to get a nice error message:
+                file.seek(SeekFrom::Start(0))?;
+                let _: serde_json::Value =
+                    serde_json::from_reader(decode(file, encoding)?).map_err(invalid)?;
+            }
+        }
+        UpdateFormat::JsonStream => {
+            let content = decode(file, encoding)?;
+            let documents = serde_json::Deserializer::from_reader(BufReader::new(content))
+                .into_iter::<serde_json::Map<String, serde_json::Value>>();
+            for document in documents {
+                document.map_err(invalid)?;
+            }
+        }
+        UpdateFormat::Csv => {
+            let delimiter = csv_delimiter.unwrap_or(DEFAULT_CSV_DELIMITER);
+            let invalid = |e: IndexError| UpdateActorError::InvalidPayload(Box::new(e));
+            let content = decode(file, encoding)?;
+            for document in
+                CsvDocuments::new(BufReader::new(content), delimiter).map_err(invalid)?
+            {
+                document.map_err(invalid)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -23,6 +23,8 @@ pub enum UpdateActorError {
     InvalidPayload(Box<dyn Error + Send + Sync + 'static>),
     #[error("{0}")]
     PayloadError(#[from] actix_web::error::PayloadError),
+    #[error("Update {0} was not processed in time.")]
+    WaitTimeout(u64),
 }
 
 impl<T> From<tokio::sync::mpsc::error::SendError<T>> for UpdateActorError {
@@ -56,6 +58,7 @@ impl ErrorCode for UpdateActorError {
                 actix_http::error::PayloadError::Overflow => Code::PayloadTooLarge,
                 _ => Code::Internal,
             },
+            UpdateActorError::WaitTimeout(_) => Code::UpdateWaitTimeout,
         }
     }
 }
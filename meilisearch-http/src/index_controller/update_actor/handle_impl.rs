@@ -1,12 +1,14 @@
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::{mpsc, oneshot};
 use uuid::Uuid;
 
 use crate::index_controller::{IndexActorHandle, UpdateStatus};
 
-use super::error::Result;
+use super::error::{Result, UpdateActorError};
 use super::{PayloadData, UpdateActor, UpdateActorHandle, UpdateMeta, UpdateMsg, UpdateStoreInfo};
 
 #[derive(Clone)]
@@ -22,13 +24,20 @@ where
         index_handle: I,
         path: impl AsRef<Path>,
         update_store_size: usize,
+        payload_limit: usize,
     ) -> anyhow::Result<Self>
     where
         I: IndexActorHandle + Clone + Send + Sync + 'static,
     {
         let path = path.as_ref().to_owned();
         let (sender, receiver) = mpsc::channel(100);
-        let actor = UpdateActor::new(update_store_size, receiver, path, index_handle)?;
+        let actor = UpdateActor::new(
+            update_store_size,
+            payload_limit,
+            receiver,
+            path,
+            index_handle,
+        )?;
 
         tokio::task::spawn(actor.run());
 
@@ -56,6 +65,39 @@ where
         receiver.await?
     }
 
+    async fn wait_update(&self, uuid: Uuid, id: u64, timeout: Duration) -> Result<UpdateStatus> {
+        let (ret, receiver) = oneshot::channel();
+        let msg = UpdateMsg::Subscribe { ret };
+        self.sender.send(msg).await?;
+        // We subscribe before reading the status, so an update processed in between is not missed.
+        let mut notifications = receiver.await?;
+
+        let wait = async {
+            loop {
+                let status = self.update_status(uuid, id).await?;
+                if status.is_finished() {
+                    return Ok(status);
+                }
+
+                loop {
+                    match notifications.recv().await {
+                        Ok(processed) if processed == (uuid, id) => break,
+                        Ok(_) => (),
+                        // Some notifications were missed, the status must be checked again.
+                        Err(RecvError::Lagged(_)) => break,
+                        Err(RecvError::Closed) => {
+                            return Err(UpdateActorError::FatalUpdateStoreError)
+                        }
+                    }
+                }
+            }
+        };
+
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| UpdateActorError::WaitTimeout(id))?
+    }
+
     async fn delete(&self, uuid: Uuid) -> Result<()> {
         let (ret, receiver) = oneshot::channel();
         let msg = UpdateMsg::Delete { uuid, ret };
@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 use std::path::PathBuf;
 
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{broadcast, mpsc, oneshot};
 use uuid::Uuid;
 
 use super::error::Result;
@@ -40,4 +40,7 @@ pub enum UpdateMsg<D> {
     GetInfo {
         ret: oneshot::Sender<Result<UpdateStoreInfo>>,
     },
+    Subscribe {
+        ret: oneshot::Sender<broadcast::Receiver<(Uuid, u64)>>,
+    },
 }
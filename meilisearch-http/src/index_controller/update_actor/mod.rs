@@ -1,3 +1,4 @@
+use std::time::Duration;
 use std::{collections::HashSet, path::PathBuf};
 
 use actix_http::error::PayloadError;
@@ -31,6 +32,8 @@ pub trait UpdateActorHandle {
 
     async fn get_all_updates_status(&self, uuid: Uuid) -> Result<Vec<UpdateStatus>>;
     async fn update_status(&self, uuid: Uuid, id: u64) -> Result<UpdateStatus>;
+    /// Waits, up to `timeout`, until the update is processed and returns its status.
+    async fn wait_update(&self, uuid: Uuid, id: u64, timeout: Duration) -> Result<UpdateStatus>;
     async fn delete(&self, uuid: Uuid) -> Result<()>;
     async fn snapshot(&self, uuid: HashSet<Uuid>, path: PathBuf) -> Result<()>;
     async fn dump(&self, uuids: HashSet<Uuid>, path: PathBuf) -> Result<()>;
@@ -18,7 +18,7 @@ use heed::{CompactionOption, Database, Env, EnvOpenOptions};
 use log::error;
 use parking_lot::{Mutex, MutexGuard};
 use tokio::runtime::Handle;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use uuid::Uuid;
 
 use codec::*;
@@ -103,6 +103,8 @@ pub struct UpdateStore {
     state: Arc<StateLock>,
     /// Wake up the loop when a new event occurs.
     notification_sender: mpsc::Sender<()>,
+    /// Notifies the index uuid and the id of each update once it is processed.
+    processed_sender: broadcast::Sender<(Uuid, u64)>,
     path: PathBuf,
 }
 
@@ -121,6 +123,7 @@ impl UpdateStore {
         let state = Arc::new(StateLock::from_state(State::Idle));
 
         let (notification_sender, notification_receiver) = mpsc::channel(10);
+        let (processed_sender, _) = broadcast::channel(100);
 
         Ok((
             Self {
@@ -130,6 +133,7 @@ impl UpdateStore {
                 updates,
                 state,
                 notification_sender,
+                processed_sender,
                 path: path.as_ref().to_owned(),
             },
             notification_receiver,
@@ -251,6 +255,11 @@ impl UpdateStore {
         Ok(meta)
     }
 
+    /// Subscribes to the notifications sent each time an update is processed.
+    pub fn subscribe(&self) -> broadcast::Receiver<(Uuid, u64)> {
+        self.processed_sender.subscribe()
+    }
+
     /// Push already processed update in the UpdateStore without triggering the notification
     /// process. This is useful for the dumps.
     pub fn register_raw_updates(
@@ -352,6 +361,9 @@ impl UpdateStore {
 
         wtxn.commit()?;
 
+        // Nobody waiting for an update is not an error.
+        let _ = self.processed_sender.send((index_uuid, update_id));
+
         if let Some(ref path) = content_path {
             remove_file(&path)?;
         }
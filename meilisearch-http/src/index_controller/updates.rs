@@ -1,17 +1,25 @@
 use chrono::{DateTime, Utc};
 use milli::update::{DocumentAdditionResult, IndexDocumentsMethod, UpdateFormat};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use uuid::Uuid;
 
 use crate::{
     error::ResponseError,
-    index::{Settings, Unchecked},
+    index::{ContentEncoding, InvalidDocuments, Rule, Settings, Unchecked},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum UpdateResult {
     DocumentsAddition(DocumentAdditionResult),
-    DocumentDeletion { deleted: u64 },
+    /// A documents addition that left out the documents not matching the document schema.
+    DocumentsAdditionSkipped {
+        addition: DocumentAdditionResult,
+        skipped: InvalidDocuments,
+    },
+    DocumentDeletion {
+        deleted: u64,
+    },
     Other,
 }
 
@@ -22,13 +30,27 @@ pub enum UpdateMeta {
     DocumentsAddition {
         method: IndexDocumentsMethod,
         format: UpdateFormat,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        csv_delimiter: Option<u8>,
+        #[serde(default, skip_serializing_if = "ContentEncoding::is_identity")]
+        encoding: ContentEncoding,
         primary_key: Option<String>,
     },
     ClearDocuments,
     DeleteDocuments {
         ids: Vec<String>,
     },
+    DeleteDocumentsByFilter {
+        filter: Value,
+    },
     Settings(Settings<Unchecked>),
+    PutRule {
+        id: String,
+        rule: Rule,
+    },
+    DeleteRule {
+        id: String,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -194,6 +216,14 @@ impl UpdateStatus {
         }
     }
 
+    /// Whether the update is done being processed, successfully or not.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            UpdateStatus::Processed(_) | UpdateStatus::Aborted(_) | UpdateStatus::Failed(_)
+        )
+    }
+
     pub fn processed(&self) -> Option<&Processed> {
         match self {
             UpdateStatus::Processed(p) => Some(p),
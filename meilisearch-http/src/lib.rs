@@ -115,10 +115,12 @@ macro_rules! create_app {
             .configure(document::services)
             .configure(index::services)
             .configure(search::services)
+            .configure(search_ws::services)
             .configure(settings::services)
             .configure(health::services)
             .configure(stats::services)
             .configure(key::services)
+            .configure(rules::services)
             .configure(dump::services)
             .configure(|s| dashboard(s, $enable_frontend))
             .wrap(
@@ -120,6 +120,20 @@ pub struct Opt {
     #[structopt(long, env = "MEILI_HTTP_PAYLOAD_SIZE_LIMIT", default_value = "100 MB")]
     pub http_payload_size_limit: Byte,
 
+    /// The default time, in milliseconds, after which a search stops refining its ranking and
+    /// returns the results it already has. Can be overridden by the `timeoutMs` search parameter.
+    #[structopt(long, env = "MEILI_SEARCH_TIMEOUT_MS")]
+    pub search_timeout_ms: Option<u64>,
+
+    /// The maximum time, in milliseconds, a read given a `waitForUpdateId` waits for this update
+    /// to be processed.
+    #[structopt(
+        long,
+        env = "MEILI_WAIT_FOR_UPDATE_TIMEOUT_MS",
+        default_value = "10000"
+    )]
+    pub wait_for_update_timeout_ms: u64,
+
     /// Read server certificates from CERTFILE.
     /// This should contain PEM-format certificates
     /// in the right order (the first certificate should
@@ -1,49 +1,21 @@
+use actix_web::http::header::VARY;
 use actix_web::{web, HttpResponse};
+use async_stream::stream;
 use log::debug;
-use milli::update::{IndexDocumentsMethod, UpdateFormat};
+use milli::update::IndexDocumentsMethod;
 use serde::Deserialize;
 use serde_json::Value;
 
 use crate::error::ResponseError;
 use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::conditional::Conditional;
+use crate::extractors::documents_format::{parse_csv_delimiter, DocumentsFormat};
 use crate::extractors::payload::Payload;
-use crate::routes::IndexParam;
+use crate::extractors::response_format::ResponseFormat;
+use crate::index::{has_relative_dates, BrowseDocumentsQuery, ExportFormat, FetchDocumentsQuery};
+use crate::routes::{IndexParam, WaitForUpdate};
 use crate::Data;
 
-const DEFAULT_RETRIEVE_DOCUMENTS_OFFSET: usize = 0;
-const DEFAULT_RETRIEVE_DOCUMENTS_LIMIT: usize = 20;
-
-/*
-macro_rules! guard_content_type {
-    ($fn_name:ident, $guard_value:literal) => {
-        fn $fn_name(head: &actix_web::dev::RequestHead) -> bool {
-            if let Some(content_type) = head.headers.get("Content-Type") {
-                content_type
-                    .to_str()
-                    .map(|v| v.contains($guard_value))
-                    .unwrap_or(false)
-            } else {
-                false
-            }
-        }
-    };
-}
-
-guard_content_type!(guard_json, "application/json");
-*/
-
-fn guard_json(head: &actix_web::dev::RequestHead) -> bool {
-    if let Some(content_type) = head.headers.get("Content-Type") {
-        content_type
-            .to_str()
-            .map(|v| v.contains("application/json"))
-            .unwrap_or(false)
-    } else {
-        // if no content-type is specified we still accept the data as json!
-        true
-    }
-}
-
 #[derive(Deserialize)]
 struct DocumentParam {
     index_uid: String,
@@ -56,12 +28,15 @@ pub fn services(cfg: &mut web::ServiceConfig) {
             .service(
                 web::resource("")
                     .route(web::get().to(get_all_documents))
-                    .route(web::post().guard(guard_json).to(add_documents))
-                    .route(web::put().guard(guard_json).to(update_documents))
+                    .route(web::post().to(add_documents))
+                    .route(web::put().to(update_documents))
                     .route(web::delete().to(clear_all_documents)),
             )
             // this route needs to be before the /documents/{document_id} to match properly
             .service(web::resource("/delete-batch").route(web::post().to(delete_documents)))
+            .service(web::resource("/delete").route(web::post().to(delete_documents_by_filter)))
+            .service(web::resource("/fetch").route(web::post().to(fetch_documents)))
+            .service(web::resource("/export").route(web::get().to(export_documents)))
             .service(
                 web::resource("/{document_id}")
                     .route(web::get().to(get_document))
@@ -73,14 +48,26 @@ pub fn services(cfg: &mut web::ServiceConfig) {
 async fn get_document(
     data: GuardedData<Public, Data>,
     path: web::Path<DocumentParam>,
+    wait: web::Query<WaitForUpdate>,
+    format: ResponseFormat,
+    conditional: Conditional,
 ) -> Result<HttpResponse, ResponseError> {
+    wait.wait(&data, &path.index_uid).await?;
     let index = path.index_uid.clone();
     let id = path.document_id.clone();
+
+    // The document id is part of the path, the request has no other parameter.
+    let version = data.index(index.clone()).await?.meta.updated_at;
+    let etag = conditional.etag(&version, &());
+    if conditional.is_fresh(&etag) {
+        return Ok(etag.not_modified());
+    }
+
     let document = data
         .retrieve_document(index, id, None as Option<Vec<String>>)
         .await?;
     debug!("returns: {:?}", document);
-    Ok(HttpResponse::Ok().json(document))
+    Ok(etag.tag(format.respond(&document, None)?))
 }
 
 async fn delete_document(
@@ -100,14 +87,24 @@ struct BrowseQuery {
     offset: Option<usize>,
     limit: Option<usize>,
     attributes_to_retrieve: Option<String>,
+    filter: Option<String>,
+    cursor: Option<String>,
+    wait_for_update_id: Option<u64>,
 }
 
 async fn get_all_documents(
     data: GuardedData<Public, Data>,
     path: web::Path<IndexParam>,
     params: web::Query<BrowseQuery>,
+    format: ResponseFormat,
+    conditional: Conditional,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("called with params: {:?}", params);
+    let wait = WaitForUpdate {
+        wait_for_update_id: params.wait_for_update_id,
+    };
+    wait.wait(&data, &path.index_uid).await?;
+
     let attributes_to_retrieve = params.attributes_to_retrieve.as_ref().and_then(|attrs| {
         let mut names = Vec::new();
         for name in attrs.split(',').map(String::from) {
@@ -119,38 +116,127 @@ async fn get_all_documents(
         Some(names)
     });
 
+    let query = BrowseDocumentsQuery {
+        offset: params.offset,
+        limit: params.limit,
+        attributes_to_retrieve: attributes_to_retrieve.clone(),
+        filter: params.filter.clone().map(Value::String),
+        cursor: params.cursor.clone(),
+    };
+
+    // The documents matching a filter on dates relative to now change over time, they aren't
+    // tagged.
+    let relative_dates = query.filter.as_ref().map_or(false, has_relative_dates);
+    let version = data.index(path.index_uid.clone()).await?.meta.updated_at;
+    let etag = conditional.etag(&version, &query);
+    if !relative_dates && conditional.is_fresh(&etag) {
+        return Ok(etag.not_modified());
+    }
+
     let documents = data
-        .retrieve_documents(
-            path.index_uid.clone(),
-            params.offset.unwrap_or(DEFAULT_RETRIEVE_DOCUMENTS_OFFSET),
-            params.limit.unwrap_or(DEFAULT_RETRIEVE_DOCUMENTS_LIMIT),
-            attributes_to_retrieve,
-        )
+        .retrieve_documents(path.index_uid.clone(), query)
         .await?;
     debug!("returns: {:?}", documents);
-    Ok(HttpResponse::Ok().json(documents))
+    let response = format.respond(&documents, attributes_to_retrieve.as_deref())?;
+    if relative_dates {
+        Ok(response)
+    } else {
+        Ok(etag.tag(response))
+    }
+}
+
+/// Route used to retrieve the documents matching a list of ids, a filter, or both. The ids
+/// matching no document are listed in the `missing` field of the response.
+async fn fetch_documents(
+    data: GuardedData<Public, Data>,
+    path: web::Path<IndexParam>,
+    wait: web::Query<WaitForUpdate>,
+    body: web::Json<FetchDocumentsQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    wait.wait(&data, &path.index_uid).await?;
+    let result = data
+        .fetch_documents(path.into_inner().index_uid, body.into_inner())
+        .await?;
+    debug!("returns: {:?}", result);
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ExportQuery {
+    filter: Option<String>,
+    wait_for_update_id: Option<u64>,
+}
+
+/// Route used to stream all the documents of an index, or those matching a filter. They are
+/// sent as NDJSON, or as CSV when the client accepts it.
+async fn export_documents(
+    data: GuardedData<Private, Data>,
+    path: web::Path<IndexParam>,
+    params: web::Query<ExportQuery>,
+    format: ResponseFormat,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", params);
+    let wait = WaitForUpdate {
+        wait_for_update_id: params.wait_for_update_id,
+    };
+    wait.wait(&data, &path.index_uid).await?;
+
+    let (format, content_type) = match format {
+        ResponseFormat::Csv => (ExportFormat::Csv, "text/csv"),
+        _ => (ExportFormat::Ndjson, "application/x-ndjson"),
+    };
+    let filter = params.into_inner().filter.map(Value::String);
+    let mut export = data
+        .export_documents(path.into_inner().index_uid, filter, format)
+        .await?;
+
+    // The errors found before anything is sent, like an invalid filter, get their own response.
+    let first = match export.recv().await {
+        Some(chunk) => Some(chunk?),
+        None => None,
+    };
+    let chunks = stream! {
+        if let Some(first) = first {
+            yield Ok::<_, ResponseError>(first);
+        }
+        while let Some(chunk) = export.recv().await {
+            yield chunk.map_err(ResponseError::from);
+        }
+    };
+
+    Ok(HttpResponse::Ok()
+        .insert_header((VARY, "Accept"))
+        .content_type(content_type)
+        .streaming(chunks))
 }
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 struct UpdateDocumentsQuery {
     primary_key: Option<String>,
+    csv_delimiter: Option<String>,
 }
 
-/// Route used when the payload type is "application/json"
-/// Used to add or replace documents
+/// Route used to add or replace documents, sent as JSON, NDJSON or CSV, possibly compressed.
 async fn add_documents(
     data: GuardedData<Private, Data>,
     path: web::Path<IndexParam>,
     params: web::Query<UpdateDocumentsQuery>,
+    format: DocumentsFormat,
     body: Payload,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("called with params: {:?}", params);
+    let format = DocumentsFormat {
+        csv_delimiter: parse_csv_delimiter(params.csv_delimiter.as_deref())?,
+        ..format
+    };
     let update_status = data
         .add_documents(
             path.into_inner().index_uid,
             IndexDocumentsMethod::ReplaceDocuments,
-            UpdateFormat::Json,
+            format,
             body,
             params.primary_key.clone(),
         )
@@ -160,20 +246,24 @@ async fn add_documents(
     Ok(HttpResponse::Accepted().json(serde_json::json!({ "updateId": update_status.id() })))
 }
 
-/// Route used when the payload type is "application/json"
-/// Used to add or replace documents
+/// Route used to add or update documents, sent as JSON, NDJSON or CSV, possibly compressed.
 async fn update_documents(
     data: GuardedData<Private, Data>,
     path: web::Path<IndexParam>,
     params: web::Query<UpdateDocumentsQuery>,
+    format: DocumentsFormat,
     body: Payload,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("called with params: {:?}", params);
+    let format = DocumentsFormat {
+        csv_delimiter: parse_csv_delimiter(params.csv_delimiter.as_deref())?,
+        ..format
+    };
     let update = data
         .add_documents(
             path.into_inner().index_uid,
             IndexDocumentsMethod::UpdateDocuments,
-            UpdateFormat::Json,
+            format,
             body,
             params.primary_key.clone(),
         )
@@ -203,6 +293,25 @@ async fn delete_documents(
     Ok(HttpResponse::Accepted().json(serde_json::json!({ "updateId": update_status.id() })))
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct DeleteDocumentsByFilter {
+    filter: Value,
+}
+
+async fn delete_documents_by_filter(
+    data: GuardedData<Private, Data>,
+    path: web::Path<IndexParam>,
+    body: web::Json<DeleteDocumentsByFilter>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let update_status = data
+        .delete_documents_by_filter(path.index_uid.clone(), body.into_inner().filter)
+        .await?;
+    debug!("returns: {:?}", update_status);
+    Ok(HttpResponse::Accepted().json(serde_json::json!({ "updateId": update_status.id() })))
+}
+
 async fn clear_all_documents(
     data: GuardedData<Private, Data>,
     path: web::Path<IndexParam>,
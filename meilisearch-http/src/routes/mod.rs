@@ -5,15 +5,18 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::error::ResponseError;
-use crate::index::{Settings, Unchecked};
+use crate::index::{InvalidDocuments, Settings, Unchecked};
 use crate::index_controller::{UpdateMeta, UpdateResult, UpdateStatus};
+use crate::Data;
 
 pub mod document;
 pub mod dump;
 pub mod health;
 pub mod index;
 pub mod key;
+pub mod rules;
 pub mod search;
+pub mod search_ws;
 pub mod settings;
 pub mod stats;
 
@@ -26,10 +29,24 @@ pub enum UpdateType {
     DocumentsAddition {
         #[serde(skip_serializing_if = "Option::is_none")]
         number: Option<usize>,
+        /// The documents left out of the addition for not matching the document schema.
+        #[serde(
+            default,
+            rename = "invalidDocuments",
+            skip_serializing_if = "Option::is_none"
+        )]
+        invalid_documents: Option<InvalidDocuments>,
     },
     DocumentsPartial {
         #[serde(skip_serializing_if = "Option::is_none")]
         number: Option<usize>,
+        /// The documents left out of the addition for not matching the document schema.
+        #[serde(
+            default,
+            rename = "invalidDocuments",
+            skip_serializing_if = "Option::is_none"
+        )]
+        invalid_documents: Option<InvalidDocuments>,
     },
     DocumentsDeletion {
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -38,6 +55,12 @@ pub enum UpdateType {
     Settings {
         settings: Settings<Unchecked>,
     },
+    Rule {
+        id: String,
+    },
+    RuleDeletion {
+        id: String,
+    },
 }
 
 impl From<&UpdateStatus> for UpdateType {
@@ -46,19 +69,29 @@ impl From<&UpdateStatus> for UpdateType {
 
         match other.meta() {
             UpdateMeta::DocumentsAddition { method, .. } => {
-                let number = match other {
+                let (number, invalid_documents) = match other {
                     UpdateStatus::Processed(processed) => match processed.success {
                         UpdateResult::DocumentsAddition(ref addition) => {
-                            Some(addition.nb_documents)
+                            (Some(addition.nb_documents), None)
                         }
-                        _ => None,
+                        UpdateResult::DocumentsAdditionSkipped {
+                            ref addition,
+                            ref skipped,
+                        } => (Some(addition.nb_documents), Some(skipped.clone())),
+                        _ => (None, None),
                     },
-                    _ => None,
+                    _ => (None, None),
                 };
 
                 match method {
-                    ReplaceDocuments => UpdateType::DocumentsAddition { number },
-                    UpdateDocuments => UpdateType::DocumentsPartial { number },
+                    ReplaceDocuments => UpdateType::DocumentsAddition {
+                        number,
+                        invalid_documents,
+                    },
+                    UpdateDocuments => UpdateType::DocumentsPartial {
+                        number,
+                        invalid_documents,
+                    },
                     _ => unreachable!(),
                 }
             }
@@ -66,9 +99,22 @@ impl From<&UpdateStatus> for UpdateType {
             UpdateMeta::DeleteDocuments { ids } => UpdateType::DocumentsDeletion {
                 number: Some(ids.len()),
             },
+            // The number of documents matching the filter is only known once processed.
+            UpdateMeta::DeleteDocumentsByFilter { .. } => {
+                let number = match other {
+                    UpdateStatus::Processed(processed) => match processed.success {
+                        UpdateResult::DocumentDeletion { deleted } => Some(deleted as usize),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                UpdateType::DocumentsDeletion { number }
+            }
             UpdateMeta::Settings(settings) => UpdateType::Settings {
                 settings: settings.clone(),
             },
+            UpdateMeta::PutRule { id, .. } => UpdateType::Rule { id: id.clone() },
+            UpdateMeta::DeleteRule { id } => UpdateType::RuleDeletion { id: id.clone() },
         }
     }
 }
@@ -202,6 +248,23 @@ pub struct IndexParam {
     index_uid: String,
 }
 
+/// Lets a read wait for an update to be processed before it is performed.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WaitForUpdate {
+    wait_for_update_id: Option<u64>,
+}
+
+impl WaitForUpdate {
+    pub async fn wait(&self, data: &Data, index_uid: &str) -> Result<(), ResponseError> {
+        if let Some(update_id) = self.wait_for_update_id {
+            data.wait_for_update(index_uid.to_string(), update_id)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct IndexUpdateResponse {
@@ -0,0 +1,85 @@
+use actix_web::{web, HttpResponse};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::error::ResponseError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::index::Rule;
+use crate::routes::IndexParam;
+use crate::Data;
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/indexes/{index_uid}/rules").route(web::get().to(list_rules)))
+        .service(
+            web::resource("/indexes/{index_uid}/rules/{rule_id}")
+                .route(web::get().to(get_rule))
+                .route(web::put().to(put_rule))
+                .route(web::delete().to(delete_rule)),
+        );
+}
+
+#[derive(Deserialize)]
+struct RuleParam {
+    index_uid: String,
+    rule_id: String,
+}
+
+#[derive(Serialize, Debug)]
+struct RuleResponse {
+    id: String,
+    #[serde(flatten)]
+    rule: Rule,
+}
+
+async fn list_rules(
+    data: GuardedData<Private, Data>,
+    path: web::Path<IndexParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let rules = data.rules(path.into_inner().index_uid).await?;
+    let rules = rules
+        .into_iter()
+        .map(|(id, rule)| RuleResponse { id, rule })
+        .collect::<Vec<_>>();
+
+    debug!("returns: {:?}", rules);
+    Ok(HttpResponse::Ok().json(rules))
+}
+
+async fn get_rule(
+    data: GuardedData<Private, Data>,
+    path: web::Path<RuleParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let RuleParam { index_uid, rule_id } = path.into_inner();
+    let rule = data.rule(index_uid, rule_id.clone()).await?;
+    let rule = RuleResponse { id: rule_id, rule };
+
+    debug!("returns: {:?}", rule);
+    Ok(HttpResponse::Ok().json(rule))
+}
+
+/// Rules are written through the update queue, so that they are applied in order with the
+/// settings and documents updates of the index.
+async fn put_rule(
+    data: GuardedData<Private, Data>,
+    path: web::Path<RuleParam>,
+    body: web::Json<Rule>,
+) -> Result<HttpResponse, ResponseError> {
+    let RuleParam { index_uid, rule_id } = path.into_inner();
+    let rule = body.into_inner();
+    debug!("called with params: {:?}", rule);
+
+    let update_status = data.put_rule(index_uid, rule_id, rule).await?;
+    debug!("returns: {:?}", update_status);
+    Ok(HttpResponse::Accepted().json(json!({ "updateId": update_status.id() })))
+}
+
+async fn delete_rule(
+    data: GuardedData<Private, Data>,
+    path: web::Path<RuleParam>,
+) -> Result<HttpResponse, ResponseError> {
+    let RuleParam { index_uid, rule_id } = path.into_inner();
+    let update_status = data.delete_rule(index_uid, rule_id).await?;
+    debug!("returns: {:?}", update_status);
+    Ok(HttpResponse::Accepted().json(json!({ "updateId": update_status.id() })))
+}
@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashSet};
+use std::collections::BTreeSet;
 
 use actix_web::{web, HttpResponse};
 use log::debug;
@@ -7,8 +7,10 @@ use serde_json::Value;
 
 use crate::error::ResponseError;
 use crate::extractors::authentication::{policies::*, GuardedData};
-use crate::index::{default_crop_length, SearchQuery, DEFAULT_SEARCH_LIMIT};
-use crate::routes::IndexParam;
+use crate::extractors::conditional::Conditional;
+use crate::extractors::response_format::ResponseFormat;
+use crate::index::SearchQuery;
+use crate::routes::{IndexParam, WaitForUpdate};
 use crate::Data;
 
 pub fn services(cfg: &mut web::ServiceConfig) {
@@ -16,6 +18,9 @@ pub fn services(cfg: &mut web::ServiceConfig) {
         web::resource("/indexes/{index_uid}/search")
             .route(web::get().to(search_with_url_query))
             .route(web::post().to(search_with_post)),
+    )
+    .service(
+        web::resource("/indexes/{index_uid}/search/explain").route(web::post().to(explain_search)),
     );
 }
 
@@ -25,15 +30,18 @@ pub struct SearchQueryGet {
     q: Option<String>,
     offset: Option<usize>,
     limit: Option<usize>,
+    page: Option<usize>,
+    hits_per_page: Option<usize>,
     attributes_to_retrieve: Option<String>,
     attributes_to_crop: Option<String>,
-    #[serde(default = "default_crop_length")]
-    crop_length: usize,
+    crop_length: Option<usize>,
     attributes_to_highlight: Option<String>,
     filter: Option<String>,
     #[serde(default = "Default::default")]
     matches: bool,
     facets_distribution: Option<String>,
+    timeout_ms: Option<u64>,
+    wait_for_update_id: Option<u64>,
 }
 
 impl From<SearchQueryGet> for SearchQuery {
@@ -48,7 +56,7 @@ impl From<SearchQueryGet> for SearchQuery {
 
         let attributes_to_highlight = other
             .attributes_to_highlight
-            .map(|attrs| attrs.split(',').map(String::from).collect::<HashSet<_>>());
+            .map(|attrs| attrs.split(',').map(String::from).collect::<BTreeSet<_>>());
 
         let facets_distribution = other
             .facets_distribution
@@ -65,7 +73,9 @@ impl From<SearchQueryGet> for SearchQuery {
         Self {
             q: other.q,
             offset: other.offset,
-            limit: other.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+            limit: other.limit,
+            page: other.page,
+            hits_per_page: other.hits_per_page,
             attributes_to_retrieve,
             attributes_to_crop,
             crop_length: other.crop_length,
@@ -73,6 +83,14 @@ impl From<SearchQueryGet> for SearchQuery {
             filter,
             matches: other.matches,
             facets_distribution,
+            hierarchical_facets: None,
+            facet_ranges: None,
+            group_by: None,
+            boost: None,
+            timeout_ms: other.timeout_ms,
+            cancelled: None,
+            vector: None,
+            hybrid: None,
         }
     }
 }
@@ -81,23 +99,88 @@ async fn search_with_url_query(
     data: GuardedData<Admin, Data>,
     path: web::Path<IndexParam>,
     params: web::Query<SearchQueryGet>,
+    format: ResponseFormat,
+    conditional: Conditional,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("called with params: {:?}", params);
-    let query = params.into_inner().into();
-    let search_result = data.search(path.into_inner().index_uid, query).await?;
-    debug!("returns: {:?}", search_result);
-    Ok(HttpResponse::Ok().json(search_result))
+    let params = params.into_inner();
+    let index_uid = path.into_inner().index_uid;
+    let wait = WaitForUpdate {
+        wait_for_update_id: params.wait_for_update_id,
+    };
+    wait.wait(&data, &index_uid).await?;
+    let columns = params
+        .attributes_to_retrieve
+        .as_ref()
+        .map(|attrs| attrs.split(',').map(String::from).collect::<Vec<_>>());
+    search(
+        &data,
+        index_uid,
+        params.into(),
+        columns,
+        format,
+        conditional,
+    )
+    .await
 }
 
 async fn search_with_post(
     data: GuardedData<Public, Data>,
     path: web::Path<IndexParam>,
+    wait: web::Query<WaitForUpdate>,
     params: web::Json<SearchQuery>,
+    format: ResponseFormat,
+    conditional: Conditional,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("search called with params: {:?}", params);
-    let search_result = data
-        .search(path.into_inner().index_uid, params.into_inner())
-        .await?;
+    let index_uid = path.into_inner().index_uid;
+    wait.wait(&data, &index_uid).await?;
+    let params = params.into_inner();
+    let columns = params
+        .attributes_to_retrieve
+        .as_ref()
+        .map(|attrs| attrs.iter().cloned().collect::<Vec<_>>());
+    search(&data, index_uid, params, columns, format, conditional).await
+}
+
+/// Performs the search, unless the client already has its result for the current version of the
+/// index. The version changes when the index is updated and when a rule starts or stops being
+/// applied. A partial result, cut short by the search timeout, isn't tagged, nor is the result
+/// of a filter on dates relative to now, which changes over time.
+async fn search(
+    data: &Data,
+    index_uid: String,
+    query: SearchQuery,
+    columns: Option<Vec<String>>,
+    format: ResponseFormat,
+    conditional: Conditional,
+) -> Result<HttpResponse, ResponseError> {
+    // The version is read before searching, so a concurrent update can't be missed.
+    let version = data.index(index_uid.clone()).await?.meta.version;
+    let etag = conditional.etag(&version, &query);
+    if conditional.is_fresh(&etag) {
+        return Ok(etag.not_modified());
+    }
+
+    let search_result = data.search(index_uid, query).await?;
     debug!("returns: {:?}", search_result);
-    Ok(HttpResponse::Ok().json(search_result))
+    let response = format.respond(&search_result, columns.as_deref())?;
+    if search_result.timed_out || search_result.relative_dates {
+        Ok(response)
+    } else {
+        Ok(etag.tag(response))
+    }
+}
+
+async fn explain_search(
+    data: GuardedData<Private, Data>,
+    path: web::Path<IndexParam>,
+    params: web::Json<SearchQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("explain search called with params: {:?}", params);
+    let explanation = data
+        .explain_search(path.into_inner().index_uid, params.into_inner())
+        .await?;
+    debug!("returns: {:?}", explanation);
+    Ok(HttpResponse::Ok().json(explanation))
 }
@@ -0,0 +1,336 @@
+//! Search-as-you-type over a WebSocket.
+//!
+//! The client sends its queries as text messages holding the same payload as the search route,
+//! and receives the result of each query, or the error it raised, as a text message. A query
+//! still being processed when a newer one arrives is cancelled and its result is never sent, even
+//! when it was already waiting to be sent. The messages can be fragmented.
+//!
+//! Browsers can't set the `X-Meili-API-Key` header when opening a WebSocket. When the upgrade
+//! request is not authenticated by its headers, the first message must authenticate the
+//! connection: `{"apiKey": "<key>"}`.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use actix_codec::{Decoder, Encoder};
+use actix_http::ws::{self, CloseCode, CloseReason, Codec, Frame, Item, Message};
+use actix_web::http::{header, HeaderValue};
+use actix_web::web::{self, BytesMut};
+use actix_web::{HttpRequest, HttpResponse};
+use async_stream::stream;
+use futures::StreamExt;
+use log::debug;
+use meilisearch_error::{Code, ErrorCode};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::error::ResponseError;
+use crate::extractors::authentication::policies::Public;
+use crate::extractors::authentication::AuthConfig;
+use crate::index::SearchQuery;
+use crate::routes::IndexParam;
+use crate::Data;
+
+/// The number of messages waiting to be sent to the client.
+const OUTGOING_MESSAGES: usize = 16;
+/// The maximum size of a message reassembled from its fragments.
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+pub fn services(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/indexes/{index_uid}/search/ws").route(web::get().to(search_ws)));
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Authentication {
+    api_key: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum WebSocketError {
+    #[error("Invalid WebSocket handshake: {0}.")]
+    Handshake(ws::HandshakeError),
+    #[error("Invalid API key.")]
+    InvalidToken,
+    #[error("Invalid message: {0}.")]
+    InvalidMessage(serde_json::Error),
+}
+
+impl ErrorCode for WebSocketError {
+    fn error_code(&self) -> Code {
+        match self {
+            WebSocketError::Handshake(_) => Code::BadRequest,
+            WebSocketError::InvalidToken => Code::InvalidToken,
+            WebSocketError::InvalidMessage(_) => Code::BadRequest,
+        }
+    }
+}
+
+async fn search_ws(
+    req: HttpRequest,
+    path: web::Path<IndexParam>,
+    payload: web::Payload,
+) -> Result<HttpResponse, ResponseError> {
+    ws::verify_handshake(req.head()).map_err(WebSocketError::Handshake)?;
+
+    let data = req
+        .app_data::<Data>()
+        .cloned()
+        .expect("The data must be configured");
+    let token = req.headers().get("x-meili-api-key").map(|t| t.as_bytes());
+    let authenticated = req
+        .app_data::<AuthConfig>()
+        .map_or(false, |config| config.authenticate::<Public>(token));
+
+    // The key is guaranteed to be present by the handshake verification.
+    let key = req.headers()[header::SEC_WEBSOCKET_KEY].as_bytes();
+    let accept = ws::hash_key(key);
+
+    let (sender, mut receiver) = mpsc::channel::<Outgoing>(OUTGOING_MESSAGES);
+    let generation = Arc::new(AtomicU64::new(0));
+    let mut connection = Connection {
+        req: req.clone(),
+        data,
+        index_uid: path.into_inner().index_uid,
+        authenticated,
+        sender,
+        search: None,
+        generation: generation.clone(),
+        fragments: None,
+    };
+
+    actix_web::rt::spawn(async move {
+        let mut payload = payload;
+        let mut codec = Codec::new();
+        let mut buffer = BytesMut::new();
+
+        'read: while let Some(chunk) = payload.next().await {
+            match chunk {
+                Ok(chunk) => buffer.extend_from_slice(&chunk),
+                Err(_) => break,
+            }
+
+            loop {
+                match codec.decode(&mut buffer) {
+                    Ok(Some(frame)) => {
+                        if !connection.handle_frame(frame).await {
+                            break 'read;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        connection.close(CloseCode::Protocol, e.to_string()).await;
+                        break 'read;
+                    }
+                }
+            }
+        }
+
+        // The client is gone, there is nobody left to send the pending result to.
+        connection.cancel_search();
+    });
+
+    let messages = stream! {
+        let mut codec = Codec::new();
+        while let Some(outgoing) = receiver.recv().await {
+            // A result superseded while it was waiting to be sent is dropped.
+            if outgoing.generation.map_or(false, |g| g != generation.load(Ordering::Relaxed)) {
+                continue;
+            }
+            let mut buffer = BytesMut::new();
+            codec.encode(outgoing.message, &mut buffer)?;
+            yield Ok::<_, ws::ProtocolError>(buffer.freeze());
+        }
+    };
+
+    Ok(HttpResponse::SwitchingProtocols()
+        .upgrade("websocket")
+        .insert_header((
+            header::SEC_WEBSOCKET_ACCEPT,
+            HeaderValue::from_bytes(&accept).expect("The accept key is valid"),
+        ))
+        .streaming(messages))
+}
+
+/// A message for the client, along with the generation of the query whose result it is.
+struct Outgoing {
+    message: Message,
+    generation: Option<u64>,
+}
+
+struct Connection {
+    req: HttpRequest,
+    data: Data,
+    index_uid: String,
+    authenticated: bool,
+    sender: mpsc::Sender<Outgoing>,
+    /// The search being processed, if any.
+    search: Option<PendingSearch>,
+    /// Incremented whenever a search is cancelled, the results of the previous generations are
+    /// never sent.
+    generation: Arc<AtomicU64>,
+    /// The fragments received so far of a fragmented text message.
+    fragments: Option<BytesMut>,
+}
+
+struct PendingSearch {
+    task: JoinHandle<()>,
+    /// Stops the search itself, which runs on a blocking thread that aborting the task can't
+    /// stop.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Connection {
+    /// Handles a frame sent by the client, returns whether the connection must be kept open.
+    async fn handle_frame(&mut self, frame: Frame) -> bool {
+        match frame {
+            Frame::Text(text) => self.handle_text(&text).await,
+            Frame::Continuation(item) => self.handle_fragment(item).await,
+            Frame::Ping(bytes) => self.send(Message::Pong(bytes)).await,
+            Frame::Pong(_) => true,
+            Frame::Close(reason) => {
+                // The result of the last query is sent before closing the connection.
+                if let Some(search) = self.search.take() {
+                    let _ = search.task.await;
+                }
+                self.send(Message::Close(reason)).await;
+                false
+            }
+            Frame::Binary(_) => {
+                let reason = "Only text messages are supported.";
+                self.close(CloseCode::Unsupported, reason).await;
+                false
+            }
+        }
+    }
+
+    async fn handle_text(&mut self, text: &[u8]) -> bool {
+        if !self.authenticated {
+            return self.authenticate(text).await;
+        }
+        self.search(text).await;
+        true
+    }
+
+    /// Reassembles a fragmented message, which is handled once its last fragment is received.
+    async fn handle_fragment(&mut self, item: Item) -> bool {
+        let (bytes, last) = match item {
+            Item::FirstText(bytes) if self.fragments.is_none() => {
+                self.fragments = Some(BytesMut::new());
+                (bytes, false)
+            }
+            Item::Continue(bytes) if self.fragments.is_some() => (bytes, false),
+            Item::Last(bytes) if self.fragments.is_some() => (bytes, true),
+            Item::FirstBinary(_) => {
+                let reason = "Only text messages are supported.";
+                self.close(CloseCode::Unsupported, reason).await;
+                return false;
+            }
+            _ => {
+                let reason = "Unexpected continuation frame.";
+                self.close(CloseCode::Protocol, reason).await;
+                return false;
+            }
+        };
+
+        let fragments = self.fragments.as_mut().expect("the message was started");
+        if fragments.len() + bytes.len() > MAX_MESSAGE_SIZE {
+            self.close(CloseCode::Size, "Message too big.").await;
+            return false;
+        }
+        fragments.extend_from_slice(&bytes);
+        if !last {
+            return true;
+        }
+
+        let text = self.fragments.take().expect("the message was started");
+        self.handle_text(&text).await
+    }
+
+    async fn authenticate(&mut self, text: &[u8]) -> bool {
+        let token = serde_json::from_slice::<Authentication>(text)
+            .ok()
+            .map(|authentication| authentication.api_key);
+        self.authenticated = self.req.app_data::<AuthConfig>().map_or(false, |config| {
+            config.authenticate::<Public>(token.as_ref().map(|t| t.as_bytes()))
+        });
+
+        if !self.authenticated {
+            self.send_error(WebSocketError::InvalidToken).await;
+            self.close(CloseCode::Policy, "Invalid API key.").await;
+        }
+
+        self.authenticated
+    }
+
+    async fn search(&mut self, text: &[u8]) {
+        // A newer query supersedes the one being processed.
+        self.cancel_search();
+
+        let mut query: SearchQuery = match serde_json::from_slice(text) {
+            Ok(query) => query,
+            Err(e) => {
+                self.send_error(WebSocketError::InvalidMessage(e)).await;
+                return;
+            }
+        };
+        debug!("websocket search called with params: {:?}", query);
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        query.cancelled = Some(cancelled.clone());
+
+        let data = self.data.clone();
+        let index_uid = self.index_uid.clone();
+        let sender = self.sender.clone();
+        let generation = Some(self.generation.load(Ordering::Relaxed));
+        let task = actix_web::rt::spawn(async move {
+            let message = match data.search(index_uid, query).await {
+                Ok(result) => serde_json::to_string(&result),
+                Err(e) => serde_json::to_string(&ResponseError::from(e)),
+            };
+            if let Ok(message) = message {
+                let message = Message::Text(message.into());
+                let _ = sender
+                    .send(Outgoing {
+                        message,
+                        generation,
+                    })
+                    .await;
+            }
+        });
+
+        self.search = Some(PendingSearch { task, cancelled });
+    }
+
+    fn cancel_search(&mut self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        if let Some(search) = self.search.take() {
+            search.cancelled.store(true, Ordering::Relaxed);
+            search.task.abort();
+        }
+    }
+
+    /// Sends a message to the client, returns false if the client is gone.
+    async fn send(&self, message: Message) -> bool {
+        let outgoing = Outgoing {
+            message,
+            generation: None,
+        };
+        self.sender.send(outgoing).await.is_ok()
+    }
+
+    async fn send_error(&self, error: WebSocketError) {
+        if let Ok(message) = serde_json::to_string(&ResponseError::from(error)) {
+            self.send(Message::Text(message.into())).await;
+        }
+    }
+
+    async fn close(&self, code: CloseCode, description: impl Into<String>) {
+        let reason = CloseReason {
+            code,
+            description: Some(description.into()),
+        };
+        self.send(Message::Close(Some(reason))).await;
+    }
+}
@@ -2,6 +2,7 @@ use actix_web::{web, HttpResponse};
 use log::debug;
 
 use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::conditional::Conditional;
 use crate::index::Settings;
 use crate::Data;
 use crate::{error::ResponseError, index::Unchecked};
@@ -17,6 +18,7 @@ macro_rules! make_setting_route {
             use crate::error::ResponseError;
             use crate::index::Settings;
             use crate::extractors::authentication::{GuardedData, policies::*};
+            use crate::extractors::conditional::Conditional;
 
             async fn delete(
                 data: GuardedData<Private, data::Data>,
@@ -50,12 +52,20 @@ macro_rules! make_setting_route {
             async fn get(
                 data: GuardedData<Private, data::Data>,
                 index_uid: actix_web::web::Path<String>,
+                conditional: Conditional,
             ) -> std::result::Result<HttpResponse, ResponseError> {
-                let settings = data.settings(index_uid.into_inner()).await?;
+                let index_uid = index_uid.into_inner();
+                let version = data.index(index_uid.clone()).await?.meta.updated_at;
+                let etag = conditional.etag(&version, &());
+                if conditional.is_fresh(&etag) {
+                    return Ok(etag.not_modified());
+                }
+
+                let settings = data.settings(index_uid).await?;
                 debug!("returns: {:?}", settings);
                 let mut json = serde_json::json!(&settings);
                 let val = json[$camelcase_attr].take();
-                Ok(HttpResponse::Ok().json(val))
+                Ok(etag.tag(HttpResponse::Ok().json(val)))
             }
 
             pub fn resources() -> Resource {
@@ -117,6 +127,41 @@ make_setting_route!(
     "rankingRules"
 );
 
+make_setting_route!(
+    "/indexes/{index_uid}/settings/hierarchical-facets",
+    std::collections::BTreeMap<String, Vec<String>>,
+    hierarchical_facets,
+    "hierarchicalFacets"
+);
+
+make_setting_route!(
+    "/indexes/{index_uid}/settings/date-attributes",
+    std::collections::BTreeSet<String>,
+    date_attributes,
+    "dateAttributes"
+);
+
+make_setting_route!(
+    "/indexes/{index_uid}/settings/search-defaults",
+    crate::index::SearchDefaults,
+    search_defaults,
+    "searchDefaults"
+);
+
+make_setting_route!(
+    "/indexes/{index_uid}/settings/pagination",
+    crate::index::Pagination,
+    pagination,
+    "pagination"
+);
+
+make_setting_route!(
+    "/indexes/{index_uid}/settings/document-schema",
+    crate::index::DocumentSchema,
+    document_schema,
+    "documentSchema"
+);
+
 macro_rules! create_services {
     ($($mod:ident),*) => {
         pub fn services(cfg: &mut web::ServiceConfig) {
@@ -137,7 +182,12 @@ create_services!(
     distinct_attribute,
     stop_words,
     synonyms,
-    ranking_rules
+    ranking_rules,
+    hierarchical_facets,
+    date_attributes,
+    search_defaults,
+    pagination,
+    document_schema
 );
 
 async fn update_all(
@@ -157,10 +207,18 @@ async fn update_all(
 async fn get_all(
     data: GuardedData<Private, Data>,
     index_uid: web::Path<String>,
+    conditional: Conditional,
 ) -> Result<HttpResponse, ResponseError> {
-    let settings = data.settings(index_uid.into_inner()).await?;
+    let index_uid = index_uid.into_inner();
+    let version = data.index(index_uid.clone()).await?.meta.updated_at;
+    let etag = conditional.etag(&version, &());
+    if conditional.is_fresh(&etag) {
+        return Ok(etag.not_modified());
+    }
+
+    let settings = data.settings(index_uid).await?;
     debug!("returns: {:?}", settings);
-    Ok(HttpResponse::Ok().json(settings))
+    Ok(etag.tag(HttpResponse::Ok().json(settings)))
 }
 
 async fn delete_all(
@@ -165,6 +165,23 @@ impl Index<'_> {
             .await
     }
 
+    pub async fn delete_by_filter(&self, filter: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/delete", self.uid);
+        self.service.post(url, json!({ "filter": filter })).await
+    }
+
+    pub async fn fetch_documents(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/fetch", self.uid);
+        self.service.post(url, body).await
+    }
+
+    /// Exports the documents of the index in the `accept` format, `query` is the query string of
+    /// the request.
+    pub async fn export_documents(&self, query: &str, accept: &str) -> (Vec<u8>, StatusCode) {
+        let url = format!("/indexes/{}/documents/export?{}", self.uid, query);
+        self.service.get_accept(url, accept).await
+    }
+
     pub async fn settings(&self) -> (Value, StatusCode) {
         let url = format!("/indexes/{}/settings", self.uid);
         self.service.get(url).await
@@ -180,12 +197,42 @@ impl Index<'_> {
         self.service.delete(url).await
     }
 
+    pub async fn search_post(&self, query: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/search", self.uid);
+        self.service.post(url, query).await
+    }
+
+    pub async fn explain_search(&self, query: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/search/explain", self.uid);
+        self.service.post(url, query).await
+    }
+
+    pub async fn rules(&self) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/rules", self.uid);
+        self.service.get(url).await
+    }
+
+    pub async fn rule(&self, id: &str) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/rules/{}", self.uid, id);
+        self.service.get(url).await
+    }
+
+    pub async fn put_rule(&self, id: &str, rule: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/rules/{}", self.uid, id);
+        self.service.put(url, rule).await
+    }
+
+    pub async fn delete_rule(&self, id: &str) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/rules/{}", self.uid, id);
+        self.service.delete(url).await
+    }
+
     pub async fn stats(&self) -> (Value, StatusCode) {
         let url = format!("/indexes/{}/stats", self.uid);
         self.service.get(url).await
     }
 
-    make_settings_test_routes!(distinct_attribute);
+    make_settings_test_routes!(distinct_attribute, document_schema);
 }
 
 pub struct GetDocumentOptions;
@@ -76,6 +76,8 @@ pub fn default_settings(dir: impl AsRef<Path>) -> Opt {
         max_index_size: Byte::from_unit(4.0, ByteUnit::GiB).unwrap(),
         max_udb_size: Byte::from_unit(4.0, ByteUnit::GiB).unwrap(),
         http_payload_size_limit: Byte::from_unit(10.0, ByteUnit::MiB).unwrap(),
+        search_timeout_ms: None,
+        wait_for_update_timeout_ms: 10000,
         ssl_cert_path: None,
         ssl_key_path: None,
         ssl_auth_path: None,
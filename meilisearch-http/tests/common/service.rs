@@ -1,4 +1,8 @@
-use actix_web::{http::StatusCode, test};
+use actix_codec::{Decoder, Encoder};
+use actix_http::ws::{Codec, Frame, Message};
+use actix_web::http::{header::HeaderMap, StatusCode};
+use actix_web::test;
+use actix_web::web::BytesMut;
 use serde_json::Value;
 
 use meilisearch_http::create_app;
@@ -27,14 +31,41 @@ impl Service {
         &self,
         url: impl AsRef<str>,
         body: impl AsRef<str>,
+    ) -> (Value, StatusCode) {
+        self.post_content(url, body, "application/json").await
+    }
+
+    /// Send a test post request from a text body, with the given `content-type` header.
+    pub async fn post_content(
+        &self,
+        url: impl AsRef<str>,
+        body: impl AsRef<str>,
+        content_type: &str,
+    ) -> (Value, StatusCode) {
+        self.post_bytes(
+            url,
+            body.as_ref().as_bytes().to_vec(),
+            &[("content-type", content_type)],
+        )
+        .await
+    }
+
+    /// Send a test post request from a binary body, with the given headers.
+    pub async fn post_bytes(
+        &self,
+        url: impl AsRef<str>,
+        body: Vec<u8>,
+        headers: &[(&str, &str)],
     ) -> (Value, StatusCode) {
         let app = test::init_service(create_app!(&self.0, true)).await;
 
-        let req = test::TestRequest::post()
+        let mut req = test::TestRequest::post()
             .uri(url.as_ref())
-            .set_payload(body.as_ref().to_string())
-            .insert_header(("content-type", "application/json"))
-            .to_request();
+            .set_payload(body);
+        for header in headers {
+            req = req.insert_header(*header);
+        }
+        let req = req.to_request();
         let res = test::call_service(&app, req).await;
         let status_code = res.status();
 
@@ -55,6 +86,52 @@ impl Service {
         (response, status_code)
     }
 
+    /// Send a get request accepting the `accept` media type, and returns the raw body.
+    pub async fn get_accept(&self, url: impl AsRef<str>, accept: &str) -> (Vec<u8>, StatusCode) {
+        let app = test::init_service(create_app!(&self.0, true)).await;
+
+        let req = test::TestRequest::get()
+            .uri(url.as_ref())
+            .insert_header(("accept", accept))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        let status_code = res.status();
+
+        let body = test::read_body(res).await;
+        (body.to_vec(), status_code)
+    }
+
+    /// Send a get request with an optional `If-None-Match` header, and returns the `ETag` of the
+    /// response.
+    pub async fn get_if_none_match(
+        &self,
+        url: impl AsRef<str>,
+        etag: Option<&str>,
+    ) -> (Option<String>, StatusCode) {
+        let (headers, status_code) = self.get_headers(url, etag).await;
+        let etag = headers
+            .get("etag")
+            .map(|etag| etag.to_str().unwrap().to_string());
+        (etag, status_code)
+    }
+
+    /// Send a get request with an optional `If-None-Match` header, and returns the headers of the
+    /// response.
+    pub async fn get_headers(
+        &self,
+        url: impl AsRef<str>,
+        etag: Option<&str>,
+    ) -> (HeaderMap, StatusCode) {
+        let app = test::init_service(create_app!(&self.0, true)).await;
+
+        let mut req = test::TestRequest::get().uri(url.as_ref());
+        if let Some(etag) = etag {
+            req = req.insert_header(("if-none-match", etag));
+        }
+        let res = test::call_service(&app, req.to_request()).await;
+        (res.headers().clone(), res.status())
+    }
+
     pub async fn put(&self, url: impl AsRef<str>, body: Value) -> (Value, StatusCode) {
         let app = test::init_service(create_app!(&self.0, true)).await;
 
@@ -81,4 +158,37 @@ impl Service {
         let response = serde_json::from_slice(&body).unwrap_or_default();
         (response, status_code)
     }
+
+    /// Opens a WebSocket, sends all the `messages` and returns the frames received in response.
+    pub async fn websocket(
+        &self,
+        url: impl AsRef<str>,
+        messages: Vec<Message>,
+    ) -> (Vec<Frame>, StatusCode) {
+        let app = test::init_service(create_app!(&self.0, true)).await;
+
+        let mut codec = Codec::new().client_mode();
+        let mut payload = BytesMut::new();
+        for message in messages {
+            codec.encode(message, &mut payload).unwrap();
+        }
+
+        let req = test::TestRequest::get()
+            .uri(url.as_ref())
+            .insert_header(("upgrade", "websocket"))
+            .insert_header(("connection", "upgrade"))
+            .insert_header(("sec-websocket-version", "13"))
+            .insert_header(("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="))
+            .set_payload(payload.freeze())
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        let status_code = res.status();
+
+        let mut body = BytesMut::from(test::read_body(res).await.as_ref());
+        let mut frames = Vec::new();
+        while let Ok(Some(frame)) = codec.decode(&mut body) {
+            frames.push(frame);
+        }
+        (frames, status_code)
+    }
 }
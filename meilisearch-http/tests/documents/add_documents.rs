@@ -55,7 +55,7 @@ async fn add_documents_test_no_content_types() {
     assert_eq!(response, json!({ "updateId": 0 }));
 }
 
-/// any other content-type is must be refused
+/// any other content-type is must be refused, with the list of the accepted ones
 #[actix_rt::test]
 async fn add_documents_test_bad_content_types() {
     let document = json!([
@@ -75,8 +75,115 @@ async fn add_documents_test_bad_content_types() {
     let res = test::call_service(&app, req).await;
     let status_code = res.status();
     let body = test::read_body(res).await;
-    assert_eq!(status_code, 405);
-    assert!(body.is_empty());
+    let response: Value = serde_json::from_slice(&body).unwrap_or_default();
+    assert_eq!(status_code, 415);
+    assert_eq!(response["errorCode"], "unsupported_media_type");
+    assert_eq!(
+        response["message"],
+        "The Content-Type `text/plain` is not supported, the accepted ones are: application/json, application/x-ndjson, text/csv."
+    );
+}
+
+#[actix_rt::test]
+async fn add_csv_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = "id:number,title,price:number,available:boolean\n\
+                     1,red shirt,12.5,true\n\
+                     2,\"blue, shirt\",,false\n";
+    let (response, code) = server
+        .service
+        .post_content("/indexes/test/documents", documents, "text/csv")
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_update_id(0).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+
+    let (response, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "id": 2, "title": "blue, shirt", "price": null, "available": false })
+    );
+}
+
+#[actix_rt::test]
+async fn add_csv_documents_with_delimiter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = "id:number;title\n1;red, shirt\n";
+    let (response, code) = server
+        .service
+        .post_content(
+            "/indexes/test/documents?csvDelimiter=%3B",
+            documents,
+            "text/csv",
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_update_id(0).await;
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(response, json!({ "id": 1, "title": "red, shirt" }));
+
+    let (response, code) = server
+        .service
+        .post_content(
+            "/indexes/test/documents?csvDelimiter=%3B%3B",
+            documents,
+            "text/csv",
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn add_invalid_csv_documents() {
+    let server = Server::new().await;
+
+    let documents = "id:number,title\n1,red shirt\ntwo,blue shirt\n";
+    let (response, code) = server
+        .service
+        .post_content("/indexes/test/documents", documents, "text/csv")
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_request");
+    assert_eq!(
+        response["message"],
+        "Invalid value `two` at line 3 in column `id`, expected a number."
+    );
+}
+
+#[actix_rt::test]
+async fn add_ndjson_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents =
+        "{ \"id\": 1, \"title\": \"red shirt\" }\n{ \"id\": 2, \"title\": \"blue shirt\" }\n";
+    let (response, code) = server
+        .service
+        .post_content("/indexes/test/documents", documents, "application/x-ndjson")
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_update_id(0).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+    assert_eq!(response["type"]["number"], 2);
+
+    let (response, code) = server
+        .service
+        .post_content(
+            "/indexes/test/documents",
+            "{ \"id\": 3 }\n[1, 2]\n",
+            "application/x-ndjson",
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_request");
 }
 
 #[actix_rt::test]
@@ -367,7 +474,7 @@ async fn add_larger_dataset() {
         })
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 77);
+    assert_eq!(response["results"].as_array().unwrap().len(), 77);
 }
 
 #[actix_rt::test]
@@ -388,7 +495,7 @@ async fn update_larger_dataset() {
         })
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 77);
+    assert_eq!(response["results"].as_array().unwrap().len(), 77);
 }
 
 #[actix_rt::test]
@@ -426,3 +533,103 @@ async fn update_documents_bad_primary_key() {
     assert_eq!(code, 200);
     assert_eq!(response["status"], "failed");
 }
+
+fn gzip(content: &[u8]) -> Vec<u8> {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[actix_rt::test]
+async fn add_compressed_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([{ "id": 1, "title": "red shirt" }, { "id": 2, "title": "blue shirt" }]);
+    let (response, code) = server
+        .service
+        .post_bytes(
+            "/indexes/test/documents",
+            gzip(documents.to_string().as_bytes()),
+            &[
+                ("content-type", "application/json"),
+                ("content-encoding", "gzip"),
+            ],
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = index.wait_update_id(0).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+    assert_eq!(response["type"]["number"], 2);
+
+    let documents = "id:number,title\n3,green hat\n";
+    let (response, code) = server
+        .service
+        .post_bytes(
+            "/indexes/test/documents",
+            gzip(documents.as_bytes()),
+            &[("content-type", "text/csv"), ("content-encoding", "gzip")],
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_update_id(1).await;
+
+    let (response, code) = index.get_document(3, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(response, json!({ "id": 3, "title": "green hat" }));
+}
+
+#[actix_rt::test]
+async fn add_documents_bad_content_encoding() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post_bytes(
+            "/indexes/test/documents",
+            b"[]".to_vec(),
+            &[("content-encoding", "compress")],
+        )
+        .await;
+    assert_eq!(code, 415, "{}", response);
+    assert_eq!(response["errorCode"], "unsupported_media_type");
+
+    // The payload is not what it claims to be.
+    let (response, code) = server
+        .service
+        .post_bytes(
+            "/indexes/test/documents",
+            br#"[{ "id": 1 }]"#.to_vec(),
+            &[("content-encoding", "gzip")],
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_request");
+}
+
+/// The payload size limit applies to the decompressed documents.
+#[actix_rt::test]
+async fn add_documents_decompressed_too_large() {
+    let server = Server::new().await;
+
+    let mut documents = b"[".to_vec();
+    documents.resize(11 * 1024 * 1024, b' ');
+    documents.push(b']');
+    let documents = gzip(&documents);
+    assert!(documents.len() < 1024 * 1024);
+
+    let (response, code) = server
+        .service
+        .post_bytes(
+            "/indexes/test/documents",
+            documents,
+            &[("content-encoding", "gzip")],
+        )
+        .await;
+    assert_eq!(code, 413, "{}", response);
+    assert_eq!(response["errorCode"], "payload_too_large");
+}
@@ -62,7 +62,7 @@ async fn clear_all_documents() {
         .get_all_documents(GetAllDocumentsOptions::default())
         .await;
     assert_eq!(code, 200);
-    assert!(response.as_array().unwrap().is_empty());
+    assert!(response["results"].as_array().unwrap().is_empty());
 }
 
 #[actix_rt::test]
@@ -79,7 +79,7 @@ async fn clear_all_documents_empty_index() {
         .get_all_documents(GetAllDocumentsOptions::default())
         .await;
     assert_eq!(code, 200);
-    assert!(response.as_array().unwrap().is_empty());
+    assert!(response["results"].as_array().unwrap().is_empty());
 }
 
 #[actix_rt::test]
@@ -103,8 +103,8 @@ async fn delete_batch() {
         .get_all_documents(GetAllDocumentsOptions::default())
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 1);
-    assert_eq!(response.as_array().unwrap()[0]["id"], 3);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"].as_array().unwrap()[0]["id"], 3);
 }
 
 #[actix_rt::test]
@@ -121,5 +121,68 @@ async fn delete_no_document_batch() {
         .get_all_documents(GetAllDocumentsOptions::default())
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 3);
+    assert_eq!(response["results"].as_array().unwrap().len(), 3);
+}
+
+#[actix_rt::test]
+async fn delete_documents_by_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["tenant", "expired"] }))
+        .await;
+    index.wait_update_id(0).await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "tenant": "a", "expired": true },
+                { "id": 1, "tenant": "a", "expired": false },
+                { "id": 2, "tenant": "b", "expired": true },
+                { "id": 3, "tenant": "b", "expired": false },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index.delete_by_filter(json!("tenant = a")).await;
+    assert_eq!(code, 202, "{}", response);
+    let update = index.wait_update_id(2).await;
+    assert_eq!(update["status"], "processed", "{}", update);
+    assert_eq!(
+        update["type"],
+        json!({ "name": "DocumentsDeletion", "number": 2 })
+    );
+
+    let (response, code) = index.delete_by_filter(json!(["expired = true"])).await;
+    assert_eq!(code, 202, "{}", response);
+    let update = index.wait_update_id(3).await;
+    assert_eq!(update["type"]["number"], 1, "{}", update);
+
+    let (response, _) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(
+        response["results"],
+        json!([{ "id": 3, "tenant": "b", "expired": false }])
+    );
+}
+
+#[actix_rt::test]
+async fn delete_documents_by_invalid_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 0, "tenant": "a" }]), None)
+        .await;
+    index.wait_update_id(0).await;
+
+    let (response, code) = index.delete_by_filter(json!("tenant = a")).await;
+    assert_eq!(code, 202, "{}", response);
+    let update = index.wait_update_id(1).await;
+    assert_eq!(update["status"], "failed", "{}", update);
+    assert_eq!(update["errorCode"], "invalid_filter");
+
+    let (_response, code) = index.get_document(0, None).await;
+    assert_eq!(code, 200);
 }
@@ -0,0 +1,191 @@
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+fn document_schema(on_invalid: &str) -> Value {
+    json!({
+        "schema": {
+            "type": "object",
+            "required": ["id", "title"],
+            "properties": {
+                "id": { "type": "integer" },
+                "title": { "type": "string" },
+                "price": { "type": "number", "minimum": 0 },
+            }
+        },
+        "onInvalid": on_invalid,
+    })
+}
+
+fn documents() -> Value {
+    json!([
+        { "id": 1, "title": "shirt", "price": 10 },
+        { "id": 2, "price": 5 },
+        { "id": 3, "title": "hat", "price": -1 },
+        { "id": 4, "title": "scarf" },
+    ])
+}
+
+#[actix_rt::test]
+async fn fail_update_with_invalid_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let (_, code) = index.update_document_schema(document_schema("fail")).await;
+    assert_eq!(code, 202);
+    index.wait_update_id(0).await;
+
+    index.add_documents(documents(), None).await;
+    let response = index.wait_update_id(1).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["errorCode"], "document_schema_violation");
+    let message = response["message"].as_str().unwrap();
+    assert!(message.contains("document `2`, `/title` breaks `/required`"));
+    assert!(message.contains("document `3`, `/price` breaks `/properties/price/minimum`"));
+
+    let (response, code) = index.get_all_documents(Default::default()).await;
+    assert_eq!(code, 200);
+    assert_eq!(response["total"], 0);
+}
+
+#[actix_rt::test]
+async fn skip_invalid_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.update_document_schema(document_schema("skip")).await;
+    index.wait_update_id(0).await;
+
+    index.add_documents(documents(), None).await;
+    let response = index.wait_update_id(1).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+    assert_eq!(response["type"]["number"], 2);
+    assert_eq!(
+        response["type"]["invalidDocuments"],
+        json!({
+            "count": 2,
+            "documents": [
+                {
+                    "documentId": "2",
+                    "errors": [{ "instancePath": "/title", "schemaPath": "/required" }]
+                },
+                {
+                    "documentId": "3",
+                    "errors": [{
+                        "instancePath": "/price",
+                        "schemaPath": "/properties/price/minimum"
+                    }]
+                },
+            ]
+        })
+    );
+
+    let (response, code) = index.get_all_documents(Default::default()).await;
+    assert_eq!(code, 200);
+    let ids: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|document| document["id"].clone())
+        .collect();
+    assert_eq!(ids, vec![json!(1), json!(4)]);
+}
+
+#[actix_rt::test]
+async fn valid_documents_are_not_reported() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.update_document_schema(document_schema("skip")).await;
+    index.wait_update_id(0).await;
+
+    index
+        .add_documents(json!([{ "id": 1, "title": "shirt" }]), None)
+        .await;
+    let response = index.wait_update_id(1).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+    assert_eq!(
+        response["type"],
+        json!({ "name": "DocumentsAddition", "number": 1 })
+    );
+}
+
+#[actix_rt::test]
+async fn reset_document_schema() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.update_document_schema(document_schema("fail")).await;
+    index.wait_update_id(0).await;
+
+    let (response, code) = index.get_document_schema().await;
+    assert_eq!(code, 200);
+    assert_eq!(response, document_schema("fail"));
+
+    index.update_document_schema(Value::Null).await;
+    index.wait_update_id(1).await;
+    let (response, _) = index.get_document_schema().await;
+    assert_eq!(response, Value::Null);
+
+    index.add_documents(documents(), None).await;
+    let response = index.wait_update_id(2).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+    assert_eq!(response["type"]["number"], 4);
+}
+
+#[actix_rt::test]
+async fn invalid_document_schema() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (_, code) = index
+        .update_document_schema(json!({ "schema": { "oneOf": [] } }))
+        .await;
+    assert_eq!(code, 400);
+
+    index
+        .update_document_schema(
+            json!({ "schema": { "properties": { "sku": { "pattern": "(" } } } }),
+        )
+        .await;
+    let response = index.wait_update_id(0).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["errorCode"], "invalid_document_schema");
+}
+
+#[actix_rt::test]
+async fn validate_partial_updates_merged_with_stored_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.update_document_schema(document_schema("fail")).await;
+    index.wait_update_id(0).await;
+
+    index
+        .add_documents(json!([{ "id": 1, "title": "shirt", "price": 10 }]), None)
+        .await;
+    let response = index.wait_update_id(1).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+
+    // The title of the stored document satisfies the schema.
+    index
+        .update_documents(json!([{ "id": 1, "price": 12 }]), None)
+        .await;
+    let response = index.wait_update_id(2).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({ "id": 1, "title": "shirt", "price": 12 }));
+
+    // A new document is validated as it was sent.
+    index
+        .update_documents(json!([{ "id": 2, "price": 5 }]), None)
+        .await;
+    let response = index.wait_update_id(3).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    let message = response["message"].as_str().unwrap();
+    assert!(message.contains("document `2`, `/title` breaks `/required`"));
+
+    // The merged document is validated, not only the fields of the update.
+    index
+        .update_documents(json!([{ "id": 1, "title": 3 }]), None)
+        .await;
+    let response = index.wait_update_id(4).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+}
@@ -0,0 +1,88 @@
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn export_documents_ndjson() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "name": "zero" },
+                { "id": 1, "name": "one", "tags": ["a", "b"] },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_update_id(0).await;
+
+    let (body, code) = index.export_documents("", "application/x-ndjson").await;
+    assert_eq!(code, 200);
+    let documents: Vec<Value> = String::from_utf8(body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(
+        documents,
+        vec![
+            json!({ "id": 0, "name": "zero" }),
+            json!({ "id": 1, "name": "one", "tags": ["a", "b"] }),
+        ]
+    );
+}
+
+#[actix_rt::test]
+async fn export_documents_csv_with_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["tenant"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "tenant": "a", "name": "zero" },
+                { "id": 1, "tenant": "b", "name": "one" },
+                { "id": 2, "tenant": "a" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_update_id(1).await;
+
+    let (body, code) = index
+        .export_documents("filter=tenant%20%3D%20a", "text/csv")
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        String::from_utf8(body).unwrap(),
+        "id,tenant,name\n0,a,zero\n2,a,\n"
+    );
+}
+
+#[actix_rt::test]
+async fn export_documents_invalid_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 0 }]), None).await;
+    index.wait_update_id(0).await;
+
+    let (body, code) = index
+        .export_documents("filter=tenant%20%3D%20a", "application/x-ndjson")
+        .await;
+    assert_eq!(code, 400);
+    let response: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response["errorCode"], "invalid_filter");
+}
+
+#[actix_rt::test]
+async fn export_unexisting_index() {
+    let server = Server::new().await;
+    let (_, code) = server
+        .index("test")
+        .export_documents("", "application/x-ndjson")
+        .await;
+    assert_eq!(code, 404);
+}
@@ -67,7 +67,7 @@ async fn get_no_documents() {
         .get_all_documents(GetAllDocumentsOptions::default())
         .await;
     assert_eq!(code, 200);
-    assert!(response.as_array().unwrap().is_empty());
+    assert!(response["results"].as_array().unwrap().is_empty());
 }
 
 #[actix_rt::test]
@@ -80,7 +80,7 @@ async fn get_all_documents_no_options() {
         .get_all_documents(GetAllDocumentsOptions::default())
         .await;
     assert_eq!(code, 200);
-    let arr = response.as_array().unwrap();
+    let arr = response["results"].as_array().unwrap();
     assert_eq!(arr.len(), 20);
     let first = serde_json::json!({
         "id":0,
@@ -116,8 +116,8 @@ async fn test_get_all_documents_limit() {
         })
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 5);
-    assert_eq!(response.as_array().unwrap()[0]["id"], 0);
+    assert_eq!(response["results"].as_array().unwrap().len(), 5);
+    assert_eq!(response["results"].as_array().unwrap()[0]["id"], 0);
 }
 
 #[actix_rt::test]
@@ -133,8 +133,8 @@ async fn test_get_all_documents_offset() {
         })
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 20);
-    assert_eq!(response.as_array().unwrap()[0]["id"], 13);
+    assert_eq!(response["results"].as_array().unwrap().len(), 20);
+    assert_eq!(response["results"].as_array().unwrap()[0]["id"], 13);
 }
 
 #[actix_rt::test]
@@ -150,16 +150,16 @@ async fn test_get_all_documents_attributes_to_retrieve() {
         })
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 20);
+    assert_eq!(response["results"].as_array().unwrap().len(), 20);
     assert_eq!(
-        response.as_array().unwrap()[0]
+        response["results"].as_array().unwrap()[0]
             .as_object()
             .unwrap()
             .keys()
             .count(),
         1
     );
-    assert!(response.as_array().unwrap()[0]
+    assert!(response["results"].as_array().unwrap()[0]
         .as_object()
         .unwrap()
         .get("name")
@@ -172,9 +172,9 @@ async fn test_get_all_documents_attributes_to_retrieve() {
         })
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 20);
+    assert_eq!(response["results"].as_array().unwrap().len(), 20);
     assert_eq!(
-        response.as_array().unwrap()[0]
+        response["results"].as_array().unwrap()[0]
             .as_object()
             .unwrap()
             .keys()
@@ -189,9 +189,9 @@ async fn test_get_all_documents_attributes_to_retrieve() {
         })
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 20);
+    assert_eq!(response["results"].as_array().unwrap().len(), 20);
     assert_eq!(
-        response.as_array().unwrap()[0]
+        response["results"].as_array().unwrap()[0]
             .as_object()
             .unwrap()
             .keys()
@@ -206,9 +206,9 @@ async fn test_get_all_documents_attributes_to_retrieve() {
         })
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 20);
+    assert_eq!(response["results"].as_array().unwrap().len(), 20);
     assert_eq!(
-        response.as_array().unwrap()[0]
+        response["results"].as_array().unwrap()[0]
             .as_object()
             .unwrap()
             .keys()
@@ -223,9 +223,9 @@ async fn test_get_all_documents_attributes_to_retrieve() {
         })
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 20);
+    assert_eq!(response["results"].as_array().unwrap().len(), 20);
     assert_eq!(
-        response.as_array().unwrap()[0]
+        response["results"].as_array().unwrap()[0]
             .as_object()
             .unwrap()
             .keys()
@@ -240,9 +240,9 @@ async fn test_get_all_documents_attributes_to_retrieve() {
         })
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 20);
+    assert_eq!(response["results"].as_array().unwrap().len(), 20);
     assert_eq!(
-        response.as_array().unwrap()[0]
+        response["results"].as_array().unwrap()[0]
             .as_object()
             .unwrap()
             .keys()
@@ -264,16 +264,16 @@ async fn get_documents_displayed_attributes() {
         .get_all_documents(GetAllDocumentsOptions::default())
         .await;
     assert_eq!(code, 200);
-    assert_eq!(response.as_array().unwrap().len(), 20);
+    assert_eq!(response["results"].as_array().unwrap().len(), 20);
     assert_eq!(
-        response.as_array().unwrap()[0]
+        response["results"].as_array().unwrap()[0]
             .as_object()
             .unwrap()
             .keys()
             .count(),
         1
     );
-    assert!(response.as_array().unwrap()[0]
+    assert!(response["results"].as_array().unwrap()[0]
         .as_object()
         .unwrap()
         .get("gender")
@@ -284,3 +284,209 @@ async fn get_documents_displayed_attributes() {
     assert_eq!(response.as_object().unwrap().keys().count(), 1);
     assert!(response.as_object().unwrap().get("gender").is_some());
 }
+
+#[actix_rt::test]
+async fn fetch_documents_by_ids() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "tenant": "a" },
+                { "id": 1, "tenant": "b" },
+                { "id": 2, "tenant": "a" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_update_id(0).await;
+
+    let (response, code) = index
+        .fetch_documents(json!({ "ids": [2, "0", 42, 2] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "results": [{ "id": 2, "tenant": "a" }, { "id": 0, "tenant": "a" }],
+            "offset": 0,
+            "limit": 20,
+            "total": 2,
+            "missing": ["42"],
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn fetch_documents_by_ids_and_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["tenant"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "tenant": "a", "name": "zero" },
+                { "id": 1, "tenant": "b", "name": "one" },
+                { "id": 2, "tenant": "a", "name": "two" },
+                { "id": 3, "tenant": "a", "name": "three" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .fetch_documents(json!({
+            "ids": [3, 1, 2, 0],
+            "filter": "tenant = a",
+            "attributesToRetrieve": ["name"],
+            "offset": 1,
+            "limit": 1,
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({ "results": [{ "name": "two" }], "offset": 1, "limit": 1, "total": 3 })
+    );
+
+    let (response, code) = index
+        .fetch_documents(json!({ "filter": "tenant = b" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["results"],
+        json!([{ "id": 1, "tenant": "b", "name": "one" }])
+    );
+}
+
+#[actix_rt::test]
+async fn fetch_documents_invalid_id() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 0 }]), None).await;
+    index.wait_update_id(0).await;
+
+    let (response, code) = index.fetch_documents(json!({ "ids": [[0]] })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn get_documents_envelope() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.load_test_set().await;
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions {
+            offset: Some(70),
+            limit: Some(5),
+            attributes_to_retrieve: Some(vec!["id"]),
+        })
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["offset"], 70);
+    assert_eq!(response["limit"], 5);
+    assert_eq!(response["total"], 77);
+    assert_eq!(response["results"].as_array().unwrap().len(), 5);
+    assert!(response["next"].is_string());
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions {
+            offset: Some(75),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+    assert!(response.get("next").is_none());
+}
+
+#[actix_rt::test]
+async fn get_documents_with_cursor() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.load_test_set().await;
+
+    let mut ids = Vec::new();
+    let mut url = "/indexes/test/documents?limit=30&attributesToRetrieve=id".to_string();
+    loop {
+        let (response, code) = index.service.get(&url).await;
+        assert_eq!(code, 200, "{}", response);
+        assert_eq!(response["total"], 77);
+        for document in response["results"].as_array().unwrap() {
+            ids.push(document["id"].as_u64().unwrap());
+        }
+        match response["next"].as_str() {
+            Some(next) => {
+                url = format!(
+                    "/indexes/test/documents?limit=30&attributesToRetrieve=id&cursor={}",
+                    next
+                )
+            }
+            None => break,
+        }
+    }
+    assert_eq!(ids, (0..77).collect::<Vec<_>>());
+}
+
+#[actix_rt::test]
+async fn get_documents_with_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["tenant"] }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "tenant": "a" },
+                { "id": 1, "tenant": "b" },
+                { "id": 2, "tenant": "a" },
+                { "id": 3, "tenant": "a" },
+            ]),
+            None,
+        )
+        .await;
+    index.wait_update_id(1).await;
+
+    let url = "/indexes/test/documents?filter=tenant%20%3D%20a&limit=2";
+    let (response, code) = index.service.get(url).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["total"], 3);
+    assert_eq!(
+        response["results"],
+        json!([{ "id": 0, "tenant": "a" }, { "id": 2, "tenant": "a" }])
+    );
+
+    let url = format!("{}&cursor={}", url, response["next"].as_str().unwrap());
+    let (response, code) = index.service.get(url).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"], json!([{ "id": 3, "tenant": "a" }]));
+    assert!(response.get("next").is_none());
+}
+
+#[actix_rt::test]
+async fn get_documents_bad_cursor() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(json!([{ "id": 0 }]), None).await;
+    index.wait_update_id(0).await;
+
+    let (response, code) = index
+        .service
+        .get("/indexes/test/documents?cursor=nope")
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_parameter");
+
+    let (response, code) = index
+        .service
+        .get("/indexes/test/documents?offset=0&cursor=00000000")
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
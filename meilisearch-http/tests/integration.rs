@@ -1,6 +1,7 @@
 mod common;
 mod documents;
 mod index;
+mod rules;
 mod search;
 mod settings;
 mod snapshot;
@@ -0,0 +1,223 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn crud_rules() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+
+    let (response, code) = index.rules().await;
+    assert_eq!(code, 200);
+    assert_eq!(response, json!([]));
+
+    let rule = json!({
+        "conditions": { "query": { "exact": "shirt" } },
+        "consequences": { "pin": [{ "id": "4", "position": 0 }], "hide": ["2"] }
+    });
+    let (response, code) = index.put_rule("promo", rule).await;
+    assert_eq!(code, 202, "{}", response);
+    assert_eq!(response["updateId"], 0);
+    let response = index.wait_update_id(0).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+    assert_eq!(response["type"], json!({ "name": "Rule", "id": "promo" }));
+
+    let (response, code) = index.rule("promo").await;
+    assert_eq!(code, 200);
+    assert_eq!(response["conditions"]["query"]["exact"], "shirt");
+
+    let (response, code) = index.rules().await;
+    assert_eq!(code, 200);
+    assert_eq!(response.as_array().unwrap().len(), 1);
+
+    let (_, code) = index.delete_rule("promo").await;
+    assert_eq!(code, 202);
+    let response = index.wait_update_id(1).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+
+    let (response, code) = index.rule("promo").await;
+    assert_eq!(code, 404);
+    assert_eq!(response["errorCode"], "rule_not_found");
+
+    let (_, code) = index.delete_rule("promo").await;
+    assert_eq!(code, 202);
+    let response = index.wait_update_id(2).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["errorCode"], "rule_not_found");
+}
+
+#[actix_rt::test]
+async fn invalid_rule() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+
+    let rule = json!({ "conditions": { "query": { "regex": "(shirt" } } });
+    let (_, code) = index.put_rule("broken", rule).await;
+    assert_eq!(code, 202);
+    let response = index.wait_update_id(0).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["errorCode"], "invalid_rule");
+
+    let rule = json!({
+        "conditions": {
+            "validFrom": "2021-07-01T00:00:00Z",
+            "validUntil": "2021-06-01T00:00:00Z"
+        }
+    });
+    index.put_rule("broken", rule).await;
+    let response = index.wait_update_id(1).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["errorCode"], "invalid_rule");
+
+    let (response, _) = index.rules().await;
+    assert_eq!(response, json!([]));
+}
+
+#[actix_rt::test]
+async fn rules_are_applied_in_order_with_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "title": "shoes", "price": 10 }]), None)
+        .await;
+    // The filter of the rule is only valid once the settings update before it is processed.
+    index
+        .update_settings(json!({ "filterableAttributes": ["price"] }))
+        .await;
+    let rule = json!({ "consequences": { "filter": "price < 20" } });
+    let (_, code) = index.put_rule("cheap", rule).await;
+    assert_eq!(code, 202);
+
+    let response = index.wait_update_id(2).await;
+    assert_eq!(response["status"], "processed", "{}", response);
+}
+
+#[actix_rt::test]
+async fn pin_and_hide_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "blue shirt" },
+        { "id": 2, "title": "red shirt" },
+        { "id": 3, "title": "green shirt" },
+        { "id": 4, "title": "shirt and tie" },
+        { "id": 5, "title": "pants" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    let rule = json!({
+        "conditions": { "query": { "exact": "shirt" } },
+        "consequences": { "pin": [{ "id": "5", "position": 0 }], "hide": ["2"] }
+    });
+    let (_, code) = index.put_rule("promo", rule).await;
+    assert_eq!(code, 202);
+    index.wait_update_id(1).await;
+
+    let (response, code) = index.search_post(json!({ "q": "shirt" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["appliedRules"], json!(["promo"]));
+    assert_eq!(response["nbHits"], 4);
+
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits[0]["id"], 5);
+    assert_eq!(hits.len(), 4);
+    assert!(hits.iter().all(|hit| hit["id"] != 2));
+
+    let (response, code) = index.search_post(json!({ "q": "pants" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("appliedRules").is_none());
+}
+
+#[actix_rt::test]
+async fn rewrite_query() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "shoes", "price": 10 },
+        { "id": 2, "title": "shoes", "price": 90 },
+        { "id": 3, "title": "hat", "price": 5 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["price"] }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let rule = json!({
+        "conditions": { "query": { "contains": "cheap" } },
+        "consequences": {
+            "removeWords": ["cheap"],
+            "filter": "price < 20",
+            "facetsDistribution": ["price"]
+        }
+    });
+    let (_, code) = index.put_rule("cheap", rule).await;
+    assert_eq!(code, 202);
+
+    let rule = json!({ "consequences": { "removeWords": ["buy", "shop"] } });
+    let (_, code) = index.put_rule("shop", rule).await;
+    assert_eq!(code, 202);
+    index.wait_update_id(3).await;
+
+    let (response, code) = index.search_post(json!({ "q": "buy cheap shoes" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["query"], "buy cheap shoes");
+    assert_eq!(response["rewrittenQuery"], "shoes");
+    assert_eq!(response["nbHits"], 1);
+    assert_eq!(response["hits"][0]["id"], 1);
+    assert!(response["facetsDistribution"].get("price").is_some());
+    assert_eq!(
+        response["rewrites"],
+        json!([
+            {
+                "rule": "cheap",
+                "removedWords": ["cheap"],
+                "addedFilter": "price < 20",
+                "addedFacets": ["price"]
+            },
+            { "rule": "shop", "removedWords": ["buy"] },
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn rule_filter_with_structured_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "shoes", "price": 10, "color": "red" },
+        { "id": 2, "title": "shoes", "price": 90, "color": "red" },
+        { "id": 3, "title": "shoes", "price": 5, "color": "blue" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["price", "color"] }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let rule = json!({ "consequences": { "filter": "price < 20" } });
+    let (_, code) = index.put_rule("cheap", rule).await;
+    assert_eq!(code, 202);
+    index.wait_update_id(2).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "shoes",
+            "filter": { "field": "color", "op": "=", "value": "red" }
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 1);
+    assert_eq!(response["hits"][0]["id"], 1);
+}
@@ -0,0 +1,167 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn boost_matching_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "shirt", "in_stock": "false" },
+        { "id": 2, "title": "shirt", "in_stock": "false" },
+        { "id": 3, "title": "shirt", "in_stock": "true" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["in_stock"] }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "boost": [{ "filter": "in_stock = true", "weight": 2.0 }]
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 3);
+    assert_eq!(response["hits"][0]["id"], 3);
+
+    let (response, code) = index
+        .search_post(json!({
+            "boost": [{ "filter": "in_stock = true", "weight": 2.0 }],
+            "offset": 1,
+            "limit": 1
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+    assert_ne!(response["hits"][0]["id"], 3);
+}
+
+#[actix_rt::test]
+async fn boost_within_ranking_buckets() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "price": 10, "in_stock": "false" },
+        { "id": 2, "price": 30, "in_stock": "false" },
+        { "id": 3, "price": 30, "in_stock": "true" },
+        { "id": 4, "price": 10, "in_stock": "true" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["price", "in_stock"],
+            "rankingRules": ["desc(price)"]
+        }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "boost": [{ "filter": "in_stock = true", "weight": 2.0 }],
+            "attributesToRetrieve": ["id"]
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"],
+        json!([{ "id": 3 }, { "id": 2 }, { "id": 4 }, { "id": 1 }])
+    );
+}
+
+#[actix_rt::test]
+async fn boost_with_group_by() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "in_stock": "true" }]), None)
+        .await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["in_stock"] }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "boost": [{ "filter": "in_stock = true", "weight": 2.0 }],
+            "groupBy": { "field": "in_stock" }
+        }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn boost_with_textual_ranking() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(
+            json!([{ "id": 1, "title": "shirt", "in_stock": "true" }]),
+            None,
+        )
+        .await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["in_stock"] }))
+        .await;
+    index.wait_update_id(1).await;
+
+    // The buckets of the textual ranking rules can't be known.
+    let (response, code) = index
+        .search_post(json!({
+            "q": "shirt",
+            "boost": [{ "filter": "in_stock = true", "weight": 2.0 }]
+        }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_parameter");
+
+    index
+        .update_settings(json!({ "rankingRules": ["asc(id)"] }))
+        .await;
+    index.wait_update_id(2).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "shirt",
+            "boost": [{ "filter": "in_stock = true", "weight": 2.0 }]
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 1);
+}
+
+#[actix_rt::test]
+async fn invalid_boost_weight() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "in_stock": "true" }]), None)
+        .await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["in_stock"] }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "boost": [{ "filter": "in_stock = true", "weight": -1 }] }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
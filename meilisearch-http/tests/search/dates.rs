@@ -0,0 +1,161 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn filter_and_sort_on_dates() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (_, code) = index
+        .update_settings(json!({
+            "dateAttributes": ["releaseDate"],
+            "rankingRules": ["desc(releaseDate)", "words", "typo", "proximity", "attribute", "exactness"]
+        }))
+        .await;
+    assert_eq!(code, 202);
+    index.wait_update_id(0).await;
+
+    let documents = json!([
+        { "id": 1, "releaseDate": "2020-06-01" },
+        { "id": 2, "releaseDate": "2021-03-04T10:00:00Z" },
+        { "id": 3, "releaseDate": "2021-01-15T12:00:00+02:00" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "filter": "releaseDate > 2021-01-01" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 2);
+    assert_eq!(response["hits"][0]["id"], 2);
+    assert_eq!(response["hits"][0]["releaseDate"], "2021-03-04T10:00:00Z");
+    assert_eq!(
+        response["hits"][1]["releaseDate"],
+        "2021-01-15T12:00:00+02:00"
+    );
+
+    let (response, code) = index
+        .search_post(json!({ "filter": "releaseDate < now-1d" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 3);
+
+    let (response, code) = index
+        .search_post(json!({
+            "filter": { "field": "releaseDate", "op": "<", "value": "2021-01-01" }
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 1);
+    assert_eq!(response["hits"][0]["id"], 1);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["releaseDate"], "2020-06-01");
+    assert!(response.get("_dates").is_none(), "{}", response);
+}
+
+#[actix_rt::test]
+async fn dates_round_trip() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "dateAttributes": ["releaseDate"] }))
+        .await;
+    index.wait_update_id(0).await;
+
+    let documents = json!([
+        { "id": 1, "releaseDate": 1609459200 },
+        { "id": 2, "releaseDate": "2021-01-01T01:00:00.250+01:00" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "filter": "releaseDate = 2021-01-01" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"],
+        json!([
+            { "id": 1, "releaseDate": 1609459200 },
+            { "id": 2, "releaseDate": "2021-01-01T01:00:00.250+01:00" },
+        ])
+    );
+}
+
+#[actix_rt::test]
+async fn relative_date_out_of_range() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "dateAttributes": ["releaseDate"] }))
+        .await;
+    index.wait_update_id(0).await;
+
+    index
+        .add_documents(json!([{ "id": 1, "releaseDate": "2021-01-01" }]), None)
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "filter": "releaseDate > now-99999999999999w" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "invalid_filter");
+
+    let (response, code) = index
+        .search_post(json!({
+            "filter": { "field": "releaseDate", "op": ">", "value": "now-99999999999999w" }
+        }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "invalid_filter");
+}
+
+#[actix_rt::test]
+async fn original_dates_are_reserved() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "dateAttributes": ["releaseDate"] }))
+        .await;
+    index.wait_update_id(0).await;
+
+    index
+        .add_documents(
+            json!([{ "id": 1, "title": "shirt", "releaseDate": "2020-06-01" }]),
+            None,
+        )
+        .await;
+    let update = index.wait_update_id(1).await;
+    assert_eq!(update["status"], "processed", "{}", update);
+
+    // The original dates are not searched, and all the attributes still are.
+    let (response, code) = index.search_post(json!({ "q": "2020" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 0, "{}", response);
+
+    let (response, code) = index.search_post(json!({ "q": "shirt" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 1, "{}", response);
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["searchableAttributes"], json!(["*"]));
+
+    index
+        .add_documents(
+            json!([{ "id": 2, "title": "hat", "_dates": { "releaseDate": "2021" } }]),
+            None,
+        )
+        .await;
+    let update = index.wait_update_id(2).await;
+    assert_eq!(update["status"], "failed", "{}", update);
+    assert_eq!(update["errorCode"], "bad_request");
+}
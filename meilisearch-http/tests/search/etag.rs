@@ -0,0 +1,150 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn search_not_modified() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "red shirt" },
+        { "id": 2, "title": "blue shirt" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    let url = "/indexes/test/search?q=shirt&limit=10";
+    let (etag, code) = server.service.get_if_none_match(url, None).await;
+    assert_eq!(code, 200);
+    let etag = etag.unwrap();
+
+    let (same, code) = server.service.get_if_none_match(url, Some(&etag)).await;
+    assert_eq!(code, 304);
+    assert_eq!(same.as_deref(), Some(etag.as_str()));
+
+    // The tag depends on the negotiated format, which caches must take into account.
+    for if_none_match in &[None, Some(etag.as_str())] {
+        let (headers, _) = server.service.get_headers(url, *if_none_match).await;
+        assert_eq!(headers.get("vary").unwrap(), "Accept");
+    }
+
+    // The order of the parameters and the defaults don't change the request.
+    let url = "/indexes/test/search?limit=10&q=shirt&offset=0";
+    let (_, code) = server.service.get_if_none_match(url, Some(&etag)).await;
+    assert_eq!(code, 304);
+
+    let url = "/indexes/test/search?q=red";
+    let (other, code) = server.service.get_if_none_match(url, Some(&etag)).await;
+    assert_eq!(code, 200);
+    assert_ne!(other.unwrap(), etag);
+
+    index
+        .add_documents(json!([{ "id": 3, "title": "green shirt" }]), None)
+        .await;
+    index.wait_update_id(1).await;
+
+    let url = "/indexes/test/search?q=shirt&limit=10";
+    let (updated, code) = server.service.get_if_none_match(url, Some(&etag)).await;
+    assert_eq!(code, 200);
+    assert_ne!(updated.unwrap(), etag);
+}
+
+#[actix_rt::test]
+async fn documents_not_modified() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "title": "red shirt" }]), None)
+        .await;
+    index.wait_update_id(0).await;
+
+    for url in &[
+        "/indexes/test/documents/1",
+        "/indexes/test/documents?limit=5",
+    ] {
+        let (etag, code) = server.service.get_if_none_match(url, None).await;
+        assert_eq!(code, 200);
+        let etag = etag.unwrap();
+
+        let (_, code) = server.service.get_if_none_match(url, Some(&etag)).await;
+        assert_eq!(code, 304);
+        let (_, code) = server.service.get_if_none_match(url, Some("*")).await;
+        assert_eq!(code, 304);
+    }
+}
+
+#[actix_rt::test]
+async fn settings_not_modified() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+
+    let url = "/indexes/test/settings";
+    let (etag, code) = server.service.get_if_none_match(url, None).await;
+    assert_eq!(code, 200);
+    let etag = etag.unwrap();
+
+    let (_, code) = server.service.get_if_none_match(url, Some(&etag)).await;
+    assert_eq!(code, 304);
+
+    // Each setting has its own representation.
+    let url = "/indexes/test/settings/stop-words";
+    let (stop_words, code) = server.service.get_if_none_match(url, Some(&etag)).await;
+    assert_eq!(code, 200);
+    let stop_words = stop_words.unwrap();
+
+    index.update_settings(json!({ "stopWords": ["the"] })).await;
+    index.wait_update_id(0).await;
+
+    let (_, code) = server
+        .service
+        .get_if_none_match(url, Some(&stop_words))
+        .await;
+    assert_eq!(code, 200);
+    let url = "/indexes/test/settings";
+    let (_, code) = server.service.get_if_none_match(url, Some(&etag)).await;
+    assert_eq!(code, 200);
+}
+
+#[actix_rt::test]
+async fn relative_dates_not_tagged() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({
+            "dateAttributes": ["releaseDate"],
+            "filterableAttributes": ["releaseDate"]
+        }))
+        .await;
+    index.wait_update_id(0).await;
+
+    index
+        .add_documents(json!([{ "id": 1, "releaseDate": "2021-01-01" }]), None)
+        .await;
+    index.wait_update_id(1).await;
+
+    let url = "/indexes/test/search?filter=releaseDate%20%3C%20now-1d";
+    let (etag, code) = server.service.get_if_none_match(url, None).await;
+    assert_eq!(code, 200);
+    assert!(etag.is_none());
+
+    let url = "/indexes/test/search?filter=releaseDate%20%3C%202022-01-01";
+    let (etag, code) = server.service.get_if_none_match(url, None).await;
+    assert_eq!(code, 200);
+    assert!(etag.is_some());
+
+    let url = "/indexes/test/documents?filter=releaseDate%20%3C%20now-1d";
+    let (etag, code) = server.service.get_if_none_match(url, None).await;
+    assert_eq!(code, 200);
+    assert!(etag.is_none());
+    let (_, code) = server.service.get_if_none_match(url, Some("*")).await;
+    assert_eq!(code, 200);
+
+    let url = "/indexes/test/documents?filter=releaseDate%20%3C%202022-01-01";
+    let (etag, code) = server.service.get_if_none_match(url, None).await;
+    assert_eq!(code, 200);
+    assert!(etag.is_some());
+}
@@ -0,0 +1,82 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn explain_search() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "the red shirt", "price": 10 },
+        { "id": 2, "title": "a red shirt", "price": 30 },
+        { "id": 3, "title": "blue jeans" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["price"],
+            "stopWords": ["the"],
+            "synonyms": { "shirt": ["tee shirt"] },
+            "rankingRules": ["words", "typo", "desc(price)"]
+        }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .explain_search(json!({
+            "q": "the red shirt",
+            "filter": { "or": [
+                { "field": "price", "op": "<", "value": 50 },
+                { "field": "price", "op": "=", "value": 100 },
+            ]}
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    assert_eq!(response["query"], "the red shirt");
+    assert_eq!(response["stopWords"], json!(["the"]));
+    assert_eq!(
+        response["queryWords"],
+        json!([
+            { "word": "red", "prefix": false, "synonyms": [] },
+            { "word": "shirt", "prefix": true, "synonyms": ["tee shirt"] },
+        ])
+    );
+    assert_eq!(
+        response["filter"],
+        json!({ "or": [
+            { "field": "price", "op": "<", "value": 50.0 },
+            { "field": "price", "op": "=", "value": 100.0 },
+        ]})
+    );
+    assert_eq!(response["nbHits"], 2);
+    assert_eq!(
+        response["rankingRules"],
+        json!([
+            { "rule": "words" },
+            { "rule": "typo" },
+            { "rule": "desc(price)", "candidates": 2 },
+        ])
+    );
+    for timing in &[
+        "filteringMs",
+        "rankingMs",
+        "fetchingMs",
+        "formattingMs",
+        "totalMs",
+    ] {
+        assert!(response["timings"][timing].is_number(), "{}", timing);
+    }
+}
+
+#[actix_rt::test]
+async fn explain_search_unexisting_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (response, code) = index.explain_search(json!({ "q": "shirt" })).await;
+    assert_eq!(code, 404, "{}", response);
+}
@@ -0,0 +1,73 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn ranges_and_histogram() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "price": 5 },
+        { "id": 2, "price": 12 },
+        { "id": 3, "price": 18.5 },
+        { "id": 4, "price": 60 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["price"] }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "facetRanges": {
+                "price": [{ "to": 10 }, { "from": 10, "to": 50 }, { "from": 50 }]
+            }
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetRanges"],
+        json!({
+            "price": [
+                { "to": 10.0, "count": 1 },
+                { "from": 10.0, "to": 50.0, "count": 2 },
+                { "from": 50.0, "count": 1 },
+            ]
+        })
+    );
+
+    let (response, code) = index
+        .search_post(json!({ "facetRanges": { "price": { "interval": 10 } } }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetRanges"],
+        json!({
+            "price": [
+                { "from": 0.0, "to": 10.0, "count": 1 },
+                { "from": 10.0, "to": 20.0, "count": 2 },
+                { "from": 60.0, "to": 70.0, "count": 1 },
+            ]
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn ranges_on_non_filterable_attribute() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "price": 5 }]), None)
+        .await;
+    index.wait_update_id(0).await;
+
+    let (_, code) = index
+        .search_post(json!({ "facetRanges": { "price": { "interval": 10 } } }))
+        .await;
+    assert_eq!(code, 400);
+}
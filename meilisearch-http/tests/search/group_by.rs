@@ -0,0 +1,72 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn top_hits_per_group() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "seller": "alice", "title": "blue shirt" },
+        { "id": 2, "seller": "bob", "title": "blue shirt" },
+        { "id": 3, "seller": "alice", "title": "red shirt" },
+        { "id": 4, "seller": "alice", "title": "green shirt" },
+        { "id": 5, "seller": "carol", "title": "yellow shirt" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["seller"] }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "groupBy": { "field": "seller", "size": 2 } }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([]));
+
+    let groups = response["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 3);
+    for group in groups {
+        let total = group["total"].as_u64().unwrap();
+        let hits = group["hits"].as_array().unwrap();
+        assert_eq!(hits.len() as u64, total.min(2));
+        for hit in hits {
+            assert_eq!(hit["seller"], group["value"]);
+        }
+    }
+
+    let alice = groups.iter().find(|g| g["value"] == "alice").unwrap();
+    assert_eq!(alice["total"], 3);
+
+    let (response, code) = index
+        .search_post(json!({
+            "groupBy": { "field": "seller" },
+            "offset": 1,
+            "limit": 1
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let groups = response["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0]["hits"].as_array().unwrap().len(), 1);
+}
+
+#[actix_rt::test]
+async fn group_by_non_filterable_attribute() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "seller": "alice" }]), None)
+        .await;
+    index.wait_update_id(0).await;
+
+    let (_, code) = index
+        .search_post(json!({ "groupBy": { "field": "seller" } }))
+        .await;
+    assert_eq!(code, 400);
+}
@@ -0,0 +1,64 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn distribution_of_selected_path() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "categories.lvl0": "books", "categories.lvl1": "books > fiction" },
+        { "id": 2, "categories.lvl0": "books", "categories.lvl1": "books > comics" },
+        { "id": 3, "categories.lvl0": "music", "categories.lvl1": "music > rock" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    let (_, code) = index
+        .update_settings(json!({
+            "hierarchicalFacets": { "categories": ["categories.lvl0", "categories.lvl1"] }
+        }))
+        .await;
+    assert_eq!(code, 202);
+    index.wait_update_id(1).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200);
+    let filterable = response["filterableAttributes"].as_array().unwrap();
+    assert!(filterable.contains(&json!("categories.lvl0")));
+    assert!(filterable.contains(&json!("categories.lvl1")));
+
+    let (response, code) = index
+        .search_post(json!({ "hierarchicalFacets": { "categories": [] } }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetsDistribution"],
+        json!({ "categories.lvl0": { "books": 2, "music": 1 } })
+    );
+
+    let (response, code) = index
+        .search_post(json!({ "hierarchicalFacets": { "categories": ["books"] } }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["facetsDistribution"],
+        json!({
+            "categories.lvl0": { "books": 2, "music": 1 },
+            "categories.lvl1": { "books > comics": 1, "books > fiction": 1 },
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn unknown_hierarchical_facet() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+
+    let (response, code) = index
+        .search_post(json!({ "hierarchicalFacets": { "categories": [] } }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+}
@@ -1,2 +1,19 @@
 // This modules contains all the test concerning search. Each particular feture of the search
 // should be tested in its own module to isolate tests and keep the tests readable.
+
+mod boost;
+mod dates;
+mod etag;
+mod explain;
+mod facet_ranges;
+mod group_by;
+mod hierarchical_facets;
+mod nested;
+mod pagination;
+mod response_format;
+mod search_defaults;
+mod structured_filter;
+mod timeout;
+mod vectors;
+mod wait_for_update;
+mod websocket;
@@ -0,0 +1,221 @@
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+fn documents() -> Value {
+    json!([
+        {
+            "id": 1,
+            "title": "The Hobbit",
+            "author": { "name": "Tolkien", "born": { "country": "South Africa" } },
+            "specs": { "weight": 300 },
+            "tags": [{ "name": "fantasy" }],
+        },
+        {
+            "id": 2,
+            "title": "Dune",
+            "author": { "name": "Herbert", "born": { "country": "USA" } },
+            "specs": { "weight": 700 },
+            "tags": [],
+        },
+    ])
+}
+
+#[actix_rt::test]
+async fn nested_documents_keep_their_shape() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(documents(), None).await;
+    index.wait_update_id(0).await;
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, documents()[0]);
+
+    let (response, code) = index.search_post(json!({ "q": "tolkien" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([documents()[0]]));
+}
+
+#[actix_rt::test]
+async fn filter_on_nested_fields() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["author.born.country", "specs.weight"] }))
+        .await;
+    index.add_documents(documents(), None).await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "filter": "author.born.country = USA" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0]["id"], 2, "{}", response);
+    assert_eq!(response["nbHits"], 1);
+
+    let (response, code) = index
+        .search_post(json!({ "filter": "specs.weight < 500" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"][0]["id"], 1, "{}", response);
+    assert_eq!(response["nbHits"], 1);
+}
+
+#[actix_rt::test]
+async fn select_nested_fields() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "displayedAttributes": ["id", "author", "specs.weight"] }))
+        .await;
+    index.add_documents(documents(), None).await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "id": 2,
+            "author": { "name": "Herbert", "born": { "country": "USA" } },
+            "specs": { "weight": 700 },
+        })
+    );
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "herbert",
+            "attributesToRetrieve": ["author.name"],
+            "attributesToHighlight": ["author"],
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["hits"][0],
+        json!({
+            "author": { "name": "Herbert" },
+            "_formatted": {
+                "author": { "name": "<em>Herbert</em>", "born": { "country": "USA" } },
+            },
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn conflicting_nested_field() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(
+            json!([{ "id": 1, "author.name": "Tolkien", "author": { "name": "Tolkien" } }]),
+            None,
+        )
+        .await;
+
+    let update = index.wait_update_id(0).await;
+    assert_eq!(update["status"], "failed", "{}", update);
+    assert_eq!(update["errorCode"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn partial_update_replaces_nested_objects() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(documents(), None).await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_documents(
+            json!([
+                { "id": 1, "author": { "name": "J. R. R. Tolkien" } },
+                { "id": 2, "author": null },
+            ]),
+            None,
+        )
+        .await;
+    let update = index.wait_update_id(1).await;
+    assert_eq!(update["status"], "processed", "{}", update);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "id": 1,
+            "title": "The Hobbit",
+            "author": { "name": "J. R. R. Tolkien" },
+            "specs": { "weight": 300 },
+            "tags": [{ "name": "fantasy" }],
+        })
+    );
+
+    let (response, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "id": 2,
+            "title": "Dune",
+            "author": null,
+            "specs": { "weight": 700 },
+            "tags": [],
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn partial_updates_of_a_document_in_one_payload() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(documents(), None).await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_documents(
+            json!([
+                { "id": 1, "author": { "name": "J. R. R. Tolkien" } },
+                { "id": 1, "title": "The Lord of the Rings" },
+            ]),
+            None,
+        )
+        .await;
+    let update = index.wait_update_id(1).await;
+    assert_eq!(update["status"], "processed", "{}", update);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "id": 1,
+            "title": "The Lord of the Rings",
+            "author": { "name": "J. R. R. Tolkien" },
+            "specs": { "weight": 300 },
+            "tags": [{ "name": "fantasy" }],
+        })
+    );
+
+    // The updates replacing no object are merged by milli, the nested objects are kept.
+    index
+        .update_documents(
+            json!([{ "id": 2, "title": "Dune Messiah" }, { "id": 2, "tags": ["sf"] }]),
+            None,
+        )
+        .await;
+    let update = index.wait_update_id(2).await;
+    assert_eq!(update["status"], "processed", "{}", update);
+
+    let (response, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response,
+        json!({
+            "id": 2,
+            "title": "Dune Messiah",
+            "author": { "name": "Herbert", "born": { "country": "USA" } },
+            "specs": { "weight": 700 },
+            "tags": ["sf"],
+        })
+    );
+}
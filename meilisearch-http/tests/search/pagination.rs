@@ -0,0 +1,85 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+async fn add_documents(server: &Server, count: u64) {
+    let index = server.index("test");
+    let documents: Vec<_> = (0..count)
+        .map(|id| json!({ "id": id, "title": "shirt" }))
+        .collect();
+    index.add_documents(json!(documents), None).await;
+    index.wait_update_id(0).await;
+}
+
+#[actix_rt::test]
+async fn search_by_page() {
+    let server = Server::new().await;
+    add_documents(&server, 25).await;
+    let index = server.index("test");
+
+    let (response, code) = index
+        .search_post(json!({ "q": "shirt", "page": 3, "hitsPerPage": 10 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["page"], 3);
+    assert_eq!(response["hitsPerPage"], 10);
+    assert_eq!(response["totalHits"], 25);
+    assert_eq!(response["totalPages"], 3);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 5);
+    assert_eq!(response["hits"][0]["id"], 20);
+
+    let (response, code) = index.search_post(json!({ "page": 0 })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([]));
+    assert_eq!(response["totalPages"], 2);
+
+    // The offset and limit don't report the totals.
+    let (response, code) = index.search_post(json!({ "limit": 10 })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("totalHits").is_none());
+    assert!(response.get("totalPages").is_none());
+}
+
+#[actix_rt::test]
+async fn max_total_hits() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    let (_, code) = index
+        .update_settings(json!({ "pagination": { "maxTotalHits": 12 } }))
+        .await;
+    assert_eq!(code, 202);
+    index.wait_update_id(0).await;
+
+    let documents: Vec<_> = (0..25).map(|id| json!({ "id": id })).collect();
+    index.add_documents(json!(documents), None).await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "page": 2, "hitsPerPage": 10 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["totalHits"], 12);
+    assert_eq!(response["totalPages"], 2);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+
+    let (response, code) = index
+        .search_post(json!({ "offset": 20, "limit": 10 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([]));
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200);
+    assert_eq!(response["pagination"], json!({ "maxTotalHits": 12 }));
+}
+
+#[actix_rt::test]
+async fn mixed_pagination() {
+    let server = Server::new().await;
+    add_documents(&server, 5).await;
+    let index = server.index("test");
+
+    let (response, code) = index.search_post(json!({ "page": 1, "limit": 10 })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
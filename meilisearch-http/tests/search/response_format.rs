@@ -0,0 +1,69 @@
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn search_response_formats() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "red shirt", "price": 10 },
+        { "id": 2, "title": "blue shirt, large" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    let url = "/indexes/test/search?q=shirt&attributesToRetrieve=title,id";
+
+    let (body, code) = server.service.get_accept(url, "application/x-ndjson").await;
+    assert_eq!(code, 200);
+    let lines: Vec<Value> = String::from_utf8(body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], json!({ "id": 1, "title": "red shirt" }));
+
+    let (body, code) = server.service.get_accept(url, "text/csv").await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        String::from_utf8(body).unwrap(),
+        "title,id\nred shirt,1\n\"blue shirt, large\",2\n"
+    );
+
+    let (body, code) = server.service.get_accept(url, "application/msgpack").await;
+    assert_eq!(code, 200);
+    let response: Value = rmp_serde::from_slice(&body).unwrap();
+    assert_eq!(response["nbHits"], 2);
+    assert_eq!(response["query"], "shirt");
+
+    let (body, code) = server.service.get_accept(url, "text/html").await;
+    assert_eq!(code, 200);
+    let response: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response["nbHits"], 2);
+}
+
+#[actix_rt::test]
+async fn browse_documents_as_csv() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "red shirt", "price": 10 },
+        { "id": 2, "title": "blue jeans", "color": "blue" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    let (body, code) = server
+        .service
+        .get_accept("/indexes/test/documents", "text/csv")
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        String::from_utf8(body).unwrap(),
+        "id,title,price,color\n1,red shirt,10,\n2,blue jeans,,blue\n"
+    );
+}
@@ -0,0 +1,96 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn search_with_defaults() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (_, code) = index
+        .update_settings(json!({
+            "searchDefaults": {
+                "attributesToRetrieve": ["title"],
+                "attributesToHighlight": ["title"],
+                "limit": 1
+            }
+        }))
+        .await;
+    assert_eq!(code, 202);
+    index.wait_update_id(0).await;
+
+    let documents = json!([
+        { "id": 1, "title": "red shirt", "color": "red" },
+        { "id": 2, "title": "blue shirt", "color": "blue" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index.search_post(json!({ "q": "shirt" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["limit"], 1);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        response["hits"][0],
+        json!({
+            "title": "red shirt",
+            "_formatted": { "title": "red <em>shirt</em>" }
+        })
+    );
+
+    // The parameters of the request win over the defaults.
+    let (response, code) = index
+        .search_post(json!({
+            "q": "shirt",
+            "limit": 2,
+            "attributesToRetrieve": ["id"],
+            "attributesToHighlight": []
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["hits"], json!([{ "id": 1 }, { "id": 2 }]));
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response["searchDefaults"],
+        json!({
+            "attributesToRetrieve": ["title"],
+            "attributesToHighlight": ["title"],
+            "limit": 1
+        })
+    );
+}
+
+#[actix_rt::test]
+async fn reset_search_defaults() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({ "searchDefaults": { "limit": 1 } }))
+        .await;
+    index.wait_update_id(0).await;
+
+    let (_, code) = server
+        .service
+        .delete("/indexes/test/settings/search-defaults")
+        .await;
+    assert_eq!(code, 202);
+    index.wait_update_id(1).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200);
+    assert_eq!(response["searchDefaults"], json!({}));
+}
+
+#[actix_rt::test]
+async fn invalid_search_defaults() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let (_, code) = index
+        .update_settings(json!({ "searchDefaults": { "q": "shirt" } }))
+        .await;
+    assert_eq!(code, 400);
+}
@@ -0,0 +1,67 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn search_with_structured_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "brand": "nike", "price": 10 },
+        { "id": 2, "brand": "adidas", "price": 15 },
+        { "id": 3, "brand": "nike", "price": 150 },
+        { "id": 4, "brand": "puma", "price": 5 },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["brand", "price"] }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "filter": {
+                "and": [
+                    { "field": "price", "op": "<", "value": 20 },
+                    { "or": [
+                        { "field": "brand", "op": "=", "value": "nike" },
+                        { "field": "brand", "op": "=", "value": "adidas" },
+                    ]},
+                ]
+            }
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 2);
+}
+
+#[actix_rt::test]
+async fn invalid_structured_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 1, "price": 10 }]), None)
+        .await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["price"] }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "filter": { "and": [{ "field": "price", "op": "<", "value": "cheap" }] }
+        }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "invalid_filter");
+    assert!(response["message"]
+        .as_str()
+        .unwrap()
+        .contains("`/and/0/value`"));
+}
@@ -0,0 +1,67 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn search_timeout_returns_partial_results() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "shirt", "in_stock": "false" },
+        { "id": 2, "title": "shirt", "in_stock": "true" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    index
+        .update_settings(json!({ "filterableAttributes": ["in_stock"] }))
+        .await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "shirt",
+            "boost": [{ "filter": "in_stock = true", "weight": 2.0 }],
+            "timeoutMs": 0
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["timedOut"], true);
+    assert_eq!(response["exhaustiveNbHits"], false);
+    assert_eq!(response["nbHits"], 2);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+
+    let (response, code) = index
+        .search_post(json!({ "q": "shirt", "timeoutMs": 60000 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("timedOut").is_none());
+}
+
+#[actix_rt::test]
+async fn search_timeout_stops_ranking() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents: Vec<_> = (0..50)
+        .map(|id| json!({ "id": id, "title": "shirt" }))
+        .collect();
+    index.add_documents(json!(documents), None).await;
+    index.wait_update_id(0).await;
+
+    let (response, code) = index
+        .search_post(json!({ "q": "shirt", "limit": 50, "timeoutMs": 0 }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["timedOut"], true);
+    assert_eq!(response["nbHits"], 50);
+    assert_eq!(response["hits"].as_array().unwrap().len(), 20);
+
+    let (response, code) = index
+        .search_post(json!({ "q": "shirt", "limit": 50, "timeoutMs": u64::MAX }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response.get("timedOut").is_none());
+    assert_eq!(response["hits"].as_array().unwrap().len(), 50);
+}
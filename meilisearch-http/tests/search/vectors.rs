@@ -0,0 +1,126 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+async fn add_documents_with_vectors(server: &Server) {
+    let index = server.index("test");
+    let documents = json!([
+        { "id": 1, "title": "red shirt", "_vectors": [1.0, 0.0, 0.0] },
+        { "id": 2, "title": "blue shirt", "_vectors": [0.0, 1.0, 0.0] },
+        { "id": 3, "title": "red dress", "_vectors": [0.9, 0.1, 0.0] },
+        { "id": 4, "title": "green hat" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+}
+
+fn ids(response: &serde_json::Value) -> Vec<u64> {
+    response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn vector_search() {
+    let server = Server::new().await;
+    add_documents_with_vectors(&server).await;
+    let index = server.index("test");
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.0, 0.0] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![1, 3, 2]);
+    assert_eq!(response["nbHits"], 3);
+    // The vectors are not returned with the documents.
+    assert!(response["hits"][0].get("_vectors").is_none());
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.0, 0.0], "filter": "id != 1" }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![3, 2]);
+}
+
+#[actix_rt::test]
+async fn hybrid_search() {
+    let server = Server::new().await;
+    add_documents_with_vectors(&server).await;
+    let index = server.index("test");
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "shirt",
+            "vector": [0.0, 1.0, 0.0],
+            "hybrid": { "semanticRatio": 0.0 }
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![1, 2]);
+
+    let (response, code) = index
+        .search_post(json!({
+            "q": "shirt",
+            "vector": [0.0, 1.0, 0.0],
+            "hybrid": { "semanticRatio": 0.9 }
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response)[0], 2);
+}
+
+#[actix_rt::test]
+async fn invalid_vector_search() {
+    let server = Server::new().await;
+    add_documents_with_vectors(&server).await;
+    let index = server.index("test");
+
+    let (response, code) = index.search_post(json!({ "vector": [1.0, 0.0] })).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "invalid_vector");
+
+    let (response, code) = index
+        .search_post(json!({ "q": "shirt", "hybrid": { "semanticRatio": 0.5 } }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "invalid_vector");
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.0, 0.0], "hybrid": { "semanticRatio": 2.0 } }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["errorCode"], "bad_parameter");
+}
+
+#[actix_rt::test]
+async fn deleted_documents_are_not_found() {
+    let server = Server::new().await;
+    add_documents_with_vectors(&server).await;
+    let index = server.index("test");
+
+    index.delete_document(1).await;
+    index.wait_update_id(1).await;
+
+    let (response, code) = index
+        .search_post(json!({ "vector": [1.0, 0.0, 0.0] }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), vec![3, 2]);
+}
+
+#[actix_rt::test]
+async fn vectors_of_different_dimensions() {
+    let server = Server::new().await;
+    add_documents_with_vectors(&server).await;
+    let index = server.index("test");
+
+    index
+        .add_documents(json!([{ "id": 5, "_vectors": [1.0, 0.0] }]), None)
+        .await;
+    let response = index.wait_update_id(1).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["errorCode"], "invalid_vector");
+}
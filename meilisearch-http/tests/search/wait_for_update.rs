@@ -0,0 +1,56 @@
+use serde_json::json;
+
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn read_after_update() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "shirt" },
+        { "id": 2, "title": "jeans" },
+    ]);
+    let (response, code) = index.add_documents(documents, None).await;
+    assert_eq!(code, 202, "{}", response);
+    let update_id = response["updateId"].as_u64().unwrap();
+
+    let url = format!("/indexes/test/search?waitForUpdateId={}", update_id);
+    let (response, code) = index.service.post(url, json!({ "q": "shirt" })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 1);
+
+    let url = format!("/indexes/test/search?q=jeans&waitForUpdateId={}", update_id);
+    let (response, code) = index.service.get(url).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["nbHits"], 1);
+
+    let (response, code) = index
+        .update_documents(json!([{ "id": 1, "color": "red" }]), None)
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let update_id = response["updateId"].as_u64().unwrap();
+
+    let url = format!("/indexes/test/documents/1?waitForUpdateId={}", update_id);
+    let (response, code) = index.service.get(url).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["color"], "red");
+
+    let url = format!("/indexes/test/documents?waitForUpdateId={}", update_id);
+    let (response, code) = index.service.get(url).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn wait_for_unexisting_update() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+
+    let (response, code) = index
+        .service
+        .post("/indexes/test/search?waitForUpdateId=12", json!({}))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+}
@@ -0,0 +1,136 @@
+use actix_http::ws::{CloseCode, Frame, Item, Message};
+use serde_json::{json, Value};
+
+use crate::common::server::default_settings;
+use crate::common::Server;
+
+use meilisearch_http::Opt;
+
+fn text(value: Value) -> Message {
+    Message::Text(value.to_string().into())
+}
+
+fn parse(frame: &Frame) -> Value {
+    match frame {
+        Frame::Text(text) => serde_json::from_slice(text).unwrap(),
+        other => panic!("unexpected frame: {:?}", other),
+    }
+}
+
+#[actix_rt::test]
+async fn search_as_you_type() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "title": "shirt" },
+        { "id": 2, "title": "shoes" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_update_id(0).await;
+
+    let messages = vec![
+        text(json!({ "q": "s" })),
+        text(json!({ "q": "sh" })),
+        text(json!({ "q": "shi" })),
+        Message::Close(None),
+    ];
+    let (frames, code) = server
+        .service
+        .websocket("/indexes/test/search/ws", messages)
+        .await;
+    assert_eq!(code, 101);
+
+    // The superseded queries may have been cancelled, but the last one is always answered.
+    let (last, results) = frames.split_last().unwrap();
+    assert!(matches!(last, Frame::Close(None)));
+    let response = parse(results.last().unwrap());
+    assert_eq!(response["query"], "shi");
+    assert_eq!(response["nbHits"], 1);
+    assert_eq!(response["hits"][0]["id"], 1);
+}
+
+#[actix_rt::test]
+async fn fragmented_query() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "title": "shirt" }]), None)
+        .await;
+    index.wait_update_id(0).await;
+
+    let query = json!({ "q": "shirt" }).to_string();
+    let (first, rest) = query.split_at(4);
+    let (middle, last) = rest.split_at(3);
+    let messages = vec![
+        Message::Continuation(Item::FirstText(first.to_string().into())),
+        Message::Continuation(Item::Continue(middle.to_string().into())),
+        Message::Continuation(Item::Last(last.to_string().into())),
+        Message::Close(None),
+    ];
+    let (frames, code) = server
+        .service
+        .websocket("/indexes/test/search/ws", messages)
+        .await;
+    assert_eq!(code, 101);
+
+    assert_eq!(frames.len(), 2, "{:?}", frames);
+    let response = parse(&frames[0]);
+    assert_eq!(response["query"], "shirt");
+    assert_eq!(response["nbHits"], 1);
+}
+
+#[actix_rt::test]
+async fn invalid_query_message() {
+    let server = Server::new().await;
+    server.index("test").create(None).await;
+
+    let messages = vec![text(json!({ "unknown": true })), Message::Close(None)];
+    let (frames, _) = server
+        .service
+        .websocket("/indexes/test/search/ws", messages)
+        .await;
+
+    assert_eq!(frames.len(), 2);
+    assert_eq!(parse(&frames[0])["errorCode"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn authentication_handshake() {
+    let temp = tempfile::tempdir().unwrap();
+    let options = Opt {
+        master_key: Some("masterKey".to_string()),
+        ..default_settings(temp.path())
+    };
+    let server = Server::new_with_options(options).await;
+
+    let messages = vec![
+        text(json!({ "apiKey": "wrongKey" })),
+        text(json!({ "q": "" })),
+    ];
+    let (frames, _) = server
+        .service
+        .websocket("/indexes/test/search/ws", messages)
+        .await;
+
+    assert_eq!(frames.len(), 2);
+    assert_eq!(parse(&frames[0])["errorCode"], "invalid_token");
+    match &frames[1] {
+        Frame::Close(Some(reason)) => assert_eq!(reason.code, CloseCode::Policy),
+        other => panic!("unexpected frame: {:?}", other),
+    }
+
+    let messages = vec![
+        text(json!({ "apiKey": "masterKey" })),
+        text(json!({ "q": "" })),
+        Message::Close(None),
+    ];
+    let (frames, _) = server
+        .service
+        .websocket("/indexes/test/search/ws", messages)
+        .await;
+
+    // The connection is authenticated, the search fails because the index doesn't exist.
+    assert_eq!(frames.len(), 2);
+    assert_eq!(parse(&frames[0])["errorCode"], "index_not_found");
+}
@@ -17,6 +17,11 @@ static DEFAULT_SETTINGS_VALUES: Lazy<HashMap<&'static str, Value>> = Lazy::new(|
     );
     map.insert("stop_words", json!([]));
     map.insert("synonyms", json!({}));
+    map.insert("hierarchical_facets", json!({}));
+    map.insert("date_attributes", json!([]));
+    map.insert("search_defaults", json!({}));
+    map.insert("pagination", json!({ "maxTotalHits": 1000 }));
+    map.insert("document_schema", json!(null));
     map
 });
 
@@ -35,7 +40,7 @@ async fn get_settings() {
     let (response, code) = index.settings().await;
     assert_eq!(code, 200);
     let settings = response.as_object().unwrap();
-    assert_eq!(settings.keys().len(), 7);
+    assert_eq!(settings.keys().len(), 12);
     assert_eq!(settings["displayedAttributes"], json!(["*"]));
     assert_eq!(settings["searchableAttributes"], json!(["*"]));
     assert_eq!(settings["filterableAttributes"], json!([]));
@@ -45,6 +50,11 @@ async fn get_settings() {
         json!(["words", "typo", "proximity", "attribute", "exactness"])
     );
     assert_eq!(settings["stopWords"], json!([]));
+    assert_eq!(settings["hierarchicalFacets"], json!({}));
+    assert_eq!(settings["dateAttributes"], json!([]));
+    assert_eq!(settings["searchDefaults"], json!({}));
+    assert_eq!(settings["pagination"], json!({ "maxTotalHits": 1000 }));
+    assert_eq!(settings["documentSchema"], json!(null));
 }
 
 #[actix_rt::test]
@@ -229,5 +239,10 @@ test_setting_routes!(
     distinct_attribute,
     stop_words,
     ranking_rules,
-    synonyms
+    synonyms,
+    hierarchical_facets,
+    date_attributes,
+    search_defaults,
+    pagination,
+    document_schema
 );